@@ -41,6 +41,9 @@ use std::{
 };
 
 // Our native executor instance.
+// Once the runtime includes the liveness pallet, its attestation reports are verified by
+// `pallet_liveness::remote_attestation::HostFunctions`, registered here along with the
+// benchmarking host functions.
 native_executor_instance!(
     pub Executor,
     runtime::api::dispatch,
@@ -1,20 +1,143 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-attestor
 
 use super::*;
 
-#[allow(unused)]
-use crate::Module as Template;
-use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use crate::Pallet as Attestor;
+use codec::{Decode, Encode};
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::Currency;
 use frame_system::RawOrigin;
+use primitives::BlockNumber;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{traits::Bounded, SaturatedConversion};
+use sp_std::prelude::*;
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"atst");
+const SEED: u32 = 0;
+
+/// A new key, the current block and the signature proving the possession of the key by
+/// `attestor`.
+fn key_proof<T: Config>(attestor: &T::AccountId) -> (Vec<u8>, BlockNumber, [u8; 64]) {
+    let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+    let block_number = frame_system::Pallet::<T>::block_number().saturated_into::<BlockNumber>();
+    let payload = Attestor::<T>::key_proof_payload(attestor, &key.0, block_number);
+    let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &key, &payload).unwrap();
+    (key.0.to_vec(), block_number, signature.0)
+}
+
+fn register<T: Config>(attestor: &T::AccountId) {
+    T::Currency::make_free_balance_be(attestor, BalanceOf::<T>::max_value() / 2u32.into());
+    let (pubkey, block_number, signature) = key_proof::<T>(attestor);
+    Attestor::<T>::attestor_register(
+        RawOrigin::Signed(attestor.clone()).into(),
+        vec![1u8; 64],
+        pubkey,
+        block_number,
+        signature,
+    )
+    .unwrap();
+}
 
 benchmarks! {
-    do_something {
-        let s in 0 .. 100;
+    attestor_register {
+        let caller: T::AccountId = whitelisted_caller();
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+        let (pubkey, block_number, signature) = key_proof::<T>(&caller);
+    }: _(RawOrigin::Signed(caller.clone()), vec![1u8; 64], pubkey, block_number, signature)
+    verify {
+        assert!(Attestors::<T>::contains_key(&caller));
+    }
+
+    attestor_update {
+        let caller: T::AccountId = whitelisted_caller();
+        register::<T>(&caller);
+    }: _(RawOrigin::Signed(caller.clone()), vec![3u8; 64])
+    verify {
+        assert_eq!(Attestors::<T>::get(&caller).url, vec![3u8; 64]);
+    }
+
+    attestor_update_metadata {
         let caller: T::AccountId = whitelisted_caller();
-    }: _(RawOrigin::Signed(caller), s)
+        register::<T>(&caller);
+        let metadata = AttestorMetadata {
+            region: vec![1u8; MAX_METADATA_LENGTH],
+            version: vec![2u8; MAX_METADATA_LENGTH],
+            capacity: 100,
+        };
+    }: _(RawOrigin::Signed(caller.clone()), metadata.clone())
+    verify {
+        assert_eq!(Metadata::<T>::get(&caller), metadata);
+    }
+
+    attestor_rotate_key {
+        // the worst case checks a full key history
+        let caller: T::AccountId = whitelisted_caller();
+        register::<T>(&caller);
+        for _ in 1..MAX_KEY_HISTORY {
+            let (pubkey, block_number, signature) = key_proof::<T>(&caller);
+            Attestor::<T>::attestor_rotate_key(
+                RawOrigin::Signed(caller.clone()).into(),
+                pubkey,
+                block_number,
+                signature,
+            )?;
+        }
+        let (pubkey, block_number, signature) = key_proof::<T>(&caller);
+    }: _(RawOrigin::Signed(caller.clone()), pubkey.clone(), block_number, signature)
+    verify {
+        assert_eq!(Attestors::<T>::get(&caller).pubkey, pubkey);
+    }
+
+    attestor_bond {
+        let caller: T::AccountId = whitelisted_caller();
+        register::<T>(&caller);
+        let stake = AttestorStake::<T>::get(&caller);
+    }: _(RawOrigin::Signed(caller.clone()), 1000u32.into())
+    verify {
+        assert_eq!(AttestorStake::<T>::get(&caller), stake + 1000u32.into());
+    }
+
+    attestor_notify_chain {
+        let g in 0 .. MAX_ATTESTOR_GEODES;
+        let pubkey = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+        let attestor = T::AccountId::decode(&mut &pubkey.0[..]).unwrap_or_default();
+        register::<T>(&attestor);
+        Attestors::<T>::mutate(&attestor, |record| {
+            for i in 0..g {
+                record.geodes.insert(account("geode", i, SEED));
+            }
+        });
+
+        frame_system::Pallet::<T>::set_block_number(10u32.into());
+        let message = HeartbeatOf::<T> {
+            version: HEARTBEAT_VERSION,
+            attestor: pubkey.0,
+            block_number: 9,
+            block_hash: frame_system::Pallet::<T>::block_hash(T::BlockNumber::from(9u32)),
+            nonce: 1,
+            geode_digest: Attestor::<T>::geode_digest(&Attestors::<T>::get(&attestor).geodes),
+            results: vec![0xff; (g as usize + 7) / 8],
+        }
+        .encode();
+        let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &pubkey, &message).unwrap();
+    }: _(RawOrigin::None, message, signature.0)
+    verify {
+        assert_eq!(AttestorLastNotify::<T>::get(&attestor), 10);
+        assert_eq!(HeartbeatNonces::<T>::get(&attestor), 1);
+    }
+
+    set_att_stake_min {
+        let stake: BalanceOf<T> = 1000u32.into();
+    }: _(RawOrigin::Root, stake)
+    verify {
+        assert_eq!(AttStakeMin::<T>::get(), stake);
+    }
+
+    set_key_overlap {
+    }: _(RawOrigin::Root, 100)
     verify {
-        assert_eq!(Something::<T>::get(), Some(s));
+        assert_eq!(KeyOverlap::<T>::get(), 100);
     }
 }
 
-impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test,);
+impl_benchmark_test_suite!(Attestor, crate::mock::new_test_ext(), crate::mock::Test,);
@@ -11,8 +11,13 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod weights;
+pub use weights::WeightInfo;
+
 #[frame_support::pallet]
 pub mod pallet {
+    use crate::weights::WeightInfo;
+    use core::convert::TryFrom;
     use frame_support::traits::{Currency, ReservableCurrency};
     use frame_support::{
         dispatch::DispatchResultWithPostInfo, pallet_prelude::*, unsigned::ValidateUnsigned,
@@ -22,12 +27,8 @@ pub mod pallet {
         pallet_prelude::*,
     };
     use primitives::BlockNumber;
-    #[cfg(feature = "full_crypto")]
-    use sp_core::crypto::Pair;
-    #[cfg(feature = "full_crypto")]
-    use sp_core::sr25519::Pair as Sr25519Pair;
     use sp_core::sr25519::{Public, Signature};
-    use sp_runtime::{RuntimeDebug, SaturatedConversion};
+    use sp_runtime::{traits::Hash, RuntimeDebug, SaturatedConversion};
     use sp_std::collections::btree_set::BTreeSet;
     use sp_std::prelude::*;
 
@@ -36,18 +37,124 @@ pub mod pallet {
     pub struct Attestor<AccountId: Ord> {
         /// Attestor's url, geode will get it and communicate with attestor.
         pub url: Vec<u8>,
-        /// Attestor's Secp256r1PublicKey
+        /// Attestor's current sr25519 public key.
         pub pubkey: Vec<u8>,
         /// Geode being attested by this attestor
         pub geodes: BTreeSet<AccountId>,
     }
 
-    type BalanceOf<T> =
+    /// Metadata an attestor declares about itself.
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct AttestorMetadata {
+        /// Region the attestor runs in, e.g. `us-east`.
+        pub region: Vec<u8>,
+        /// Version of the attestor software.
+        pub version: Vec<u8>,
+        /// Max number of geodes the attestor is willing to attest, 0 for
+        /// `MAX_ATTESTOR_GEODES`.
+        pub capacity: u32,
+    }
+
+    /// An sr25519 key used by an attestor and the blocks it is valid for. It signs the
+    /// attestor's heartbeats and misconduct evidence, as the attestor's account key does.
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct AttestorKey {
+        pub pubkey: Vec<u8>,
+        /// Block the key became valid at.
+        pub activated: BlockNumber,
+        /// Block the key stops being valid at, `None` while it is the current key.
+        pub expires: Option<BlockNumber>,
+    }
+
+    impl AttestorKey {
+        /// Whether the key was valid at `block_number`.
+        pub fn valid_at(&self, block_number: BlockNumber) -> bool {
+            self.activated <= block_number
+                && self.expires.map_or(true, |expires| block_number < expires)
+        }
+    }
+
+    /// Heartbeat an attestor signs with its account key, or one of its sr25519 keys valid at
+    /// the block the heartbeat is bound to, to show the chain it is alive.
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct Heartbeat<Hash> {
+        /// Format of the heartbeat, `HEARTBEAT_VERSION`.
+        pub version: u8,
+        /// Attestor's account key.
+        pub attestor: [u8; 32],
+        /// A recent block the heartbeat is bound to, older heartbeats are rejected.
+        pub block_number: BlockNumber,
+        pub block_hash: Hash,
+        /// Above the nonce of the last heartbeat of the attestor.
+        pub nonce: u64,
+        /// Hash of the geodes the attestor attests, see `geode_digest`.
+        pub geode_digest: Hash,
+        /// Bitmap of the geodes which passed their last challenge, in the order of the
+        /// attestor's geodes, lowest bit of the first byte first.
+        pub results: Vec<u8>,
+    }
+
+    /// Notified of the results of the geodes carried by attestor heartbeats.
+    pub trait OnHeartbeat<AccountId> {
+        fn on_heartbeat(attestor: &AccountId, results: &[(AccountId, bool)]);
+    }
+
+    impl<AccountId> OnHeartbeat<AccountId> for () {
+        fn on_heartbeat(_attestor: &AccountId, _results: &[(AccountId, bool)]) {}
+    }
+
+    /// Gives the geodes assigned to attestors which they don't attest yet.
+    pub trait AssignmentRegistry<AccountId> {
+        /// Number of geodes assigned to the attestor which it doesn't attest yet.
+        fn assigned_geodes(attestor: &AccountId) -> u32;
+    }
+
+    impl<AccountId> AssignmentRegistry<AccountId> for () {
+        fn assigned_geodes(_attestor: &AccountId) -> u32 {
+            0
+        }
+    }
+
+    /// Tells which attestors attest which geodes, e.g. to only accept reports about a geode
+    /// from its attestors.
+    pub trait AttestorRegistry<AccountId> {
+        /// Whether the attestor is attesting the geode.
+        fn is_attesting(attestor: &AccountId, geode: &AccountId) -> bool;
+    }
+
+    impl<AccountId> AttestorRegistry<AccountId> for () {
+        fn is_attesting(_attestor: &AccountId, _geode: &AccountId) -> bool {
+            false
+        }
+    }
+
+    pub type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
     pub type AttestorOf<T> = Attestor<<T as frame_system::Config>::AccountId>;
+    pub type HeartbeatOf<T> = Heartbeat<<T as frame_system::Config>::Hash>;
+    pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::NegativeImbalance;
 
     pub const UNSIGNED_TXS_PRIORITY: u64 = 100;
     pub const DEFAULT_ATT_STAKE_MIN: primitives::Balance = 1000;
+    /// Max length of the region and version in the attestor metadata.
+    pub const MAX_METADATA_LENGTH: usize = 64;
+    /// Blocks a proof of possession of a new attestor key is accepted for.
+    pub const KEY_PROOF_MAX_AGE: BlockNumber = 10;
+    /// Context of the payload proving the possession of a new attestor key.
+    pub const KEY_PROOF_CONTEXT: &[u8] = b"automata/attestor-key";
+    /// Max number of keys kept in the key history of an attestor.
+    pub const MAX_KEY_HISTORY: usize = 16;
+    /// Blocks a rotated key stays valid for by default, about an hour.
+    pub const DEFAULT_KEY_OVERLAP: BlockNumber = 600;
+    /// Version of the heartbeat format accepted by `attestor_notify_chain`.
+    pub const HEARTBEAT_VERSION: u8 = 2;
+    /// Max number of blocks between the block a heartbeat is bound to and its inclusion.
+    pub const HEARTBEAT_MAX_AGE: BlockNumber = 10;
+    /// Max number of geodes an attestor attests or is assigned to, whatever capacity it
+    /// declares.
+    pub const MAX_ATTESTOR_GEODES: u32 = 500;
 
     /// Configure the pallet by specifying the parameters and types on which it depends.
     #[pallet::config]
@@ -57,6 +164,12 @@ pub mod pallet {
         /// The currency in which fees are paid and contract balances are held.
         type Currency: ReservableCurrency<Self::AccountId>;
         type Call: From<Call<Self>>;
+        /// Handler of the geode results in heartbeats, e.g. liveness scoring geode health.
+        type OnHeartbeat: OnHeartbeat<Self::AccountId>;
+        /// Geodes assigned to attestors, counted in their load, e.g. liveness.
+        type Assignments: AssignmentRegistry<Self::AccountId>;
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::pallet]
@@ -79,6 +192,52 @@ pub mod pallet {
     pub type AttestorLastNotify<T: Config> =
         StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumber, ValueQuery>;
 
+    /// Attestors indexed by the block they notified the chain. Entries are left behind when
+    /// attestors notify again or exit, consumers have to check them against
+    /// `AttestorLastNotify`.
+    #[pallet::storage]
+    #[pallet::getter(fn notify_queue)]
+    pub type NotifyQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    /// Balance reserved by each attestor, the min stake at registration plus what it bonded
+    /// since. What is left of it after slashes is released once the attestor is removed,
+    /// whether done unbonding or for missing heartbeats.
+    #[pallet::storage]
+    #[pallet::getter(fn attestor_stake)]
+    pub type AttestorStake<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn attestor_metadata)]
+    pub type Metadata<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, AttestorMetadata, ValueQuery>;
+
+    /// Keys used by each attestor, oldest first, the last one is the current key. Kept after
+    /// the attestor is removed so reports signed with its keys can still be verified.
+    #[pallet::storage]
+    #[pallet::getter(fn key_history)]
+    pub type KeyHistory<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Vec<AttestorKey>, ValueQuery>;
+
+    /// Nonce of the last heartbeat of each attestor, kept after the attestor is removed so
+    /// that its heartbeats can't be replayed if it registers again.
+    #[pallet::storage]
+    #[pallet::getter(fn heartbeat_nonce)]
+    pub type HeartbeatNonces<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+    #[pallet::type_value]
+    pub(super) fn DefaultKeyOverlap<T: Config>() -> BlockNumber {
+        DEFAULT_KEY_OVERLAP
+    }
+
+    /// Blocks the previous key of an attestor stays valid for after a rotation.
+    #[pallet::storage]
+    #[pallet::getter(fn key_overlap)]
+    pub(super) type KeyOverlap<T: Config> =
+        StorageValue<_, BlockNumber, ValueQuery, DefaultKeyOverlap<T>>;
+
     #[pallet::type_value]
     pub(super) fn DefaultAttStakeMin<T: Config>() -> BalanceOf<T> {
         T::Currency::minimum_balance()
@@ -101,7 +260,7 @@ pub mod pallet {
     // Pallets use events to inform users when important changes are made.
     // https://substrate.dev/docs/en/knowledgebase/runtime/events
     #[pallet::event]
-    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T> = "Balance")]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         /// Attestor registered. \[attestor_id\]
@@ -110,6 +269,17 @@ pub mod pallet {
         AttestorRemove(T::AccountId),
         /// Attestor's url updated. \[attestor_id\]
         AttestorUpdate(T::AccountId),
+        /// Attestor reserved more stake. \[attestor_id, value\]
+        AttestorBond(T::AccountId, BalanceOf<T>),
+        /// Attestor's stake released after unbonding. \[attestor_id, value\]
+        AttestorUnbond(T::AccountId, BalanceOf<T>),
+        /// Attestor's stake slashed for misbehaving. \[attestor_id, amount\]
+        StakeSlash(T::AccountId, BalanceOf<T>),
+        /// Attestor's metadata updated. \[attestor_id\]
+        AttestorMetadataUpdate(T::AccountId),
+        /// Attestor rotated its pubkey, the previous one expires at the block.
+        /// \[attestor_id, expires\]
+        AttestorKeyRotate(T::AccountId, BlockNumber),
         /// Event documentation should end with an array that provides descriptive names for event
         /// parameters. [something, who]
         SomethingStored(u32, T::AccountId),
@@ -124,6 +294,24 @@ pub mod pallet {
         AlreadyRegistered,
         /// Invalid notification input.
         InvalidNotification,
+        /// The notification isn't signed by the attestor.
+        InvalidNotificationSignature,
+        /// The notification is bound to an unknown or too old block.
+        StaleNotification,
+        /// The notification nonce isn't above the last one.
+        ReplayedNotification,
+        /// The notification doesn't match the geodes the attestor attests.
+        GeodeDigestMismatch,
+        /// Region or version in the metadata is too long.
+        InvalidMetadata,
+        /// The pubkey was already used by the attestor.
+        KeyAlreadyUsed,
+        /// The pubkey isn't an sr25519 public key.
+        InvalidPubkey,
+        /// The new key didn't sign `key_proof_payload` for a recent block.
+        InvalidKeyProof,
+        /// The attestor attests as many geodes as its declared capacity.
+        AttestorAtCapacity,
     }
 
     #[pallet::validate_unsigned]
@@ -133,35 +321,28 @@ pub mod pallet {
         fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
             match call {
                 Call::attestor_notify_chain(message, signature_raw_bytes) => {
-                    // validate inputs
-                    if message.len() != 40 {
-                        return InvalidTransaction::Call.into();
-                    }
-
-                    let mut attestor = [0u8; 32];
-                    attestor.copy_from_slice(&message[0..32]);
-
-                    let pubkey = Public::from_raw(attestor.clone());
-                    let signature = Signature::from_raw(signature_raw_bytes.clone());
-
-                    #[cfg(feature = "full_crypto")]
-                    if !Sr25519Pair::verify(&signature, message, &pubkey) {
-                        return InvalidTransaction::Call.into();
-                    }
-
-                    let acc = T::AccountId::decode(&mut &attestor[..]).unwrap_or_default();
-                    if !<Attestors<T>>::contains_key(acc) {
-                        return InvalidTransaction::Call.into();
-                    }
+                    let (_, heartbeat) = Self::check_heartbeat(message, signature_raw_bytes)
+                        .map_err(|err| match err {
+                            Error::<T>::InvalidNotificationSignature => {
+                                InvalidTransaction::BadProof
+                            }
+                            Error::<T>::StaleNotification | Error::<T>::ReplayedNotification => {
+                                InvalidTransaction::Stale
+                            }
+                            _ => InvalidTransaction::Call,
+                        })?;
+
+                    // valid until the block it is bound to gets too old
+                    let block_number =
+                        <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+                    let longevity = (heartbeat.block_number + HEARTBEAT_MAX_AGE)
+                        .saturating_sub(block_number)
+                        .max(1);
 
                     ValidTransaction::with_tag_prefix("Automata/attestor/notify")
                         .priority(UNSIGNED_TXS_PRIORITY)
-                        .and_provides((
-                            attestor,
-                            <frame_system::Pallet<T>>::block_number()
-                                .saturated_into::<BlockNumber>(),
-                        ))
-                        .longevity(3)
+                        .and_provides((heartbeat.attestor, heartbeat.nonce))
+                        .longevity(longevity as u64)
                         .propagate(true)
                         .build()
                 }
@@ -178,20 +359,29 @@ pub mod pallet {
     // Dispatchable functions must be annotated with a weight and must return a DispatchResult.
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Register as an attestor.
-        #[pallet::weight(0)]
+        /// Register as an attestor, with the signature of `key_proof_payload` by the sr25519
+        /// `pubkey` for a recent `block_number` to prove its possession.
+        #[pallet::weight(T::WeightInfo::attestor_register())]
         pub fn attestor_register(
             origin: OriginFor<T>,
             url: Vec<u8>,
             pubkey: Vec<u8>,
+            block_number: BlockNumber,
+            signature: [u8; 64],
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             ensure!(
                 !<Attestors<T>>::contains_key(&who),
                 Error::<T>::AlreadyRegistered
             );
+            Self::check_key_proof(&who, &pubkey, block_number, &signature)?;
             let limit = <AttStakeMin<T>>::get();
             T::Currency::reserve(&who, limit)?;
+            <AttestorStake<T>>::mutate(&who, |stake| *stake += limit);
+
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            Self::push_key(&who, pubkey.clone(), block_number);
 
             let attestor = AttestorOf::<T> {
                 url,
@@ -200,9 +390,8 @@ pub mod pallet {
             };
             <Attestors<T>>::insert(&who, attestor);
 
-            let block_number =
-                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
             <AttestorLastNotify<T>>::insert(&who, block_number);
+            <NotifyQueue<T>>::append(block_number, &who);
 
             <AttestorNum<T>>::put(<AttestorNum<T>>::get() + 1);
 
@@ -211,7 +400,7 @@ pub mod pallet {
         }
 
         /// Called by attestor to update its url.
-        #[pallet::weight(0)]
+        #[pallet::weight(T::WeightInfo::attestor_update())]
         pub fn attestor_update(origin: OriginFor<T>, url: Vec<u8>) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             ensure!(
@@ -225,42 +414,109 @@ pub mod pallet {
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
-        pub fn attestor_notify_chain(
-            _origin: OriginFor<T>,
-            message: Vec<u8>,
-            signature_raw_bytes: [u8; 64],
+        /// Called by attestor to update its metadata.
+        #[pallet::weight(T::WeightInfo::attestor_update_metadata())]
+        pub fn attestor_update_metadata(
+            origin: OriginFor<T>,
+            metadata: AttestorMetadata,
         ) -> DispatchResultWithPostInfo {
-            // validate inputs
-            ensure!(message.len() == 40, Error::<T>::InvalidNotification);
-
-            let mut attestor = [0u8; 32];
-            attestor.copy_from_slice(&message[0..32]);
-
-            let pubkey = Public::from_raw(attestor.clone());
-            let signature = Signature::from_raw(signature_raw_bytes.clone());
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <Attestors<T>>::contains_key(&who),
+                Error::<T>::InvalidAttestor
+            );
+            ensure!(
+                metadata.region.len() <= MAX_METADATA_LENGTH
+                    && metadata.version.len() <= MAX_METADATA_LENGTH,
+                Error::<T>::InvalidMetadata
+            );
+            <Metadata<T>>::insert(&who, metadata);
+            Self::deposit_event(Event::AttestorMetadataUpdate(who));
+            Ok(().into())
+        }
 
-            #[cfg(feature = "full_crypto")]
+        /// Called by attestor to rotate its pubkey, proving the possession of the new one as
+        /// `attestor_register` does. The previous key stays valid for `KeyOverlap` blocks so
+        /// reports signed in flight are still accepted.
+        #[pallet::weight(T::WeightInfo::attestor_rotate_key())]
+        pub fn attestor_rotate_key(
+            origin: OriginFor<T>,
+            pubkey: Vec<u8>,
+            block_number: BlockNumber,
+            signature: [u8; 64],
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
             ensure!(
-                Sr25519Pair::verify(&signature, message, &pubkey),
-                Error::<T>::InvalidNotification
+                <Attestors<T>>::contains_key(&who),
+                Error::<T>::InvalidAttestor
+            );
+            Self::check_key_proof(&who, &pubkey, block_number, &signature)?;
+            ensure!(
+                !<KeyHistory<T>>::get(&who)
+                    .iter()
+                    .any(|key| key.pubkey == pubkey),
+                Error::<T>::KeyAlreadyUsed
             );
 
-            let acc = T::AccountId::decode(&mut &attestor[..]).unwrap_or_default();
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            let expires = block_number + <KeyOverlap<T>>::get();
+            Self::retire_key(&who, expires);
+            Self::push_key(&who, pubkey.clone(), block_number);
+
+            <Attestors<T>>::mutate(&who, |attestor| attestor.pubkey = pubkey);
+            Self::deposit_event(Event::AttestorKeyRotate(who, expires));
+            Ok(().into())
+        }
+
+        /// Called by attestor to reserve more stake, weighing its misconduct reports more.
+        #[pallet::weight(T::WeightInfo::attestor_bond())]
+        pub fn attestor_bond(
+            origin: OriginFor<T>,
+            value: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
             ensure!(
-                <Attestors<T>>::contains_key(&acc),
+                <Attestors<T>>::contains_key(&who),
                 Error::<T>::InvalidAttestor
             );
+            T::Currency::reserve(&who, value)?;
+            <AttestorStake<T>>::mutate(&who, |stake| *stake += value);
+            Self::deposit_event(Event::AttestorBond(who, value));
+            Ok(().into())
+        }
+
+        /// Called by attestor through an unsigned transaction to show it is alive, along with
+        /// the results of its geodes. The message is an encoded `Heartbeat` signed by the
+        /// attestor's account key. The weight assumes the attestor attests
+        /// `MAX_ATTESTOR_GEODES` geodes, the actual weight is refunded after dispatch.
+        #[pallet::weight(T::WeightInfo::attestor_notify_chain(MAX_ATTESTOR_GEODES))]
+        pub fn attestor_notify_chain(
+            _origin: OriginFor<T>,
+            message: Vec<u8>,
+            signature_raw_bytes: [u8; 64],
+        ) -> DispatchResultWithPostInfo {
+            let (acc, heartbeat) = Self::check_heartbeat(&message, &signature_raw_bytes)?;
+            <HeartbeatNonces<T>>::insert(&acc, heartbeat.nonce);
 
             let block_number =
                 <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
             <AttestorLastNotify<T>>::insert(&acc, block_number);
+            <NotifyQueue<T>>::append(block_number, &acc);
 
-            Ok(().into())
+            let results = <Attestors<T>>::get(&acc)
+                .geodes
+                .into_iter()
+                .enumerate()
+                .map(|(i, geode)| (geode, heartbeat.results[i / 8] & (1 << (i % 8)) != 0))
+                .collect::<Vec<_>>();
+            T::OnHeartbeat::on_heartbeat(&acc, &results);
+
+            Ok(Some(T::WeightInfo::attestor_notify_chain(results.len() as u32)).into())
         }
 
         /// Called by root to set the min stake
-        #[pallet::weight(0)]
+        #[pallet::weight(T::WeightInfo::set_att_stake_min())]
         pub fn set_att_stake_min(
             origin: OriginFor<T>,
             stake: BalanceOf<T>,
@@ -269,6 +525,17 @@ pub mod pallet {
             <AttStakeMin<T>>::put(stake);
             Ok(().into())
         }
+
+        /// Called by root to set the blocks a rotated key stays valid for
+        #[pallet::weight(T::WeightInfo::set_key_overlap())]
+        pub fn set_key_overlap(
+            origin: OriginFor<T>,
+            overlap: BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            let _who = ensure_root(origin)?;
+            <KeyOverlap<T>>::put(overlap);
+            Ok(().into())
+        }
     }
 
     impl<T: Config> Pallet<T> {
@@ -280,6 +547,72 @@ pub mod pallet {
             SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
         }
 
+        /// Check a heartbeat message: its version, the signature of the attestor, the recent
+        /// block it is bound to, its nonce against the last one of the attestor, and the
+        /// digest of the geodes the attestor attests. Return the attestor and the heartbeat.
+        pub fn check_heartbeat(
+            message: &[u8],
+            signature_raw_bytes: &[u8; 64],
+        ) -> Result<(T::AccountId, HeartbeatOf<T>), Error<T>> {
+            let heartbeat = HeartbeatOf::<T>::decode(&mut &message[..])
+                .map_err(|_| Error::<T>::InvalidNotification)?;
+            ensure!(
+                heartbeat.version == HEARTBEAT_VERSION,
+                Error::<T>::InvalidNotification
+            );
+
+            let acc = T::AccountId::decode(&mut &heartbeat.attestor[..])
+                .map_err(|_| Error::<T>::InvalidAttestor)?;
+            let signature = Signature::from_raw(*signature_raw_bytes);
+            let signed_by = |pubkey: &[u8]| {
+                Public::try_from(pubkey).map_or(false, |pubkey| {
+                    sp_io::crypto::sr25519_verify(&signature, message, &pubkey)
+                })
+            };
+            ensure!(
+                signed_by(&heartbeat.attestor)
+                    || <KeyHistory<T>>::get(&acc)
+                        .iter()
+                        .any(|key| key.valid_at(heartbeat.block_number) && signed_by(&key.pubkey)),
+                Error::<T>::InvalidNotificationSignature
+            );
+            ensure!(
+                <Attestors<T>>::contains_key(&acc),
+                Error::<T>::InvalidAttestor
+            );
+
+            // bound to one of the recent blocks, not the current one as its hash is unknown yet
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            ensure!(
+                heartbeat.block_number < block_number
+                    && block_number - heartbeat.block_number <= HEARTBEAT_MAX_AGE
+                    && <frame_system::Pallet<T>>::block_hash(
+                        heartbeat.block_number.saturated_into::<T::BlockNumber>()
+                    ) == heartbeat.block_hash,
+                Error::<T>::StaleNotification
+            );
+            ensure!(
+                heartbeat.nonce > <HeartbeatNonces<T>>::get(&acc),
+                Error::<T>::ReplayedNotification
+            );
+            let geodes = <Attestors<T>>::get(&acc).geodes;
+            ensure!(
+                heartbeat.geode_digest == Self::geode_digest(&geodes),
+                Error::<T>::GeodeDigestMismatch
+            );
+            ensure!(
+                heartbeat.results.len() == (geodes.len() + 7) / 8,
+                Error::<T>::InvalidNotification
+            );
+            Ok((acc, heartbeat))
+        }
+
+        /// Hash of a set of geodes, as attestors put it in their heartbeats.
+        pub fn geode_digest(geodes: &BTreeSet<T::AccountId>) -> T::Hash {
+            T::Hashing::hash_of(geodes)
+        }
+
         /// Return attestors' url and pubkey list for rpc.
         pub fn attestor_list() -> Vec<(Vec<u8>, Vec<u8>, u32)> {
             let mut res = Vec::<(Vec<u8>, Vec<u8>, u32)>::new();
@@ -295,6 +628,25 @@ pub mod pallet {
             res
         }
 
+        /// Return each attestor's number of attested and assigned geodes and declared capacity
+        /// for rpc.
+        pub fn attestor_loads() -> Vec<(T::AccountId, u32, u32)> {
+            <Attestors<T>>::iter()
+                .map(|(id, attestor)| {
+                    let capacity = <Metadata<T>>::get(&id).capacity;
+                    let load = attestor.geodes.len() as u32 + T::Assignments::assigned_geodes(&id);
+                    (id, load, capacity)
+                })
+                .collect()
+        }
+
+        /// Whether the attestor can take one more geode on top of the `load` geodes it attests
+        /// or is assigned.
+        pub fn has_capacity(attestor: &T::AccountId, load: usize) -> bool {
+            let capacity = <Metadata<T>>::get(attestor).capacity;
+            load < MAX_ATTESTOR_GEODES as usize && (capacity == 0 || load < capacity as usize)
+        }
+
         /// Return list of attestors of a geode
         pub fn attestors_of_geode(geode: T::AccountId) -> Vec<(Vec<u8>, Vec<u8>)> {
             let mut res = Vec::new();
@@ -322,6 +674,11 @@ pub mod pallet {
             <AttestorNum<T>>::put(<AttestorNum<T>>::get() - 1);
             <AttestorLastNotify<T>>::remove(&attestor);
             <Attestors<T>>::remove(&attestor);
+            <Metadata<T>>::remove(&attestor);
+            Self::retire_key(
+                &attestor,
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>(),
+            );
 
             // deposit event
             Self::deposit_event(Event::AttestorRemove(attestor));
@@ -329,6 +686,102 @@ pub mod pallet {
             ret
         }
 
+        /// Whether `pubkey` was a valid key of the attestor at `block_number`.
+        pub fn key_valid_at(
+            attestor: &T::AccountId,
+            pubkey: &[u8],
+            block_number: BlockNumber,
+        ) -> bool {
+            <KeyHistory<T>>::get(attestor)
+                .iter()
+                .any(|key| key.pubkey == pubkey && key.valid_at(block_number))
+        }
+
+        /// Payload a new key of the attestor signs to prove its possession: `KEY_PROOF_CONTEXT`,
+        /// the attestor's account, the key and a recent block number, SCALE encoded.
+        pub fn key_proof_payload(
+            attestor: &T::AccountId,
+            pubkey: &[u8],
+            block_number: BlockNumber,
+        ) -> Vec<u8> {
+            (KEY_PROOF_CONTEXT, attestor, pubkey, block_number).encode()
+        }
+
+        /// Check that `pubkey` is an sr25519 key which signed `key_proof_payload` for a block
+        /// at most `KEY_PROOF_MAX_AGE` blocks old, so that attestors can't claim keys of
+        /// others.
+        fn check_key_proof(
+            attestor: &T::AccountId,
+            pubkey: &[u8],
+            block_number: BlockNumber,
+            signature: &[u8; 64],
+        ) -> Result<(), Error<T>> {
+            let key = Public::try_from(pubkey).map_err(|_| Error::<T>::InvalidPubkey)?;
+            let now = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            ensure!(
+                block_number <= now
+                    && now - block_number <= KEY_PROOF_MAX_AGE
+                    && sp_io::crypto::sr25519_verify(
+                        &Signature::from_raw(*signature),
+                        &Self::key_proof_payload(attestor, pubkey, block_number),
+                        &key
+                    ),
+                Error::<T>::InvalidKeyProof
+            );
+            Ok(())
+        }
+
+        /// Add `pubkey` as the current key of the attestor, dropping the oldest keys beyond
+        /// `MAX_KEY_HISTORY`.
+        fn push_key(attestor: &T::AccountId, pubkey: Vec<u8>, activated: BlockNumber) {
+            <KeyHistory<T>>::mutate(attestor, |history| {
+                history.push(AttestorKey {
+                    pubkey,
+                    activated,
+                    expires: None,
+                });
+                if history.len() > MAX_KEY_HISTORY {
+                    history.drain(..history.len() - MAX_KEY_HISTORY);
+                }
+            });
+        }
+
+        /// Let the current key of the attestor expire at `expires`.
+        fn retire_key(attestor: &T::AccountId, expires: BlockNumber) {
+            <KeyHistory<T>>::mutate(attestor, |history| {
+                if let Some(key) = history.last_mut() {
+                    if key.expires.is_none() {
+                        key.expires = Some(expires);
+                    }
+                }
+            });
+        }
+
+        /// Slash up to `amount` of the attestor's stake, the slashed imbalance is returned to
+        /// the caller to handle.
+        pub fn slash_stake(
+            attestor: &T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> NegativeImbalanceOf<T> {
+            let amount = amount.min(<AttestorStake<T>>::get(attestor));
+            let (imbalance, remaining) = T::Currency::slash_reserved(attestor, amount);
+            let slashed = amount - remaining;
+            <AttestorStake<T>>::mutate_exists(attestor, |stake| {
+                if let Some(stake) = stake {
+                    *stake -= slashed;
+                }
+            });
+            Self::deposit_event(Event::StakeSlash(attestor.clone(), slashed));
+            imbalance
+        }
+
+        /// Release the whole stake of the attestor, once it is removed.
+        pub fn release_stake(attestor: &T::AccountId) {
+            let stake = <AttestorStake<T>>::take(attestor);
+            T::Currency::unreserve(attestor, stake);
+            Self::deposit_event(Event::AttestorUnbond(attestor.clone(), stake));
+        }
+
         /// detach geode from attestors
         pub fn detach_geode_from_attestors(geode: &T::AccountId) {
             // clean record on attestors
@@ -383,8 +836,79 @@ pub mod pallet {
                 }
             }
 
+            // clean NotifyQueue
+            {
+                let mut notify_queue = Vec::new();
+                <NotifyQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        notify_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in notify_queue.iter() {
+                    <NotifyQueue<T>>::remove(block);
+                }
+            }
+
+            // clean AttestorStake, giving the stakes back
+            {
+                let mut attestor_stakes = Vec::new();
+                <AttestorStake<T>>::iter()
+                    .map(|(key, _)| {
+                        attestor_stakes.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in attestor_stakes.iter() {
+                    Self::release_stake(attestor);
+                }
+            }
+
+            // clean Metadata
+            {
+                let mut metadata = Vec::new();
+                <Metadata<T>>::iter()
+                    .map(|(key, _)| {
+                        metadata.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in metadata.iter() {
+                    <Metadata<T>>::remove(attestor);
+                }
+            }
+
+            // clean KeyHistory
+            {
+                let mut key_histories = Vec::new();
+                <KeyHistory<T>>::iter()
+                    .map(|(key, _)| {
+                        key_histories.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in key_histories.iter() {
+                    <KeyHistory<T>>::remove(attestor);
+                }
+            }
+
+            // clean HeartbeatNonces
+            {
+                let mut heartbeat_nonces = Vec::new();
+                <HeartbeatNonces<T>>::iter()
+                    .map(|(key, _)| {
+                        heartbeat_nonces.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in heartbeat_nonces.iter() {
+                    <HeartbeatNonces<T>>::remove(attestor);
+                }
+            }
+
             // reset AttestorNum
             <AttestorNum<T>>::put(0);
         }
     }
+
+    impl<T: Config> AttestorRegistry<T::AccountId> for Pallet<T> {
+        fn is_attesting(attestor: &T::AccountId, geode: &T::AccountId) -> bool {
+            <GeodeAttestors<T>>::get(geode).contains(attestor)
+        }
+    }
 }
@@ -1,11 +1,13 @@
 use crate as attestor;
-use frame_support::parameter_types;
+use frame_support::{dispatch::DispatchResultWithPostInfo, parameter_types};
 use frame_system as system;
-use sp_core::H256;
+use sp_core::{sr25519, Pair, H256};
+use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
 };
+use std::sync::Arc;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -83,6 +85,9 @@ impl attestor::Config for Test {
     type Event = Event;
     type Currency = Balances;
     type Call = Call;
+    type OnHeartbeat = ();
+    type Assignments = ();
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -97,10 +102,43 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     .unwrap();
 
     let mut ext = sp_io::TestExternalities::new(t);
+    ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
     ext.execute_with(|| System::set_block_number(1));
     ext
 }
 
+/// The pubkey of `pair`, the current block and the signature proving the possession of the
+/// key by `attestor`, as `attestor_register` and `attestor_rotate_key` take them.
+pub fn key_proof(attestor: u64, pair: &sr25519::Pair) -> (Vec<u8>, u32, [u8; 64]) {
+    let pubkey = pair.public().0.to_vec();
+    let block_number = System::block_number() as u32;
+    let payload = AttestorModule::key_proof_payload(&attestor, &pubkey, block_number);
+    (pubkey, block_number, pair.sign(&payload).0)
+}
+
+/// The attestor key seeded with `seed`.
+pub fn key_pair(seed: u8) -> sr25519::Pair {
+    sr25519::Pair::from_seed(&[seed; 32])
+}
+
+/// Register `attestor` with the key seeded with `seed`.
+pub fn register_attestor(attestor: u64, url: Vec<u8>, seed: u8) -> DispatchResultWithPostInfo {
+    let (pubkey, block_number, signature) = key_proof(attestor, &key_pair(seed));
+    AttestorModule::attestor_register(
+        Origin::signed(attestor),
+        url,
+        pubkey,
+        block_number,
+        signature,
+    )
+}
+
+/// Rotate the key of `attestor` to the key of `pair`.
+pub fn rotate_key(attestor: u64, pair: &sr25519::Pair) -> DispatchResultWithPostInfo {
+    let (pubkey, block_number, signature) = key_proof(attestor, pair);
+    AttestorModule::attestor_rotate_key(Origin::signed(attestor), pubkey, block_number, signature)
+}
+
 pub fn events() -> Vec<Event> {
     let evt = System::events()
         .into_iter()
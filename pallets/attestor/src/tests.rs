@@ -1,14 +1,24 @@
-use crate::{mock::*, Attestor};
-use frame_support::assert_ok;
+use crate::{
+    mock::*, Attestor, AttestorKey, AttestorMetadata, HeartbeatOf, HEARTBEAT_VERSION,
+    MAX_ATTESTOR_GEODES,
+};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::Imbalance,
+    unsigned::{TransactionSource, TransactionValidityError, ValidateUnsigned},
+};
 use frame_system::pallet_prelude::*;
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::transaction_validity::InvalidTransaction;
 
 #[test]
 fn it_works_for_attestor_register() {
     new_test_ext().execute_with(|| {
         let url = vec![1];
-        let pubkey = vec![2];
         let min_stake = 100;
         let attestor_account = 1;
+        let (pubkey, block_number, signature) = key_proof(attestor_account, &key_pair(2));
 
         // set the min stake balance
         assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
@@ -17,7 +27,9 @@ fn it_works_for_attestor_register() {
         assert_ok!(AttestorModule::attestor_register(
             Origin::signed(attestor_account),
             url.clone(),
-            pubkey.clone()
+            pubkey.clone(),
+            block_number,
+            signature
         ));
         let data = AttestorModule::attestors(&attestor_account);
 
@@ -53,9 +65,9 @@ fn it_works_for_attestor_register() {
 fn it_works_for_attestor_remove() {
     new_test_ext().execute_with(|| {
         let url = vec![1];
-        let pubkey = vec![2];
         let min_stake = 100;
         let attestor_account = 1;
+        let (pubkey, block_number, signature) = key_proof(attestor_account, &key_pair(2));
 
         // set the min stake balance
         assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
@@ -64,7 +76,9 @@ fn it_works_for_attestor_remove() {
         assert_ok!(AttestorModule::attestor_register(
             Origin::signed(attestor_account),
             url.clone(),
-            pubkey.clone()
+            pubkey.clone(),
+            block_number,
+            signature
         ));
 
         // remove old events
@@ -98,9 +112,9 @@ fn it_works_for_attestor_remove() {
 fn it_works_for_attestor_update() {
     new_test_ext().execute_with(|| {
         let url = vec![1];
-        let pubkey = vec![2];
         let min_stake = 100;
         let attestor_account = 1;
+        let (pubkey, block_number, signature) = key_proof(attestor_account, &key_pair(2));
 
         // set the min stake balance
         assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
@@ -109,7 +123,9 @@ fn it_works_for_attestor_update() {
         assert_ok!(AttestorModule::attestor_register(
             Origin::signed(attestor_account),
             url.clone(),
-            pubkey.clone()
+            pubkey.clone(),
+            block_number,
+            signature
         ));
 
         let data = AttestorModule::attestors(&attestor_account);
@@ -155,6 +171,342 @@ fn it_works_for_attestor_update() {
     });
 }
 
+#[test]
+fn it_works_for_attestor_update_metadata() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let metadata = AttestorMetadata {
+            region: b"us-east".to_vec(),
+            version: b"0.2.0".to_vec(),
+            capacity: 10,
+        };
+
+        // not registered yet
+        assert_noop!(
+            AttestorModule::attestor_update_metadata(
+                Origin::signed(attestor_account),
+                metadata.clone()
+            ),
+            crate::Error::<Test>::InvalidAttestor
+        );
+
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), 100));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+        assert_eq!(
+            AttestorModule::attestor_metadata(attestor_account),
+            Default::default()
+        );
+
+        assert_noop!(
+            AttestorModule::attestor_update_metadata(
+                Origin::signed(attestor_account),
+                AttestorMetadata {
+                    region: vec![0; crate::MAX_METADATA_LENGTH + 1],
+                    ..metadata.clone()
+                }
+            ),
+            crate::Error::<Test>::InvalidMetadata
+        );
+
+        events();
+        assert_ok!(AttestorModule::attestor_update_metadata(
+            Origin::signed(attestor_account),
+            metadata.clone()
+        ));
+        assert_eq!(
+            AttestorModule::attestor_metadata(attestor_account),
+            metadata
+        );
+        assert_eq!(
+            events(),
+            [Event::attestor(crate::Event::AttestorMetadataUpdate(
+                attestor_account
+            )),]
+        );
+
+        // the metadata goes away with the attestor
+        AttestorModule::attestor_remove(attestor_account);
+        assert!(!crate::Metadata::<Test>::contains_key(attestor_account));
+    });
+}
+
+#[test]
+fn it_works_for_attestor_rotate_key() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let (old_pair, new_pair) = (key_pair(2), key_pair(3));
+        let (old_key, new_key) = (old_pair.public().0.to_vec(), new_pair.public().0.to_vec());
+
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), 100));
+        assert_ok!(AttestorModule::set_key_overlap(Origin::root(), 5));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+        assert_eq!(
+            AttestorModule::key_history(attestor_account),
+            vec![AttestorKey {
+                pubkey: old_key.clone(),
+                activated: 1,
+                expires: None,
+            }]
+        );
+
+        System::set_block_number(10);
+        events();
+        assert_ok!(rotate_key(attestor_account, &new_pair));
+        assert_eq!(AttestorModule::attestors(attestor_account).pubkey, new_key);
+        assert_eq!(
+            events(),
+            [Event::attestor(crate::Event::AttestorKeyRotate(
+                attestor_account,
+                15
+            )),]
+        );
+
+        // both keys are accepted during the overlap, only the old one before the rotation
+        assert!(AttestorModule::key_valid_at(&attestor_account, &old_key, 5));
+        assert!(!AttestorModule::key_valid_at(
+            &attestor_account,
+            &new_key,
+            5
+        ));
+        assert!(AttestorModule::key_valid_at(
+            &attestor_account,
+            &old_key,
+            14
+        ));
+        assert!(AttestorModule::key_valid_at(
+            &attestor_account,
+            &new_key,
+            14
+        ));
+        assert!(!AttestorModule::key_valid_at(
+            &attestor_account,
+            &old_key,
+            15
+        ));
+        assert!(AttestorModule::key_valid_at(
+            &attestor_account,
+            &new_key,
+            15
+        ));
+
+        // keys can't be reused
+        assert_noop!(
+            rotate_key(attestor_account, &old_pair),
+            crate::Error::<Test>::KeyAlreadyUsed
+        );
+        let (_, block_number, signature) = key_proof(attestor_account, &key_pair(4));
+        assert_noop!(
+            AttestorModule::attestor_rotate_key(
+                Origin::signed(attestor_account),
+                vec![4; 33],
+                block_number,
+                signature
+            ),
+            crate::Error::<Test>::InvalidPubkey
+        );
+
+        // history is bounded, the oldest keys are dropped first
+        for i in 0..crate::MAX_KEY_HISTORY as u8 {
+            assert_ok!(rotate_key(attestor_account, &key_pair(10 + i)));
+        }
+        let history = AttestorModule::key_history(attestor_account);
+        assert_eq!(history.len(), crate::MAX_KEY_HISTORY);
+        assert_eq!(history[0].pubkey, key_pair(10).public().0.to_vec());
+
+        // removing the attestor expires its current key but keeps the history
+        System::set_block_number(20);
+        AttestorModule::attestor_remove(attestor_account);
+        let history = AttestorModule::key_history(attestor_account);
+        assert_eq!(history.last().unwrap().expires, Some(20));
+        assert!(AttestorModule::key_valid_at(
+            &attestor_account,
+            &history.last().unwrap().pubkey,
+            19
+        ));
+    });
+}
+
+#[test]
+fn it_attestor_key_proof_invalid() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let pair = key_pair(2);
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), 100));
+        System::set_block_number(20);
+        let register = |(pubkey, block_number, signature): (Vec<u8>, u32, [u8; 64])| {
+            AttestorModule::attestor_register(
+                Origin::signed(attestor_account),
+                vec![1],
+                pubkey,
+                block_number,
+                signature,
+            )
+        };
+
+        // signed by another key
+        let (pubkey, block_number, _) = key_proof(attestor_account, &pair);
+        let (_, _, signature) = key_proof(attestor_account, &key_pair(3));
+        assert_noop!(
+            register((pubkey, block_number, signature)),
+            crate::Error::<Test>::InvalidKeyProof
+        );
+        // signed for another attestor, the key can't be claimed by others
+        assert_noop!(
+            register(key_proof(2, &pair)),
+            crate::Error::<Test>::InvalidKeyProof
+        );
+        // signed for a block too old or not there yet
+        let proof = key_proof(attestor_account, &pair);
+        System::set_block_number(21 + crate::KEY_PROOF_MAX_AGE as u64);
+        assert_noop!(
+            register(proof.clone()),
+            crate::Error::<Test>::InvalidKeyProof
+        );
+        System::set_block_number(19);
+        assert_noop!(register(proof), crate::Error::<Test>::InvalidKeyProof);
+        // not an sr25519 key
+        let (_, block_number, signature) = key_proof(attestor_account, &pair);
+        assert_noop!(
+            register((vec![2; 65], block_number, signature)),
+            crate::Error::<Test>::InvalidPubkey
+        );
+
+        // the key is proven, and the same has to hold when rotating to a new key
+        assert_ok!(register(key_proof(attestor_account, &pair)));
+        let (pubkey, block_number, _) = key_proof(attestor_account, &key_pair(4));
+        let (_, _, signature) = key_proof(attestor_account, &key_pair(5));
+        assert_noop!(
+            AttestorModule::attestor_rotate_key(
+                Origin::signed(attestor_account),
+                pubkey,
+                block_number,
+                signature
+            ),
+            crate::Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn it_works_for_attestor_capacity() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), 100));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+
+        // no capacity declared, only bounded by MAX_ATTESTOR_GEODES
+        assert!(AttestorModule::has_capacity(
+            &attestor_account,
+            MAX_ATTESTOR_GEODES as usize - 1
+        ));
+        assert!(!AttestorModule::has_capacity(
+            &attestor_account,
+            MAX_ATTESTOR_GEODES as usize
+        ));
+
+        assert_ok!(AttestorModule::attestor_update_metadata(
+            Origin::signed(attestor_account),
+            AttestorMetadata {
+                capacity: 2,
+                ..Default::default()
+            }
+        ));
+        assert!(AttestorModule::has_capacity(&attestor_account, 1));
+        assert!(!AttestorModule::has_capacity(&attestor_account, 2));
+
+        crate::Attestors::<Test>::mutate(attestor_account, |attestor| {
+            attestor.geodes.insert(10);
+        });
+        assert_eq!(
+            AttestorModule::attestor_loads(),
+            vec![(attestor_account, 1, 2)]
+        );
+    });
+}
+
+#[test]
+fn it_works_for_attestor_bond() {
+    new_test_ext().execute_with(|| {
+        let min_stake = 100;
+        let attestor_account = 1;
+
+        // not registered yet
+        assert_noop!(
+            AttestorModule::attestor_bond(Origin::signed(attestor_account), 50),
+            crate::Error::<Test>::InvalidAttestor
+        );
+
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), min_stake);
+        events();
+
+        assert_ok!(AttestorModule::attestor_bond(
+            Origin::signed(attestor_account),
+            50
+        ));
+        assert_eq!(
+            AttestorModule::attestor_stake(attestor_account),
+            min_stake + 50
+        );
+        assert_eq!(Balances::reserved_balance(attestor_account), min_stake + 50);
+        assert_eq!(
+            events(),
+            [
+                Event::pallet_balances(pallet_balances::Event::Reserved(attestor_account, 50)),
+                Event::attestor(crate::Event::AttestorBond(attestor_account, 50)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_works_for_attestor_slash_stake() {
+    new_test_ext().execute_with(|| {
+        let min_stake = 100;
+        let attestor_account = 1;
+
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+
+        assert_eq!(
+            AttestorModule::slash_stake(&attestor_account, 30).peek(),
+            30
+        );
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), 70);
+        assert_eq!(Balances::reserved_balance(attestor_account), 70);
+
+        // never more than the stake
+        assert_eq!(
+            AttestorModule::slash_stake(&attestor_account, min_stake).peek(),
+            70
+        );
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), 0);
+        assert_eq!(
+            Balances::free_balance(attestor_account),
+            INIT_BALANCE - min_stake
+        );
+    });
+}
+
+#[test]
+fn it_works_for_clean_storage_releases_stake() {
+    new_test_ext().execute_with(|| {
+        let min_stake = 100;
+        let attestor_account = 1;
+
+        assert_ok!(AttestorModule::set_att_stake_min(Origin::root(), min_stake));
+        assert_ok!(register_attestor(attestor_account, vec![1], 2));
+
+        AttestorModule::clean_storage();
+        assert!(!crate::AttestorStake::<Test>::contains_key(
+            attestor_account
+        ));
+        assert_eq!(Balances::reserved_balance(attestor_account), 0);
+        assert_eq!(Balances::free_balance(attestor_account), INIT_BALANCE);
+    });
+}
+
 #[test]
 fn it_works_for_set_att_stake_min() {
     new_test_ext().execute_with(|| {
@@ -166,3 +518,153 @@ fn it_works_for_set_att_stake_min() {
         assert_eq!(AttestorModule::att_stake_min(), min_stake);
     });
 }
+
+#[test]
+fn it_works_for_set_key_overlap() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(AttestorModule::key_overlap(), crate::DEFAULT_KEY_OVERLAP);
+        assert_ok!(AttestorModule::set_key_overlap(Origin::root(), 100));
+        assert_eq!(AttestorModule::key_overlap(), 100);
+    });
+}
+
+/// A registered attestor whose account is an sr25519 key.
+fn register_keyed_attestor() -> (sr25519::Pair, u64) {
+    let pair = sr25519::Pair::from_seed(&[7; 32]);
+    let account = codec::Decode::decode(&mut &pair.public().0[..]).unwrap();
+    Balances::make_free_balance_be(&account, INIT_BALANCE);
+    assert_ok!(register_attestor(account, vec![1], 2));
+    (pair, account)
+}
+
+/// A heartbeat of the attestor bound to `block_number`, which has to have a hash.
+fn heartbeat(pair: &sr25519::Pair, block_number: u32, nonce: u64) -> HeartbeatOf<Test> {
+    HeartbeatOf::<Test> {
+        version: HEARTBEAT_VERSION,
+        attestor: pair.public().0,
+        block_number,
+        block_hash: System::block_hash(block_number as u64),
+        nonce,
+        geode_digest: AttestorModule::geode_digest(&Default::default()),
+        results: vec![],
+    }
+}
+
+fn notify_call(pair: &sr25519::Pair, heartbeat: &HeartbeatOf<Test>) -> crate::Call<Test> {
+    let message = heartbeat.encode();
+    let signature = pair.sign(&message);
+    crate::Call::attestor_notify_chain(message, signature.0)
+}
+
+fn validate(call: &crate::Call<Test>) -> Result<(), TransactionValidityError> {
+    AttestorModule::validate_unsigned(TransactionSource::External, call).map(|_| ())
+}
+
+#[test]
+fn it_works_for_attestor_notify_chain() {
+    new_test_ext().execute_with(|| {
+        let (pair, account) = register_keyed_attestor();
+        frame_system::BlockHash::<Test>::insert(4, H256::repeat_byte(4));
+        System::set_block_number(5);
+
+        let call = notify_call(&pair, &heartbeat(&pair, 4, 1));
+        assert_ok!(validate(&call));
+        if let crate::Call::attestor_notify_chain(message, signature) = call.clone() {
+            assert_ok!(AttestorModule::attestor_notify_chain(
+                Origin::none(),
+                message,
+                signature
+            ));
+        }
+        assert_eq!(AttestorModule::attestor_last_notification(account), 5);
+        assert_eq!(AttestorModule::heartbeat_nonce(account), 1);
+
+        // the same heartbeat can't be replayed
+        assert_eq!(validate(&call), Err(InvalidTransaction::Stale.into()));
+        assert_ok!(validate(&notify_call(&pair, &heartbeat(&pair, 4, 2))));
+    });
+}
+
+#[test]
+fn it_works_for_attestor_notify_chain_rotated_key() {
+    new_test_ext().execute_with(|| {
+        let (pair, account) = register_keyed_attestor();
+        let rotated = sr25519::Pair::from_seed(&[8; 32]);
+        frame_system::BlockHash::<Test>::insert(2, H256::repeat_byte(2));
+        frame_system::BlockHash::<Test>::insert(4, H256::repeat_byte(4));
+        System::set_block_number(3);
+        let (pubkey, block_number, signature) = key_proof(account, &rotated);
+        assert_ok!(AttestorModule::attestor_rotate_key(
+            Origin::signed(account),
+            pubkey,
+            block_number,
+            signature
+        ));
+        System::set_block_number(5);
+
+        // the rotated key signs the heartbeats bound to the blocks it is valid at
+        assert_ok!(validate(&notify_call(&rotated, &heartbeat(&pair, 4, 1))));
+        assert_eq!(
+            validate(&notify_call(&rotated, &heartbeat(&pair, 2, 1))),
+            Err(InvalidTransaction::BadProof.into())
+        );
+    });
+}
+
+#[test]
+fn it_attestor_notify_chain_invalid() {
+    new_test_ext().execute_with(|| {
+        let (pair, _) = register_keyed_attestor();
+        frame_system::BlockHash::<Test>::insert(4, H256::repeat_byte(4));
+        System::set_block_number(5);
+
+        // unknown version
+        let mut invalid = heartbeat(&pair, 4, 1);
+        invalid.version = HEARTBEAT_VERSION + 1;
+        assert_eq!(
+            validate(&notify_call(&pair, &invalid)),
+            Err(InvalidTransaction::Call.into())
+        );
+
+        // signed by another key
+        let other = sr25519::Pair::from_seed(&[8; 32]);
+        assert_eq!(
+            validate(&notify_call(&other, &heartbeat(&pair, 4, 1))),
+            Err(InvalidTransaction::BadProof.into())
+        );
+
+        // bound to a wrong hash, to the current block, or to a block too old
+        let mut invalid = heartbeat(&pair, 4, 1);
+        invalid.block_hash = H256::repeat_byte(5);
+        assert_eq!(
+            validate(&notify_call(&pair, &invalid)),
+            Err(InvalidTransaction::Stale.into())
+        );
+        assert_eq!(
+            validate(&notify_call(&pair, &heartbeat(&pair, 5, 1))),
+            Err(InvalidTransaction::Stale.into())
+        );
+        System::set_block_number(5 + crate::HEARTBEAT_MAX_AGE as u64);
+        assert_eq!(
+            validate(&notify_call(&pair, &heartbeat(&pair, 4, 1))),
+            Err(InvalidTransaction::Stale.into())
+        );
+        System::set_block_number(5);
+
+        // not matching the geodes of the attestor
+        let mut invalid = heartbeat(&pair, 4, 1);
+        invalid.geode_digest = H256::repeat_byte(6);
+        assert_eq!(
+            validate(&notify_call(&pair, &invalid)),
+            Err(InvalidTransaction::Call.into())
+        );
+
+        // results for geodes the attestor doesn't attest
+        let mut invalid = heartbeat(&pair, 4, 1);
+        invalid.results = vec![1];
+        assert_eq!(
+            validate(&notify_call(&pair, &invalid)),
+            Err(InvalidTransaction::Call.into())
+        );
+    });
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weights for pallet_attestor
+//!
+//! The base weights are estimated from the storage accesses of each call until they are
+//! regenerated from the benchmarks on the reference hardware with:
+//!
+//! ./scripts/benchmark-weights.sh attestor
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_attestor.
+pub trait WeightInfo {
+    fn attestor_register() -> Weight;
+    fn attestor_update() -> Weight;
+    fn attestor_update_metadata() -> Weight;
+    fn attestor_rotate_key() -> Weight;
+    fn attestor_bond() -> Weight;
+    fn attestor_notify_chain(g: u32) -> Weight;
+    fn set_att_stake_min() -> Weight;
+    fn set_key_overlap() -> Weight;
+}
+
+/// Weights for pallet_attestor using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn attestor_register() -> Weight {
+        (96_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(7 as Weight))
+    }
+    fn attestor_update() -> Weight {
+        (22_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn attestor_update_metadata() -> Weight {
+        (24_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn attestor_rotate_key() -> Weight {
+        (83_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn attestor_bond() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn attestor_notify_chain(g: u32) -> Weight {
+        (853_000_000 as Weight)
+            .saturating_add((6_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(g as Weight)))
+    }
+    fn set_att_stake_min() -> Weight {
+        (3_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_key_overlap() -> Weight {
+        (3_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn attestor_register() -> Weight {
+        (96_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(6 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(7 as Weight))
+    }
+    fn attestor_update() -> Weight {
+        (22_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn attestor_update_metadata() -> Weight {
+        (24_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn attestor_rotate_key() -> Weight {
+        (83_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn attestor_bond() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn attestor_notify_chain(g: u32) -> Weight {
+        (853_000_000 as Weight)
+            .saturating_add((6_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(RocksDbWeight::get().reads(5 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(3 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(g as Weight)))
+    }
+    fn set_att_stake_min() -> Weight {
+        (3_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_key_overlap() -> Weight {
+        (3_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+}
@@ -0,0 +1,43 @@
+//! Benchmarking setup for pallet-enclave
+
+use super::*;
+
+use crate::Pallet as Enclave;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+fn measurement(i: u8) -> Measurement {
+    Measurement {
+        mr_enclave: [i; 32],
+        mr_signer: [i; 32],
+        isv_svn: i as u16,
+    }
+}
+
+benchmarks! {
+    add_measurement {
+        let measurement = measurement(1);
+    }: _(RawOrigin::Root, measurement.clone())
+    verify {
+        assert_eq!(Measurements::<T>::get(&measurement), Some(MeasurementStatus::Approved));
+    }
+
+    deprecate_measurement {
+        let measurement = measurement(1);
+        Enclave::<T>::add_measurement(RawOrigin::Root.into(), measurement.clone())?;
+    }: _(RawOrigin::Root, measurement.clone())
+    verify {
+        assert_eq!(Measurements::<T>::get(&measurement), Some(MeasurementStatus::Deprecated));
+    }
+
+    revoke_measurement {
+        let measurement = measurement(1);
+        Enclave::<T>::add_measurement(RawOrigin::Root.into(), measurement.clone())?;
+    }: _(RawOrigin::Root, measurement.clone())
+    verify {
+        assert_eq!(Measurements::<T>::get(&measurement), Some(MeasurementStatus::Revoked));
+    }
+}
+
+impl_benchmark_test_suite!(Enclave, crate::mock::new_test_ext(), crate::mock::Test,);
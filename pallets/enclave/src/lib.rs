@@ -0,0 +1,228 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use crate::weights::WeightInfo;
+    use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::RuntimeDebug;
+    use sp_std::prelude::*;
+
+    #[cfg(feature = "std")]
+    use serde::{Deserialize, Serialize};
+
+    /// Measurement of an enclave build, as `geode_sgx_ra::SgxRaMsg3::get_mr_enclave`,
+    /// `get_mr_signer` and `get_isv_svn` read it from the quote.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct Measurement {
+        pub mr_enclave: [u8; 32],
+        pub mr_signer: [u8; 32],
+        pub isv_svn: u16,
+    }
+
+    /// Whether geodes may run an enclave build.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub enum MeasurementStatus {
+        /// Geodes running the build get attested.
+        Approved,
+        /// The build is outdated, geodes already attested keep running it but no new
+        /// attestation is accepted.
+        Deprecated,
+        /// The build must not run, geodes running it are degraded.
+        Revoked,
+    }
+
+    /// Gives the status of enclave builds, e.g. to attest geodes.
+    pub trait MeasurementRegistry {
+        /// Status of the build, `None` if it was never added.
+        fn measurement_status(measurement: &Measurement) -> Option<MeasurementStatus>;
+
+        /// Approve the build, for benchmarks.
+        #[cfg(feature = "runtime-benchmarks")]
+        fn approve_measurement(measurement: &Measurement);
+    }
+
+    /// Knows no build.
+    impl MeasurementRegistry for () {
+        fn measurement_status(_measurement: &Measurement) -> Option<MeasurementStatus> {
+            None
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn approve_measurement(_measurement: &Measurement) {}
+    }
+
+    /// Notified when an enclave build is revoked.
+    pub trait OnMeasurementRevoke {
+        fn on_measurement_revoke(measurement: &Measurement);
+    }
+
+    impl OnMeasurementRevoke for () {
+        fn on_measurement_revoke(_measurement: &Measurement) {}
+    }
+
+    /// Configure the pallet by specifying the parameters and types on which it depends.
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// Because this pallet emits events, it depends on the runtime's definition of an event.
+        type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+        /// Origin allowed to add, deprecate and revoke builds, e.g. the council.
+        type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+        /// Handler of revoked builds, e.g. liveness degrading the geodes running them.
+        type OnRevoke: OnMeasurementRevoke;
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// Enclave builds known to the chain. Revoked builds are kept so they can't be added
+    /// again.
+    #[pallet::storage]
+    #[pallet::getter(fn measurements)]
+    pub type Measurements<T: Config> =
+        StorageMap<_, Blake2_128Concat, Measurement, MeasurementStatus, OptionQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig {
+        /// Builds approved at genesis.
+        pub measurements: Vec<Measurement>,
+    }
+
+    #[cfg(feature = "std")]
+    impl Default for GenesisConfig {
+        fn default() -> Self {
+            GenesisConfig {
+                measurements: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            for measurement in self.measurements.iter() {
+                <Measurements<T>>::insert(measurement, MeasurementStatus::Approved);
+            }
+        }
+    }
+
+    // Pallets use events to inform users when important changes are made.
+    // https://substrate.dev/docs/en/knowledgebase/runtime/events
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event {
+        /// Governance approved an enclave build. \[measurement\]
+        MeasurementAdded(Measurement),
+        /// Governance deprecated an enclave build. \[measurement\]
+        MeasurementDeprecated(Measurement),
+        /// Governance revoked an enclave build. \[measurement\]
+        MeasurementRevoked(Measurement),
+    }
+
+    // Errors inform users that something went wrong.
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The build was already added.
+        MeasurementExists,
+        /// No such build.
+        UnknownMeasurement,
+        /// Only approved builds can be deprecated.
+        NotApproved,
+        /// The build is already revoked.
+        AlreadyRevoked,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    // Dispatchable functions allows users to interact with the pallet and invoke state changes.
+    // These functions materialize as "extrinsics", which are often compared to transactions.
+    // Dispatchable functions must be annotated with a weight and must return a DispatchResult.
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Called by governance to approve an enclave build.
+        #[pallet::weight(T::WeightInfo::add_measurement())]
+        pub fn add_measurement(
+            origin: OriginFor<T>,
+            measurement: Measurement,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(
+                !<Measurements<T>>::contains_key(&measurement),
+                Error::<T>::MeasurementExists
+            );
+            <Measurements<T>>::insert(&measurement, MeasurementStatus::Approved);
+            Self::deposit_event(Event::MeasurementAdded(measurement));
+            Ok(().into())
+        }
+
+        /// Called by governance to stop approving an outdated enclave build, the geodes
+        /// already running it keep their attestors.
+        #[pallet::weight(T::WeightInfo::deprecate_measurement())]
+        pub fn deprecate_measurement(
+            origin: OriginFor<T>,
+            measurement: Measurement,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            let status =
+                <Measurements<T>>::get(&measurement).ok_or(Error::<T>::UnknownMeasurement)?;
+            ensure!(
+                status == MeasurementStatus::Approved,
+                Error::<T>::NotApproved
+            );
+            <Measurements<T>>::insert(&measurement, MeasurementStatus::Deprecated);
+            Self::deposit_event(Event::MeasurementDeprecated(measurement));
+            Ok(().into())
+        }
+
+        /// Called by governance to revoke an enclave build, e.g. a vulnerable one. The geodes
+        /// running it are degraded by `OnRevoke`.
+        #[pallet::weight(T::WeightInfo::revoke_measurement())]
+        pub fn revoke_measurement(
+            origin: OriginFor<T>,
+            measurement: Measurement,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            let status =
+                <Measurements<T>>::get(&measurement).ok_or(Error::<T>::UnknownMeasurement)?;
+            ensure!(
+                status != MeasurementStatus::Revoked,
+                Error::<T>::AlreadyRevoked
+            );
+            <Measurements<T>>::insert(&measurement, MeasurementStatus::Revoked);
+            T::OnRevoke::on_measurement_revoke(&measurement);
+            Self::deposit_event(Event::MeasurementRevoked(measurement));
+            Ok(().into())
+        }
+    }
+
+    impl<T: Config> MeasurementRegistry for Pallet<T> {
+        fn measurement_status(measurement: &Measurement) -> Option<MeasurementStatus> {
+            <Measurements<T>>::get(measurement)
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn approve_measurement(measurement: &Measurement) {
+            <Measurements<T>>::insert(measurement, MeasurementStatus::Approved);
+        }
+    }
+}
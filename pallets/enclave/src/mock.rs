@@ -0,0 +1,114 @@
+use crate as enclave;
+use enclave::{Measurement, OnMeasurementRevoke};
+use frame_support::{parameter_types, traits::GenesisBuild};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        EnclaveModule: enclave::{Module, Call, Storage, Event, Config},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+}
+
+parameter_types! {
+    /// Builds `OnRevoke` was notified of.
+    pub static Revoked: Vec<Measurement> = vec![];
+}
+
+pub struct MockOnRevoke;
+
+impl OnMeasurementRevoke for MockOnRevoke {
+    fn on_measurement_revoke(measurement: &Measurement) {
+        let mut revoked = Revoked::get();
+        revoked.push(measurement.clone());
+        Revoked::set(revoked);
+    }
+}
+
+impl enclave::Config for Test {
+    type Event = Event;
+    type GovernanceOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type OnRevoke = MockOnRevoke;
+    type WeightInfo = ();
+}
+
+/// A build approved at genesis.
+pub fn genesis_measurement() -> Measurement {
+    Measurement {
+        mr_enclave: [1; 32],
+        mr_signer: [2; 32],
+        isv_svn: 1,
+    }
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    GenesisBuild::<Test>::assimilate_storage(
+        &enclave::GenesisConfig {
+            measurements: vec![genesis_measurement()],
+        },
+        &mut t,
+    )
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+pub fn events() -> Vec<Event> {
+    let evt = System::events()
+        .into_iter()
+        .map(|evt| evt.event)
+        .collect::<Vec<_>>();
+
+    System::reset_events();
+
+    evt
+}
@@ -0,0 +1,120 @@
+use crate::{mock::*, Error, Event as EnclaveEvent, Measurement, MeasurementStatus};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+fn new_measurement() -> Measurement {
+    Measurement {
+        isv_svn: 2,
+        ..genesis_measurement()
+    }
+}
+
+#[test]
+fn it_works_for_add_measurement() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            EnclaveModule::measurements(genesis_measurement()),
+            Some(MeasurementStatus::Approved)
+        );
+        assert_eq!(EnclaveModule::measurements(new_measurement()), None);
+
+        assert_noop!(
+            EnclaveModule::add_measurement(Origin::signed(1), new_measurement()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            EnclaveModule::add_measurement(Origin::root(), genesis_measurement()),
+            Error::<Test>::MeasurementExists
+        );
+        assert_ok!(EnclaveModule::add_measurement(
+            Origin::root(),
+            new_measurement()
+        ));
+        assert_eq!(
+            EnclaveModule::measurements(new_measurement()),
+            Some(MeasurementStatus::Approved)
+        );
+        assert_eq!(
+            events(),
+            vec![Event::enclave(EnclaveEvent::MeasurementAdded(
+                new_measurement()
+            ))]
+        );
+    });
+}
+
+#[test]
+fn it_works_for_deprecate_measurement() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            EnclaveModule::deprecate_measurement(Origin::signed(1), genesis_measurement()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            EnclaveModule::deprecate_measurement(Origin::root(), new_measurement()),
+            Error::<Test>::UnknownMeasurement
+        );
+        assert_ok!(EnclaveModule::deprecate_measurement(
+            Origin::root(),
+            genesis_measurement()
+        ));
+        assert_eq!(
+            EnclaveModule::measurements(genesis_measurement()),
+            Some(MeasurementStatus::Deprecated)
+        );
+        assert_noop!(
+            EnclaveModule::deprecate_measurement(Origin::root(), genesis_measurement()),
+            Error::<Test>::NotApproved
+        );
+        // deprecated builds can't be approved again
+        assert_noop!(
+            EnclaveModule::add_measurement(Origin::root(), genesis_measurement()),
+            Error::<Test>::MeasurementExists
+        );
+        assert!(Revoked::get().is_empty());
+    });
+}
+
+#[test]
+fn it_works_for_revoke_measurement() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            EnclaveModule::revoke_measurement(Origin::signed(1), genesis_measurement()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            EnclaveModule::revoke_measurement(Origin::root(), new_measurement()),
+            Error::<Test>::UnknownMeasurement
+        );
+
+        // deprecated builds can be revoked too
+        assert_ok!(EnclaveModule::deprecate_measurement(
+            Origin::root(),
+            genesis_measurement()
+        ));
+        assert_ok!(EnclaveModule::revoke_measurement(
+            Origin::root(),
+            genesis_measurement()
+        ));
+        assert_eq!(
+            EnclaveModule::measurements(genesis_measurement()),
+            Some(MeasurementStatus::Revoked)
+        );
+        assert_eq!(Revoked::get(), vec![genesis_measurement()]);
+        assert_eq!(
+            events().last(),
+            Some(&Event::enclave(EnclaveEvent::MeasurementRevoked(
+                genesis_measurement()
+            )))
+        );
+
+        assert_noop!(
+            EnclaveModule::revoke_measurement(Origin::root(), genesis_measurement()),
+            Error::<Test>::AlreadyRevoked
+        );
+        assert_noop!(
+            EnclaveModule::deprecate_measurement(Origin::root(), genesis_measurement()),
+            Error::<Test>::NotApproved
+        );
+    });
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weights for pallet_enclave
+//!
+//! The base weights are estimated from the storage accesses of each call until they are
+//! regenerated from the benchmarks on the reference hardware with:
+//!
+//! ./scripts/benchmark-weights.sh enclave
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_enclave.
+pub trait WeightInfo {
+    fn add_measurement() -> Weight;
+    fn deprecate_measurement() -> Weight;
+    fn revoke_measurement() -> Weight;
+}
+
+/// Weights for pallet_enclave using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn add_measurement() -> Weight {
+        (12_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn deprecate_measurement() -> Weight {
+        (12_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn revoke_measurement() -> Weight {
+        (15_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn add_measurement() -> Weight {
+        (12_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn deprecate_measurement() -> Weight {
+        (12_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn revoke_measurement() -> Weight {
+        (15_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+}
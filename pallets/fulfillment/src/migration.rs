@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::property::GeodeProperties;
+use crate::{Config, Deal, DealState, Deals, Event, LayoutVersion, Pallet, Releases, WeightInfo};
+use codec::{Decode, Encode};
+use frame_support::{
+    storage::{migration::storage_key_iter, unhashed::kill_prefix},
+    traits::{Get, PalletInfoAccess},
+    weights::Weight,
+    Blake2_128Concat, StorageHasher, Twox128,
+};
+use pallet_geode::GeodeRegistry;
+use sp_io::KillStorageResult;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Storage items of the V1 layout removed as they are, once `Geodes` is migrated.
+const V1_ITEMS: [&[u8]; 3] = [b"AttStakeMin", b"RegisterGeodes", b"Attestors"];
+
+/// Geode state of the V1 layout.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub enum OldGeodeState {
+    Registered,
+    Attested,
+    InOrder,
+    InWork,
+}
+
+/// Geode record of the V1 layout, stored in `Geodes`.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct OldGeode<AccountId, Hash> {
+    pub owner: AccountId,
+    pub user: Option<AccountId>,
+    pub provider: Option<AccountId>,
+    pub ip: Vec<u8>,
+    pub dns: Vec<u8>,
+    pub props: Option<GeodeProperties>,
+    pub binary: Option<Hash>,
+    pub attestors: Vec<AccountId>,
+    pub state: OldGeodeState,
+}
+
+/// Start migrating the V1 layout, where fulfillment registered and attested geodes itself, to
+/// V2. The hook carries the migration on in the next blocks with `migrate_step`.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+    <LayoutVersion<T>>::put(Releases::MigratingToV2);
+    T::DbWeight::get()
+        .writes(1)
+        .saturating_add(migrate_step::<T>())
+}
+
+/// Migrate at most `MaxMigrationsPerBlock` V1 geodes, then remove as many keys of the other
+/// V1 items. The layout is V2 once nothing is left.
+///
+/// The geodes which were on the market or sold keep their user and binary in `Deals` if
+/// `Config::Geodes` knows them with the same provider, the other deals are dropped with a
+/// `DealDropped` event. Everything else is dropped: the providers and attestors have to
+/// register with the geode and attestor pallets, whose attestation replaces the one of V1.
+pub fn migrate_step<T: Config>() -> Weight {
+    let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+    let mut budget = T::MaxMigrationsPerBlock::get();
+    let mut weight = T::DbWeight::get().reads(1);
+
+    let geodes =
+        storage_key_iter::<T::AccountId, OldGeode<T::AccountId, T::Hash>, Blake2_128Concat>(
+            pallet, b"Geodes",
+        )
+        .drain()
+        .take(budget as usize)
+        .collect::<Vec<_>>();
+    budget = budget.saturating_sub(geodes.len() as u32);
+    for (geode, old) in geodes {
+        weight = weight.saturating_add(<T as Config>::WeightInfo::migrate_geode());
+        let state = match old.state {
+            OldGeodeState::InOrder => DealState::InOrder,
+            OldGeodeState::InWork => DealState::InWork,
+            _ => continue,
+        };
+        if old.provider.is_none() || T::Geodes::provider(&geode) != old.provider {
+            <Pallet<T>>::deposit_event(Event::DealDropped(geode, old.user));
+            continue;
+        }
+        <Deals<T>>::insert(
+            &geode,
+            Deal {
+                user: old.user,
+                binary: old.binary,
+                state,
+            },
+        );
+    }
+
+    for item in V1_ITEMS.iter() {
+        if budget == 0 {
+            return weight;
+        }
+        let prefix = [Twox128::hash(pallet), Twox128::hash(item)].concat();
+        match kill_prefix(&prefix, Some(budget)) {
+            KillStorageResult::AllRemoved(removed) => {
+                weight = weight.saturating_add(T::DbWeight::get().writes(removed as Weight));
+                budget = budget.saturating_sub(removed);
+            }
+            KillStorageResult::SomeRemaining(removed) => {
+                return weight.saturating_add(T::DbWeight::get().writes(removed as Weight));
+            }
+        }
+    }
+    <LayoutVersion<T>>::put(Releases::V2);
+
+    weight.saturating_add(T::DbWeight::get().writes(1))
+}
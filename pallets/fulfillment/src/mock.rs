@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use frame_support::{parameter_types, weights::Weight};
+use frame_support::{parameter_types, traits::GenesisBuild, weights::Weight};
 use frame_system as system;
 use pallet_balances as balances;
+use pallet_geode::GeodeState;
 use pallet_stake as stake;
 
 use sp_core::H256;
@@ -22,7 +23,7 @@ frame_support::construct_runtime!(
         System: frame_system::{Module, Call, Config, Storage, Event<T>},
         Stake: stake::{Module, Call, Storage, Event<T>},
         Balances: balances::{Module, Call, Storage, Event<T>},
-        Fulfillment: fulfillment::{Module, Call, Storage, Event<T>},
+        Fulfillment: fulfillment::{Module, Call, Storage, Event<T>, Config},
     }
 );
 
@@ -73,9 +74,38 @@ impl balances::Config for Test {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    /// Geodes of the mock registry. \[geode, provider, state\]
+    pub static Geodes: Vec<(u64, u64, GeodeState)> = vec![];
+}
+
+pub struct MockGeodeRegistry;
+
+impl pallet_geode::GeodeRegistry<u64> for MockGeodeRegistry {
+    fn provider(geode: &u64) -> Option<u64> {
+        Geodes::get()
+            .into_iter()
+            .find(|(id, _, _)| id == geode)
+            .map(|(_, provider, _)| provider)
+    }
+
+    fn state(geode: &u64) -> Option<GeodeState> {
+        Geodes::get()
+            .into_iter()
+            .find(|(id, _, _)| id == geode)
+            .map(|(_, _, state)| state)
+    }
+}
+
+parameter_types! {
+    pub const MaxMigrationsPerBlock: u32 = 2;
+}
+
 impl fulfillment::Config for Test {
     type Event = Event;
-    type GeodeOffReport = fulfillment::GeodeReportOffenceHandler<u64, Stake>;
+    type Geodes = MockGeodeRegistry;
+    type MaxMigrationsPerBlock = MaxMigrationsPerBlock;
+    type WeightInfo = ();
 }
 
 impl stake::Config for Test {
@@ -94,7 +124,26 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisBuild::<Test>::assimilate_storage(&fulfillment::GenesisConfig {}, &mut t).unwrap();
     let mut ext = sp_io::TestExternalities::new(t);
     ext.execute_with(|| System::set_block_number(1));
     ext
 }
+
+/// Register the attested geode of the provider with the mock registry.
+pub fn register_geode(geode: u64, provider: u64) {
+    let mut geodes = Geodes::get();
+    geodes.push((geode, provider, GeodeState::Attested));
+    Geodes::set(geodes);
+}
+
+pub fn events() -> Vec<Event> {
+    let evt = System::events()
+        .into_iter()
+        .map(|evt| evt.event)
+        .collect::<Vec<_>>();
+
+    System::reset_events();
+
+    evt
+}
@@ -1,8 +1,207 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::mock::*;
+use crate::migration::{OldGeode, OldGeodeState};
+use crate::{mock::*, Commodity, Deal, DealState, Error, LayoutVersion, Releases};
+use codec::Encode;
+use frame_support::storage::migration::{get_storage_value, put_storage_value, storage_key_iter};
+use frame_support::traits::Hooks;
+use frame_support::{assert_noop, assert_ok, Blake2_128Concat, StorageHasher};
+use pallet_geode::GeodeState;
+use sp_core::H256;
+
+const GEODE: u64 = 1;
+const PROVIDER: u64 = 2;
+const ATTESTOR: u64 = 3;
+const USER: u64 = 4;
+
+#[test]
+fn it_works_commodity() {
+    new_test_ext().execute_with(|| {
+        // only attested geodes can be put on the market
+        Geodes::set(vec![(5, PROVIDER, GeodeState::Registered)]);
+        assert!(!Fulfillment::contains_key(&5));
+        assert!(!Fulfillment::set_geode_state(&5, Some(DealState::InOrder)));
+        register_geode(GEODE, PROVIDER);
+        assert!(Fulfillment::contains_key(&GEODE));
+        assert_eq!(Fulfillment::provider(&GEODE), Some(PROVIDER));
+        assert!(!Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InWork)
+        ));
+
+        // listed, taken off the market, listed again and sold
+        assert!(Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InOrder)
+        ));
+        assert!(Fulfillment::set_geode_state(&GEODE, None));
+        assert_eq!(Fulfillment::deals(GEODE), None);
+        assert!(Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InOrder)
+        ));
+        Fulfillment::set_user(&GEODE, USER);
+        assert!(Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InWork)
+        ));
+        assert_eq!(
+            Fulfillment::deals(GEODE),
+            Some(Deal {
+                user: Some(USER),
+                binary: None,
+                state: DealState::InWork,
+            })
+        );
+
+        // sold geodes stay on the market
+        assert!(!Fulfillment::set_geode_state(&GEODE, None));
+        assert!(Fulfillment::contains_key(&GEODE));
+    });
+}
+
+#[test]
+fn it_works_vendor_set_binary() {
+    new_test_ext().execute_with(|| {
+        let hash = H256::repeat_byte(1);
+        assert_noop!(
+            Fulfillment::vendor_set_binary(Origin::signed(USER), GEODE, hash),
+            Error::<Test>::InvalidGeode
+        );
+        register_geode(GEODE, PROVIDER);
+        assert!(Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InOrder)
+        ));
+        Fulfillment::set_user(&GEODE, USER);
+        assert_noop!(
+            Fulfillment::vendor_set_binary(Origin::signed(USER), GEODE, hash),
+            Error::<Test>::InvalidGeodeState
+        );
+
+        assert!(Fulfillment::set_geode_state(
+            &GEODE,
+            Some(DealState::InWork)
+        ));
+        assert_noop!(
+            Fulfillment::vendor_set_binary(Origin::signed(PROVIDER), GEODE, hash),
+            Error::<Test>::NoRight
+        );
+        assert_ok!(Fulfillment::vendor_set_binary(
+            Origin::signed(USER),
+            GEODE,
+            hash
+        ));
+        assert_eq!(Fulfillment::deals(GEODE).unwrap().binary, Some(hash));
+        assert_eq!(
+            events().last(),
+            Some(&Event::fulfillment(crate::Event::SetBinary(GEODE)))
+        );
+    });
+}
+
+#[test]
+fn it_works_genesis_layout() {
+    new_test_ext().execute_with(|| {
+        // new chains start with the current layout and aren't migrated
+        assert_eq!(Fulfillment::layout_version(), Releases::V2);
+        Fulfillment::on_runtime_upgrade();
+        assert_eq!(Fulfillment::layout_version(), Releases::V2);
+    });
+}
 
 #[test]
-fn is_work() {
-    new_test_ext().execute_with(|| {});
+fn it_works_migrate_to_v2() {
+    new_test_ext().execute_with(|| {
+        let binary = H256::repeat_byte(1);
+        let old_geode = |state, binary| OldGeode::<u64, H256> {
+            owner: GEODE,
+            user: Some(USER),
+            provider: Some(PROVIDER),
+            ip: vec![],
+            dns: vec![],
+            props: None,
+            binary,
+            attestors: vec![ATTESTOR],
+            state,
+        };
+        let put_geode = |geode: u64, record: OldGeode<u64, H256>| {
+            put_storage_value(
+                b"Fulfillment",
+                b"Geodes",
+                &Blake2_128Concat::hash(&geode.encode()),
+                record,
+            )
+        };
+        // sold and known to the registry, unknown, and not on the market
+        register_geode(GEODE, PROVIDER);
+        register_geode(6, PROVIDER);
+        put_geode(GEODE, old_geode(OldGeodeState::InWork, Some(binary)));
+        put_geode(5, old_geode(OldGeodeState::InOrder, None));
+        put_geode(6, old_geode(OldGeodeState::Attested, None));
+        put_storage_value(b"Fulfillment", b"AttStakeMin", &[], 100u64);
+        put_storage_value(
+            b"Fulfillment",
+            b"Attestors",
+            &Blake2_128Concat::hash(&ATTESTOR.encode()),
+            (vec![1u8], vec![2u8]),
+        );
+
+        // a chain which ran the V1 layout never recorded it
+        LayoutVersion::<Test>::kill();
+        assert_eq!(Fulfillment::layout_version(), Releases::V1);
+        Fulfillment::on_runtime_upgrade();
+        assert_eq!(Fulfillment::layout_version(), Releases::MigratingToV2);
+
+        // two items every block: the last geode and AttStakeMin, then Attestors
+        Fulfillment::on_initialize(2);
+        assert_eq!(Fulfillment::layout_version(), Releases::MigratingToV2);
+        Fulfillment::on_initialize(3);
+        assert_eq!(Fulfillment::layout_version(), Releases::V2);
+        assert!(
+            events().contains(&Event::fulfillment(crate::Event::DealDropped(
+                5,
+                Some(USER)
+            )))
+        );
+        assert_eq!(
+            Fulfillment::deals(GEODE),
+            Some(Deal {
+                user: Some(USER),
+                binary: Some(binary),
+                state: DealState::InWork,
+            })
+        );
+        assert_eq!(Fulfillment::deals(5), None);
+        assert_eq!(Fulfillment::deals(6), None);
+        assert_eq!(
+            storage_key_iter::<u64, OldGeode<u64, H256>, Blake2_128Concat>(
+                b"Fulfillment",
+                b"Geodes"
+            )
+            .count(),
+            0
+        );
+        assert_eq!(
+            get_storage_value::<u64>(b"Fulfillment", b"AttStakeMin", &[]),
+            None
+        );
+        assert_eq!(
+            get_storage_value::<(Vec<u8>, Vec<u8>)>(
+                b"Fulfillment",
+                b"Attestors",
+                &Blake2_128Concat::hash(&ATTESTOR.encode())
+            ),
+            None
+        );
+
+        // the migrated geode stays sold, later upgrades leave it alone
+        assert_ok!(Fulfillment::vendor_set_binary(
+            Origin::signed(USER),
+            GEODE,
+            H256::repeat_byte(2)
+        ));
+        Fulfillment::on_runtime_upgrade();
+        assert!(Fulfillment::deals(GEODE).is_some());
+    });
 }
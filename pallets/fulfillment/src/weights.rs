@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weights for pallet_fulfillment
+//!
+//! The base weights are estimated from the storage accesses of each call until they are
+//! regenerated from the benchmarks on the reference hardware with:
+//!
+//! ./scripts/benchmark-weights.sh fulfillment
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_fulfillment.
+pub trait WeightInfo {
+    fn vendor_set_binary() -> Weight;
+    fn migrate_geode() -> Weight;
+}
+
+/// Weights for pallet_fulfillment using the Substrate node and recommended hardware.
+/// Migrating a geode covers taking its V1 record, checking its provider and writing its deal.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn vendor_set_binary() -> Weight {
+        (31_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn migrate_geode() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn vendor_set_binary() -> Weight {
+        (31_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn migrate_geode() -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+}
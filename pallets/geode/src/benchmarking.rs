@@ -0,0 +1,255 @@
+//! Benchmarking setup for pallet-geode
+
+use super::*;
+
+use crate::network::{MAX_HOSTNAME_LEN, MAX_LABEL_LEN};
+use crate::Pallet as GeodeModule;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Get, Hooks};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+const SEED: u32 = 0;
+
+fn funded_provider<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+    let provider: T::AccountId = account(name, index, SEED);
+    <T as Config>::Currency::make_free_balance_be(
+        &provider,
+        BalanceOf::<T>::max_value() / 2u32.into(),
+    );
+    provider
+}
+
+/// The longest valid dns, made of labels as long as allowed.
+fn longest_dns<T: Config>(c: u8) -> Vec<u8> {
+    let len = (T::MaxDnsLength::get() as usize).min(MAX_HOSTNAME_LEN);
+    let mut dns = (0..len)
+        .map(|i| {
+            if i % (MAX_LABEL_LEN + 1) == MAX_LABEL_LEN {
+                b'.'
+            } else {
+                c
+            }
+        })
+        .collect::<Vec<_>>();
+    if dns.last() == Some(&b'.') {
+        dns.pop();
+    }
+    dns
+}
+
+/// A geode with the longest ip and dns and as many properties as allowed.
+fn full_geode<T: Config>(provider: &T::AccountId, index: u32) -> GeodeOf<T> {
+    let max_len = T::MaxPropLength::get() as usize;
+    let mut props = BTreeMap::new();
+    for i in 0..T::MaxProps::get() {
+        let mut name = vec![b'p'; max_len];
+        name[..4].copy_from_slice(&i.to_be_bytes());
+        props.insert(name, vec![b'v'; max_len]);
+    }
+    GeodeOf::<T> {
+        id: account("geode", index, SEED),
+        provider: provider.clone(),
+        ip: vec![1u8; 16],
+        dns: longest_dns::<T>(b'd'),
+        props,
+        ..Default::default()
+    }
+}
+
+fn register_geode<T: Config>(provider: &T::AccountId, index: u32) -> T::AccountId {
+    let geode = full_geode::<T>(provider, index);
+    let id = geode.id.clone();
+    GeodeModule::<T>::provider_register_geode(RawOrigin::Signed(provider.clone()).into(), geode)
+        .unwrap();
+    id
+}
+
+fn register_geodes<T: Config>(provider: &T::AccountId, n: u32) -> Vec<T::AccountId> {
+    (0..n).map(|i| register_geode::<T>(provider, i)).collect()
+}
+
+benchmarks! {
+    provider_register_geode {
+        let provider = funded_provider::<T>("provider", 0);
+        GeodeDeposit::<T>::put(BalanceOf::<T>::from(1000u32));
+        let geode = full_geode::<T>(&provider, 0);
+        let id = geode.id.clone();
+    }: _(RawOrigin::Signed(provider), geode)
+    verify {
+        assert_eq!(Geodes::<T>::get(&id).state, GeodeState::Registered);
+    }
+
+    geode_remove {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+    }: _(RawOrigin::Signed(provider), geode.clone())
+    verify {
+        assert!(!Geodes::<T>::contains_key(&geode));
+    }
+
+    update_geode_props {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+        let name = Geodes::<T>::get(&geode).props.keys().next().cloned().unwrap();
+        let value = vec![b'w'; T::MaxPropLength::get() as usize];
+    }: _(RawOrigin::Signed(provider), geode.clone(), name.clone(), value.clone())
+    verify {
+        assert_eq!(Geodes::<T>::get(&geode).props.get(&name), Some(&value));
+    }
+
+    update_geode_dns {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+        let dns = longest_dns::<T>(b'e');
+    }: _(RawOrigin::Signed(provider), geode.clone(), dns.clone())
+    verify {
+        assert_eq!(Geodes::<T>::get(&geode).dns, dns);
+    }
+
+    update_geode_promise {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+    }: _(RawOrigin::Signed(provider), geode.clone(), 100)
+    verify {
+        assert_eq!(Geodes::<T>::get(&geode).promise, 100);
+    }
+
+    turn_geode_offline {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+    }: _(RawOrigin::Signed(provider), geode.clone())
+    verify {
+        assert_eq!(Geodes::<T>::get(&geode).state, GeodeState::Offline);
+    }
+
+    turn_geode_online {
+        let provider = funded_provider::<T>("provider", 0);
+        let geode = register_geode::<T>(&provider, 0);
+        GeodeModule::<T>::turn_geode_offline(
+            RawOrigin::Signed(provider.clone()).into(),
+            geode.clone(),
+        )?;
+    }: _(RawOrigin::Signed(provider), geode.clone())
+    verify {
+        assert_eq!(Geodes::<T>::get(&geode).state, GeodeState::Registered);
+    }
+
+    provider_register {
+        let caller: T::AccountId = whitelisted_caller();
+        <T as Config>::Currency::make_free_balance_be(
+            &caller,
+            BalanceOf::<T>::max_value() / 2u32.into(),
+        );
+        let metadata = vec![1u8; MAX_PROVIDER_METADATA_LENGTH];
+        let url = vec![2u8; MAX_PROVIDER_URL_LENGTH];
+    }: _(RawOrigin::Signed(caller.clone()), metadata, url, 1000u32.into())
+    verify {
+        assert!(Providers::<T>::contains_key(&caller));
+    }
+
+    provider_update {
+        let provider = funded_provider::<T>("provider", 0);
+        GeodeModule::<T>::provider_register(
+            RawOrigin::Signed(provider.clone()).into(),
+            vec![1u8; MAX_PROVIDER_METADATA_LENGTH],
+            vec![2u8; MAX_PROVIDER_URL_LENGTH],
+            1000u32.into(),
+        )?;
+        let url = vec![4u8; MAX_PROVIDER_URL_LENGTH];
+    }: _(RawOrigin::Signed(provider.clone()), vec![3u8; MAX_PROVIDER_METADATA_LENGTH], url.clone())
+    verify {
+        assert_eq!(Providers::<T>::get(&provider).url, url);
+    }
+
+    provider_bond {
+        let provider = funded_provider::<T>("provider", 0);
+        GeodeModule::<T>::provider_register(
+            RawOrigin::Signed(provider.clone()).into(),
+            vec![1u8; MAX_PROVIDER_METADATA_LENGTH],
+            vec![2u8; MAX_PROVIDER_URL_LENGTH],
+            1000u32.into(),
+        )?;
+    }: _(RawOrigin::Signed(provider.clone()), 1000u32.into())
+    verify {
+        assert_eq!(Providers::<T>::get(&provider).stake, 2000u32.into());
+    }
+
+    provider_deregister {
+        let provider = funded_provider::<T>("provider", 0);
+        GeodeModule::<T>::provider_register(
+            RawOrigin::Signed(provider.clone()).into(),
+            vec![1u8; MAX_PROVIDER_METADATA_LENGTH],
+            vec![2u8; MAX_PROVIDER_URL_LENGTH],
+            1000u32.into(),
+        )?;
+    }: _(RawOrigin::Signed(provider.clone()))
+    verify {
+        assert!(!Providers::<T>::contains_key(&provider));
+    }
+
+    provider_offline_geodes {
+        let n in 1 .. MAX_GEODES_PER_CALL as u32;
+        let provider = funded_provider::<T>("provider", 0);
+        let geodes = register_geodes::<T>(&provider, n);
+    }: _(RawOrigin::Signed(provider), geodes.clone())
+    verify {
+        for geode in geodes.iter() {
+            assert_eq!(Geodes::<T>::get(geode).state, GeodeState::Offline);
+        }
+    }
+
+    provider_remove_geodes {
+        let n in 1 .. MAX_GEODES_PER_CALL as u32;
+        let provider = funded_provider::<T>("provider", 0);
+        let geodes = register_geodes::<T>(&provider, n);
+    }: _(RawOrigin::Signed(provider.clone()), geodes)
+    verify {
+        assert_eq!(Providers::<T>::get(&provider).geode_num, 0);
+    }
+
+    provider_transfer_geodes {
+        let n in 1 .. MAX_GEODES_PER_CALL as u32;
+        let provider = funded_provider::<T>("provider", 0);
+        let to = funded_provider::<T>("provider", 1);
+        GeodeModule::<T>::provider_register(
+            RawOrigin::Signed(to.clone()).into(),
+            vec![],
+            vec![],
+            1000u32.into(),
+        )?;
+        let geodes = register_geodes::<T>(&provider, n);
+    }: _(RawOrigin::Signed(provider), geodes, to.clone())
+    verify {
+        assert_eq!(Providers::<T>::get(&to).geode_num, n);
+    }
+
+    on_initialize {
+        let n in 1 .. MAX_GEODES_PER_CALL as u32;
+        frame_system::Pallet::<T>::set_block_number(1u32.into());
+        let provider = funded_provider::<T>("provider", 0);
+        let geodes = register_geodes::<T>(&provider, n);
+        GeodeModule::<T>::provider_remove_geodes(
+            RawOrigin::Signed(provider).into(),
+            geodes.clone(),
+        )?;
+        let now = 1 + HISTORY_RETENTION_BLOCK_NUMBER;
+    }: {
+        GeodeModule::<T>::on_initialize(now.into());
+    }
+    verify {
+        for geode in geodes.iter() {
+            assert!(GeodeHistory::<T>::get(geode).is_empty());
+        }
+    }
+
+    set_geode_deposit {
+        let deposit: BalanceOf<T> = 1000u32.into();
+    }: _(RawOrigin::Root, deposit)
+    verify {
+        assert_eq!(GeodeDeposit::<T>::get(), deposit);
+    }
+}
+
+impl_benchmark_test_suite!(GeodeModule, crate::mock::new_test_ext(), crate::mock::Test,);
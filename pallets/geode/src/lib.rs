@@ -1,6 +1,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+mod network;
+mod property;
 
 #[cfg(test)]
 mod mock;
@@ -8,15 +10,35 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
 #[frame_support::pallet]
 pub mod pallet {
+    pub use crate::network::{validate_dns, validate_ip};
+    pub use crate::property::{
+        validate_prop, validate_props, Operator, PropKey, PropValue, Requirement, Selector,
+        TcbLevel,
+    };
+    use crate::weights::WeightInfo;
     use codec::{Decode, Encode};
     use frame_support::ensure;
     use frame_support::pallet_prelude::*;
+    use frame_support::traits::{BalanceStatus, Currency, ReservableCurrency};
+    use frame_support::transactional;
     use frame_system::pallet_prelude::*;
     use primitives::BlockNumber;
-    use sp_runtime::{RuntimeDebug, SaturatedConversion};
-    use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+    use sp_runtime::{
+        traits::{Saturating, Zero},
+        RuntimeDebug, SaturatedConversion,
+    };
+    use sp_std::{
+        collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+        prelude::*,
+    };
 
     #[cfg(feature = "std")]
     use serde::{Deserialize, Serialize};
@@ -41,6 +63,52 @@ pub mod pallet {
         Null,
     }
 
+    /// Why a geode changed its state
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub enum TransitionCause {
+        /// Provider registered, removed or turned the geode online/offline.
+        ProviderAction,
+        /// Attestors attested the geode.
+        Attestation,
+        /// An attestor attesting the geode exited or timed out.
+        AttestorRemoval,
+        /// The geode stayed in a state longer than liveness allows.
+        LivenessExpiry,
+        /// Attestors reported a misconduct of the geode.
+        Misconduct,
+        /// The geode started or stopped serving an order.
+        Order,
+        /// Governance reversed the geode's slash on appeal.
+        Appeal,
+        /// The number of attestors the geode needs changed.
+        AttestorTarget,
+        /// Governance revoked the enclave build the geode runs.
+        EnclaveRevocation,
+    }
+
+    /// A record of a geode's state change
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub struct StateTransition {
+        pub from: GeodeState,
+        pub to: GeodeState,
+        /// Block number when the transition happened
+        pub block: BlockNumber,
+        pub cause: TransitionCause,
+    }
+
+    /// Number of the latest transitions kept in a geode's history.
+    pub const MAX_GEODE_HISTORY_LEN: usize = 32;
+    /// Number of blocks the history of a removed geode is kept for.
+    pub const HISTORY_RETENTION_BLOCK_NUMBER: BlockNumber = 14_400;
+    /// Max number of geodes a provider can offline/remove/transfer in one call.
+    pub const MAX_GEODES_PER_CALL: usize = 100;
+    pub const DEFAULT_PROVIDER_REPUTATION: u32 = 100;
+    /// Max length of a provider's metadata and of its url.
+    pub const MAX_PROVIDER_METADATA_LENGTH: usize = 128;
+    pub const MAX_PROVIDER_URL_LENGTH: usize = 256;
+
     #[derive(PartialEq, Eq, Clone, RuntimeDebug)]
     pub enum DetachOption {
         /// Remove the geode
@@ -57,6 +125,49 @@ pub mod pallet {
         }
     }
 
+    /// All the state transitions a geode is allowed to take. `Null` stands for a geode which
+    /// is not in the registry, so `(Null, Registered)` is a registration and `(_, Null)` a removal.
+    pub const GEODE_STATE_TRANSITIONS: [(GeodeState, GeodeState); 20] = [
+        (GeodeState::Null, GeodeState::Registered),
+        (GeodeState::Registered, GeodeState::Attested),
+        (GeodeState::Registered, GeodeState::Offline),
+        (GeodeState::Registered, GeodeState::Unknown),
+        (GeodeState::Registered, GeodeState::Null),
+        (GeodeState::Attested, GeodeState::Registered),
+        (GeodeState::Attested, GeodeState::Instantiated),
+        (GeodeState::Attested, GeodeState::Offline),
+        (GeodeState::Attested, GeodeState::Unknown),
+        (GeodeState::Attested, GeodeState::Null),
+        (GeodeState::Instantiated, GeodeState::Attested),
+        (GeodeState::Instantiated, GeodeState::DegradedInstantiated),
+        (GeodeState::Instantiated, GeodeState::Unknown),
+        (GeodeState::DegradedInstantiated, GeodeState::Registered),
+        (GeodeState::DegradedInstantiated, GeodeState::Instantiated),
+        (GeodeState::DegradedInstantiated, GeodeState::Unknown),
+        (GeodeState::Offline, GeodeState::Registered),
+        (GeodeState::Offline, GeodeState::Null),
+        (GeodeState::Unknown, GeodeState::Registered),
+        (GeodeState::Unknown, GeodeState::Null),
+    ];
+
+    impl GeodeState {
+        /// Whether `GEODE_STATE_TRANSITIONS` allows a geode to move from this state to `to`.
+        pub fn can_transit_to(&self, to: &GeodeState) -> bool {
+            GEODE_STATE_TRANSITIONS
+                .iter()
+                .any(|(from, into)| from == self && into == to)
+        }
+
+        /// Whether geodes can only stay in this state for a limited time, such geodes are
+        /// queued in `StateQueues` so that the timeouts are enforced without scanning.
+        pub fn is_timed(&self) -> bool {
+            matches!(
+                self,
+                GeodeState::Registered | GeodeState::DegradedInstantiated | GeodeState::Unknown
+            )
+        }
+    }
+
     /// The geode struct shows its status
     #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
@@ -82,17 +193,157 @@ pub mod pallet {
     pub type GeodeOf<T> =
         Geode<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::Hash>;
 
+    /// The provider struct, created when an account registers as provider or registers
+    /// its first geode.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct Provider<Balance> {
+        /// Provider's metadata, e.g. its name.
+        pub metadata: Vec<u8>,
+        /// Url to contact the provider.
+        pub url: Vec<u8>,
+        /// Balance reserved as the provider's stake.
+        pub stake: Balance,
+        /// Provider's reputation, starts from `DEFAULT_PROVIDER_REPUTATION`.
+        pub reputation: u32,
+        /// Number of geodes the provider owns.
+        pub geode_num: u32,
+    }
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type ProviderOf<T> = Provider<BalanceOf<T>>;
+    pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::NegativeImbalance;
+
+    /// Resolves who placed the order a geode is serving.
+    pub trait OrderHolder<AccountId, Hash> {
+        /// Return the user who placed the order, `None` if there is no such order.
+        fn holder(order: &Hash) -> Option<AccountId>;
+    }
+
+    impl<AccountId, Hash> OrderHolder<AccountId, Hash> for () {
+        fn holder(_order: &Hash) -> Option<AccountId> {
+            None
+        }
+    }
+
+    /// Notified of the geodes getting registered.
+    pub trait OnGeodeRegister<AccountId> {
+        fn on_geode_register(geode: &AccountId);
+    }
+
+    impl<AccountId> OnGeodeRegister<AccountId> for () {
+        fn on_geode_register(_geode: &AccountId) {}
+    }
+
+    /// Notified of the geodes getting their properties updated.
+    pub trait OnGeodePropsUpdate<AccountId> {
+        fn on_geode_props_update(geode: &AccountId);
+    }
+
+    impl<AccountId> OnGeodePropsUpdate<AccountId> for () {
+        fn on_geode_props_update(_geode: &AccountId) {}
+    }
+
+    /// Notified of the geodes getting removed.
+    pub trait OnGeodeRemove<AccountId> {
+        fn on_geode_remove(geode: &AccountId);
+    }
+
+    impl<AccountId> OnGeodeRemove<AccountId> for () {
+        fn on_geode_remove(_geode: &AccountId) {}
+    }
+
+    /// Tells which geodes have their funds held for a slash.
+    pub trait SlashRegistry<AccountId> {
+        /// Whether the geode has a slash waiting to be applied or decided.
+        fn slash_pending(geode: &AccountId) -> bool;
+    }
+
+    impl<AccountId> SlashRegistry<AccountId> for () {
+        fn slash_pending(_geode: &AccountId) -> bool {
+            false
+        }
+    }
+
+    /// Gives the registered geodes, e.g. to sell the attested ones.
+    pub trait GeodeRegistry<AccountId> {
+        /// Provider of the geode, `None` if it isn't registered.
+        fn provider(geode: &AccountId) -> Option<AccountId>;
+        /// State of the geode, `None` if it isn't registered.
+        fn state(geode: &AccountId) -> Option<GeodeState>;
+    }
+
+    impl<AccountId> GeodeRegistry<AccountId> for () {
+        fn provider(_geode: &AccountId) -> Option<AccountId> {
+            None
+        }
+
+        fn state(_geode: &AccountId) -> Option<GeodeState> {
+            None
+        }
+    }
+
     #[pallet::config]
     pub trait Config: frame_system::Config + pallet_attestor::Config {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The currency in which providers stake and pay geode deposits.
+        type Currency: ReservableCurrency<Self::AccountId>;
+        /// Max length of a geode's dns.
+        #[pallet::constant]
+        type MaxDnsLength: Get<u32>;
+        /// Max number of properties a geode can have.
+        #[pallet::constant]
+        type MaxProps: Get<u32>;
+        /// Max length of a property's name and of its value.
+        #[pallet::constant]
+        type MaxPropLength: Get<u32>;
+        /// Deposit reserved for each byte of a geode's ip, dns and properties.
+        #[pallet::constant]
+        type DepositPerByte: Get<BalanceOf<Self>>;
+        /// Handler of newly registered geodes, e.g. liveness assigning attestors to them.
+        type OnGeodeRegister: OnGeodeRegister<Self::AccountId>;
+        /// Handler of geodes whose properties changed, e.g. liveness checking the number of
+        /// attestors they need.
+        type OnGeodePropsUpdate: OnGeodePropsUpdate<Self::AccountId>;
+        /// Handler of removed geodes, e.g. liveness forgetting their assignments.
+        type OnGeodeRemove: OnGeodeRemove<Self::AccountId>;
+        /// Slashes holding on to geodes, which can't be removed or transferred meanwhile, e.g.
+        /// liveness during the appeal window.
+        type Slashes: SlashRegistry<Self::AccountId>;
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// At every block, prune the history of the geodes removed
+        /// `HISTORY_RETENTION_BLOCK_NUMBER` blocks before, unless they got registered or
+        /// removed again since.
+        fn on_initialize(block_number: T::BlockNumber) -> Weight {
+            let mut pruned = 0u32;
+            let now = block_number.saturated_into::<BlockNumber>();
+            if let Some(block) = now.checked_sub(HISTORY_RETENTION_BLOCK_NUMBER) {
+                for geode in <HistoryPruneQueue<T>>::take(block).iter() {
+                    pruned += 1;
+                    let removed_at = <GeodeHistory<T>>::get(geode)
+                        .last()
+                        .filter(|transition| transition.to == GeodeState::Null)
+                        .map(|transition| transition.block);
+                    if !<Geodes<T>>::contains_key(geode) && removed_at == Some(block) {
+                        <GeodeHistory<T>>::remove(geode);
+                    }
+                }
+            }
+            <T as Config>::WeightInfo::on_initialize(pruned)
+        }
+    }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T> = "Balance")]
     pub enum Event<T: Config> {
         /// Provider register geode. \[provider_id, geode_id\]
         GeodeRegister(T::AccountId, T::AccountId),
@@ -111,6 +362,20 @@ pub mod pallet {
         GeodeStateUpdate(T::AccountId, GeodeState),
         /// Geode's promise updated
         GeodePromiseUpdate(T::AccountId),
+        /// Provider registered. \[provider_id\]
+        ProviderRegister(T::AccountId),
+        /// Provider's metadata or url updated. \[provider_id\]
+        ProviderUpdate(T::AccountId),
+        /// Provider bonded more stake. \[provider_id, amount\]
+        ProviderBond(T::AccountId, BalanceOf<T>),
+        /// Provider deregistered and got its stake back. \[provider_id\]
+        ProviderDeregister(T::AccountId),
+        /// Geode transferred to another provider. \[geode_id, from_provider, to_provider\]
+        GeodeTransfer(T::AccountId, T::AccountId, T::AccountId),
+        /// Geode's deposit slashed for misbehaving. \[geode_id, amount\]
+        DepositSlash(T::AccountId, BalanceOf<T>),
+        /// Provider's stake slashed for a misbehaving geode. \[provider_id, amount\]
+        StakeSlash(T::AccountId, BalanceOf<T>),
     }
 
     #[pallet::error]
@@ -129,6 +394,30 @@ pub mod pallet {
         GeodeInOrder,
         /// Invalid input
         InvalidInput,
+        /// A well known property doesn't follow the property schema.
+        InvalidProperty,
+        /// The geode's state can't transit to the requested state.
+        IllegalTransition,
+        /// Provider already registered.
+        AlreadyProvider,
+        /// Use an invalid provider id.
+        InvalidProvider,
+        /// The provider still owns geodes so you can't do this.
+        ProviderHasGeodes,
+        /// The ip is neither an IPv4 nor an IPv6 address.
+        InvalidIp,
+        /// The dns is too long or not a valid hostname.
+        InvalidDns,
+        /// The geode has too many properties.
+        TooManyProps,
+        /// A property's name or value is too long.
+        PropTooLong,
+        /// The provider's metadata or url is too long.
+        ProviderInfoTooLong,
+        /// The provider's reserved balance doesn't cover the geode deposit.
+        InsufficientDeposit,
+        /// The geode has a slash pending so you can't do this.
+        GeodeSlashPending,
     }
 
     #[pallet::pallet]
@@ -175,11 +464,66 @@ pub mod pallet {
     pub type GeodeUpdateCounters<T: Config> =
         StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
 
+    /// Geodes entering a timed state, indexed by the state and the block they entered it.
+    /// Entries are left behind when geodes leave the state, consumers have to check them
+    /// against `state_since`.
+    #[pallet::storage]
+    #[pallet::getter(fn state_queues)]
+    pub type StateQueues<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        GeodeState,
+        Twox64Concat,
+        BlockNumber,
+        Vec<T::AccountId>,
+        ValueQuery,
+    >;
+
+    /// Latest state transitions of each geode, oldest first, kept across registrations. The
+    /// history of a removed geode is kept for `HISTORY_RETENTION_BLOCK_NUMBER` blocks so that
+    /// its removal can still be explained.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_history)]
+    pub type GeodeHistory<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Vec<StateTransition>, ValueQuery>;
+
+    /// Removed geodes indexed by the block they got removed, to prune their history. Entries
+    /// are left behind when geodes are registered again, they are checked against
+    /// `GeodeHistory` when processed.
+    #[pallet::storage]
+    #[pallet::getter(fn history_prune_queue)]
+    pub type HistoryPruneQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn providers)]
+    pub type Providers<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, ProviderOf<T>, ValueQuery>;
+
+    /// Deposit a provider has to reserve for each geode it registers.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_deposit)]
+    pub type GeodeDeposit<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// Deposit reserved from the provider for each geode. It is forfeited when the geode is
+    /// slashed, and returned when the geode is removed otherwise.
+    #[pallet::storage]
+    #[pallet::getter(fn reserved_deposits)]
+    pub type ReservedDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    /// Geodes owned by each provider.
+    #[pallet::storage]
+    #[pallet::getter(fn provider_geodes)]
+    pub type ProviderGeodes<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BTreeSet<T::AccountId>, ValueQuery>;
+
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Called by provider to register a geode. The user/attestors/state/provider will be
-        /// set automatically regardless of what you set.
-        #[pallet::weight(0)]
+        /// set automatically regardless of what you set. `GeodeDeposit` plus `DepositPerByte`
+        /// for each byte of the ip, dns and properties is reserved from the provider.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_register_geode())]
         pub fn provider_register_geode(
             origin: OriginFor<T>,
             geode_record: GeodeOf<T>,
@@ -188,34 +532,54 @@ pub mod pallet {
             let mut geode_record = geode_record;
             let geode = geode_record.id.clone();
             ensure!(!<Geodes<T>>::contains_key(&geode), Error::<T>::AlreadyGeode);
+            ensure!(validate_ip(&geode_record.ip), Error::<T>::InvalidIp);
+            Self::ensure_valid_dns(&geode_record.dns)?;
+            Self::ensure_valid_props(&geode_record.props)?;
 
-            let block_number = <frame_system::Pallet<T>>::block_number();
-            geode_record.state = GeodeState::Registered;
+            let deposit = <GeodeDeposit<T>>::get()
+                .saturating_add(Self::byte_deposit(Self::record_bytes(&geode_record)));
+            <T as Config>::Currency::reserve(&who, deposit)?;
+            <ReservedDeposits<T>>::insert(&geode, deposit);
+
+            geode_record.state = GeodeState::Null;
+            geode_record.order = None;
             geode_record.provider = who.clone();
 
             <Geodes<T>>::insert(geode.clone(), geode_record);
-            <RegisteredGeodes<T>>::insert(&geode, block_number.saturated_into::<BlockNumber>());
+            Self::transit(
+                &geode,
+                GeodeState::Registered,
+                TransitionCause::ProviderAction,
+            )?;
             <GeodeUpdateCounters<T>>::insert(&geode, 0);
+            Self::add_provider_geode(&who, &geode);
+            T::OnGeodeRegister::on_geode_register(&geode);
             Self::deposit_event(Event::GeodeRegister(who, geode));
             Ok(().into())
         }
 
-        /// Called by provider to remove geode .
-        /// Return Ok() only when the geode's state is Registered/Attested
-        #[pallet::weight(0)]
+        /// Called by provider to remove geode, the geode's deposit is returned.
+        /// Return Ok() only when the geode's state is Registered/Attested/Unknown and it has no
+        /// slash pending
+        #[pallet::weight(<T as Config>::WeightInfo::geode_remove())]
         pub fn geode_remove(
             origin: OriginFor<T>,
             geode: T::AccountId,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            match Self::detach_geode(DetachOption::Remove, geode, Some(who)) {
+            match Self::detach_geode(
+                DetachOption::Remove,
+                geode,
+                Some(who),
+                TransitionCause::ProviderAction,
+            ) {
                 Ok(_) => Ok(().into()),
                 Err(e) => Err(e.into()),
             }
         }
 
         /// Called by provider to update geode properties
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::update_geode_props())]
         pub fn update_geode_props(
             origin: OriginFor<T>,
             geode: T::AccountId,
@@ -225,15 +589,23 @@ pub mod pallet {
             let who = ensure_signed(origin)?;
             let mut geode_use = <Geodes<T>>::get(&geode);
             ensure!(geode_use.provider == who, Error::<T>::NoRight);
+            ensure!(
+                validate_prop(&prop_name, &prop_value),
+                Error::<T>::InvalidProperty
+            );
+            let old_bytes = Self::record_bytes(&geode_use);
             geode_use.props.insert(prop_name, prop_value);
+            Self::ensure_valid_props(&geode_use.props)?;
+            Self::adjust_deposit(&geode, &who, old_bytes, Self::record_bytes(&geode_use))?;
             <Geodes<T>>::insert(&geode, geode_use);
             <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
+            T::OnGeodePropsUpdate::on_geode_props_update(&geode);
             Self::deposit_event(Event::PropsUpdate(geode));
             Ok(().into())
         }
 
         /// Called by provider to bound dns to geode's ip.
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::update_geode_dns())]
         pub fn update_geode_dns(
             origin: OriginFor<T>,
             geode: T::AccountId,
@@ -242,7 +614,10 @@ pub mod pallet {
             let who = ensure_signed(origin)?;
             let mut geode_use = <Geodes<T>>::get(&geode);
             ensure!(geode_use.provider == who, Error::<T>::NoRight);
+            Self::ensure_valid_dns(&dns)?;
+            let old_bytes = Self::record_bytes(&geode_use);
             geode_use.dns = dns;
+            Self::adjust_deposit(&geode, &who, old_bytes, Self::record_bytes(&geode_use))?;
             <Geodes<T>>::insert(&geode, geode_use);
             <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
             Self::deposit_event(Event::DnsUpdate(geode));
@@ -250,7 +625,7 @@ pub mod pallet {
         }
 
         /// Called by provider to set promise block number
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::update_geode_promise())]
         pub fn update_geode_promise(
             origin: OriginFor<T>,
             geode: T::AccountId,
@@ -273,43 +648,252 @@ pub mod pallet {
         }
 
         /// Called by provider to turn geode offline
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::turn_geode_offline())]
         pub fn turn_geode_offline(
             origin: OriginFor<T>,
             geode: T::AccountId,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            match Self::detach_geode(DetachOption::Offline, geode, Some(who)) {
+            match Self::detach_geode(
+                DetachOption::Offline,
+                geode,
+                Some(who),
+                TransitionCause::ProviderAction,
+            ) {
                 Ok(_) => Ok(().into()),
                 Err(e) => Err(e.into()),
             }
         }
 
         /// Called by provider to turn geode online
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::turn_geode_online())]
         pub fn turn_geode_online(
             origin: OriginFor<T>,
             geode: T::AccountId,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
+            ensure!(<Geodes<T>>::contains_key(&geode), Error::<T>::InvalidGeode);
+            let geode_use = <Geodes<T>>::get(&geode);
+            ensure!(geode_use.provider == who, Error::<T>::NoRight);
             ensure!(
-                !<OfflineGeodes<T>>::contains_key(&geode),
+                geode_use.state == GeodeState::Offline,
                 Error::<T>::InvalidGeodeState
             );
-            let mut geode_use = <Geodes<T>>::get(&geode);
-            ensure!(geode_use.provider == who, Error::<T>::NoRight);
+            Self::transit(
+                &geode,
+                GeodeState::Registered,
+                TransitionCause::ProviderAction,
+            )?;
+            Ok(().into())
+        }
 
-            geode_use.state = GeodeState::Registered;
-            <Geodes<T>>::insert(&geode, &geode_use);
+        /// Register as a provider with `stake` reserved.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_register())]
+        pub fn provider_register(
+            origin: OriginFor<T>,
+            metadata: Vec<u8>,
+            url: Vec<u8>,
+            stake: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                !<Providers<T>>::contains_key(&who),
+                Error::<T>::AlreadyProvider
+            );
+            Self::ensure_valid_provider_info(&metadata, &url)?;
+            <T as Config>::Currency::reserve(&who, stake)?;
 
-            let block_number = <frame_system::Pallet<T>>::block_number();
-            <RegisteredGeodes<T>>::insert(&geode, block_number.saturated_into::<BlockNumber>());
+            let provider = ProviderOf::<T> {
+                metadata,
+                url,
+                stake,
+                reputation: DEFAULT_PROVIDER_REPUTATION,
+                geode_num: 0,
+            };
+            <Providers<T>>::insert(&who, provider);
+            Self::deposit_event(Event::ProviderRegister(who));
+            Ok(().into())
+        }
 
-            <OfflineGeodes<T>>::remove(&geode);
+        /// Called by provider to update its metadata and contact url.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_update())]
+        pub fn provider_update(
+            origin: OriginFor<T>,
+            metadata: Vec<u8>,
+            url: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <Providers<T>>::contains_key(&who),
+                Error::<T>::InvalidProvider
+            );
+            Self::ensure_valid_provider_info(&metadata, &url)?;
+            <Providers<T>>::mutate(&who, |provider| {
+                provider.metadata = metadata;
+                provider.url = url;
+            });
+            Self::deposit_event(Event::ProviderUpdate(who));
+            Ok(().into())
+        }
 
-            <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
+        /// Called by provider to reserve more stake.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_bond())]
+        pub fn provider_bond(
+            origin: OriginFor<T>,
+            value: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <Providers<T>>::contains_key(&who),
+                Error::<T>::InvalidProvider
+            );
+            <T as Config>::Currency::reserve(&who, value)?;
+            <Providers<T>>::mutate(&who, |provider| {
+                provider.stake = provider.stake.saturating_add(value)
+            });
+            Self::deposit_event(Event::ProviderBond(who, value));
+            Ok(().into())
+        }
+
+        /// Called by provider owning no geode to deregister and get its stake back. Geodes with
+        /// a slash pending can't be removed or transferred, so their provider stays until the
+        /// slash is over.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_deregister())]
+        pub fn provider_deregister(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <Providers<T>>::contains_key(&who),
+                Error::<T>::InvalidProvider
+            );
+            let provider = <Providers<T>>::get(&who);
+            ensure!(provider.geode_num == 0, Error::<T>::ProviderHasGeodes);
+
+            <T as Config>::Currency::unreserve(&who, provider.stake);
+            <Providers<T>>::remove(&who);
+            <ProviderGeodes<T>>::remove(&who);
+            Self::deposit_event(Event::ProviderDeregister(who));
+            Ok(().into())
+        }
+
+        /// Called by provider to turn a list of its geodes offline.
+        /// Either all the geodes turn offline or none of them does.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_offline_geodes(geodes.len() as u32))]
+        #[transactional]
+        pub fn provider_offline_geodes(
+            origin: OriginFor<T>,
+            geodes: Vec<T::AccountId>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::ensure_provider_geodes(&who, &geodes)?;
+            for geode in geodes.iter() {
+                ensure!(
+                    <Geodes<T>>::get(&geode)
+                        .state
+                        .can_transit_to(&GeodeState::Offline),
+                    Error::<T>::IllegalTransition
+                );
+            }
+
+            for geode in geodes {
+                Self::detach_geode(
+                    DetachOption::Offline,
+                    geode,
+                    Some(who.clone()),
+                    TransitionCause::ProviderAction,
+                )?;
+            }
+            Ok(().into())
+        }
+
+        /// Called by provider to remove a list of its geodes.
+        /// Either all the geodes get removed or none of them does.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_remove_geodes(geodes.len() as u32))]
+        #[transactional]
+        pub fn provider_remove_geodes(
+            origin: OriginFor<T>,
+            geodes: Vec<T::AccountId>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::ensure_provider_geodes(&who, &geodes)?;
+            for geode in geodes.iter() {
+                ensure!(
+                    <Geodes<T>>::get(&geode)
+                        .state
+                        .can_transit_to(&GeodeState::Null),
+                    Error::<T>::IllegalTransition
+                );
+                ensure!(
+                    !T::Slashes::slash_pending(geode),
+                    Error::<T>::GeodeSlashPending
+                );
+            }
+
+            for geode in geodes {
+                Self::detach_geode(
+                    DetachOption::Remove,
+                    geode,
+                    Some(who.clone()),
+                    TransitionCause::ProviderAction,
+                )?;
+            }
+            Ok(().into())
+        }
+
+        /// Called by provider to transfer a list of its geodes to another provider account.
+        /// Geodes serving an order or with a slash pending can't be transferred, and `to` has
+        /// to be a registered provider.
+        #[pallet::weight(<T as Config>::WeightInfo::provider_transfer_geodes(geodes.len() as u32))]
+        #[transactional]
+        pub fn provider_transfer_geodes(
+            origin: OriginFor<T>,
+            geodes: Vec<T::AccountId>,
+            to: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(who != to, Error::<T>::InvalidInput);
+            ensure!(
+                <Providers<T>>::contains_key(&to),
+                Error::<T>::InvalidProvider
+            );
+            Self::ensure_provider_geodes(&who, &geodes)?;
+            for geode in geodes.iter() {
+                ensure!(
+                    <Geodes<T>>::get(&geode).order.is_none(),
+                    Error::<T>::GeodeInOrder
+                );
+                ensure!(
+                    !T::Slashes::slash_pending(geode),
+                    Error::<T>::GeodeSlashPending
+                );
+            }
 
-            Self::deposit_event(Event::GeodeStateUpdate(geode, GeodeState::Registered));
+            for geode in geodes {
+                // the deposit goes along with the geode
+                let deposit = <ReservedDeposits<T>>::get(&geode);
+                let remaining = <T as Config>::Currency::repatriate_reserved(
+                    &who,
+                    &to,
+                    deposit,
+                    BalanceStatus::Reserved,
+                )?;
+                ensure!(remaining.is_zero(), Error::<T>::InsufficientDeposit);
+                <Geodes<T>>::mutate(&geode, |geode_record| geode_record.provider = to.clone());
+                Self::remove_provider_geode(&who, &geode);
+                Self::add_provider_geode(&to, &geode);
+                <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
+                Self::deposit_event(Event::GeodeTransfer(geode, who.clone(), to.clone()));
+            }
+            Ok(().into())
+        }
+
+        /// Called by root to set the deposit of registering a geode
+        #[pallet::weight(<T as Config>::WeightInfo::set_geode_deposit())]
+        pub fn set_geode_deposit(
+            origin: OriginFor<T>,
+            deposit: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let _who = ensure_root(origin)?;
+            <GeodeDeposit<T>>::put(deposit);
             Ok(().into())
         }
     }
@@ -337,6 +921,25 @@ pub mod pallet {
             res
         }
 
+        /// Return geodes in the given state whose properties match the selector
+        pub fn select_geodes(state: GeodeState, selector: Selector) -> Vec<GeodeOf<T>> {
+            let ids: Vec<T::AccountId> = match state {
+                GeodeState::Registered => <RegisteredGeodes<T>>::iter_keys().collect(),
+                GeodeState::Attested => <AttestedGeodes<T>>::iter_keys().collect(),
+                GeodeState::Instantiated => <InstantiatedGeodes<T>>::iter_keys().collect(),
+                GeodeState::DegradedInstantiated => {
+                    <DegradedInstantiatedGeodes<T>>::iter_keys().collect()
+                }
+                GeodeState::Offline => <OfflineGeodes<T>>::iter_keys().collect(),
+                GeodeState::Unknown => <UnknownGeodes<T>>::iter_keys().collect(),
+                GeodeState::Null => Vec::new(),
+            };
+            ids.into_iter()
+                .map(|id| <Geodes<T>>::get(id))
+                .filter(|geode| selector.matches(&geode.props))
+                .collect()
+        }
+
         /// Return list geode an attestor is attesting
         pub fn attestor_attested_geodes(attestor: T::AccountId) -> Vec<GeodeOf<T>> {
             let mut res = Vec::new();
@@ -348,6 +951,14 @@ pub mod pallet {
             res
         }
 
+        /// Return geodes owned by a provider
+        pub fn geodes_of_provider(provider: T::AccountId) -> Vec<GeodeOf<T>> {
+            <ProviderGeodes<T>>::get(&provider)
+                .iter()
+                .map(|geode| <Geodes<T>>::get(geode))
+                .collect()
+        }
+
         pub fn geode_state(geode: T::AccountId) -> Option<GeodeState> {
             if <Geodes<T>>::contains_key(&geode) {
                 Some(<Geodes<T>>::get(&geode).state)
@@ -356,163 +967,390 @@ pub mod pallet {
             }
         }
 
-        pub fn degrade_geode(geode: &T::AccountId) {
+        /// Move the geode into the state `to`, the only place a geode changes its state.
+        /// Keeps the geode record, the per-state maps, the update counter and the history
+        /// consistent, and rejects transitions not in `GEODE_STATE_TRANSITIONS`.
+        /// Transiting to `Null` removes the geode.
+        pub fn transit(
+            geode: &T::AccountId,
+            to: GeodeState,
+            cause: TransitionCause,
+        ) -> Result<(), Error<T>> {
+            ensure!(<Geodes<T>>::contains_key(&geode), Error::<T>::InvalidGeode);
             let mut geode_record = <Geodes<T>>::get(&geode);
-            let to_state;
-            match geode_record.state {
-                GeodeState::Attested => {
-                    to_state = GeodeState::Registered;
-                    <AttestedGeodes<T>>::remove(&geode);
+            let from = geode_record.state.clone();
+            ensure!(from.can_transit_to(&to), Error::<T>::IllegalTransition);
+
+            Self::remove_from_state_map(&from, geode);
+            Self::record_transition(geode, from, to.clone(), cause);
+
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            if to == GeodeState::Null {
+                Self::remove_provider_geode(&geode_record.provider, geode);
+                <Geodes<T>>::remove(&geode);
+                <HistoryPruneQueue<T>>::append(block_number, geode);
+                T::OnGeodeRemove::on_geode_remove(geode);
+                Self::deposit_event(Event::GeodeRemove(geode.clone()));
+            } else {
+                Self::insert_into_state_map(&to, geode, block_number);
+                geode_record.state = to.clone();
+                <Geodes<T>>::insert(&geode, geode_record);
+                <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
+                Self::deposit_event(Event::GeodeStateUpdate(geode.clone(), to));
+            }
+            Ok(())
+        }
+
+        fn ensure_valid_dns(dns: &[u8]) -> Result<(), Error<T>> {
+            ensure!(
+                dns.len() <= T::MaxDnsLength::get() as usize && validate_dns(dns),
+                Error::<T>::InvalidDns
+            );
+            Ok(())
+        }
+
+        fn ensure_valid_props(props: &BTreeMap<Vec<u8>, Vec<u8>>) -> Result<(), Error<T>> {
+            ensure!(
+                props.len() <= T::MaxProps::get() as usize,
+                Error::<T>::TooManyProps
+            );
+            let max_len = T::MaxPropLength::get() as usize;
+            ensure!(
+                props
+                    .iter()
+                    .all(|(name, value)| name.len() <= max_len && value.len() <= max_len),
+                Error::<T>::PropTooLong
+            );
+            ensure!(validate_props(props), Error::<T>::InvalidProperty);
+            Ok(())
+        }
+
+        fn ensure_valid_provider_info(metadata: &[u8], url: &[u8]) -> Result<(), Error<T>> {
+            ensure!(
+                metadata.len() <= MAX_PROVIDER_METADATA_LENGTH
+                    && url.len() <= MAX_PROVIDER_URL_LENGTH,
+                Error::<T>::ProviderInfoTooLong
+            );
+            Ok(())
+        }
+
+        /// Number of bytes of a geode's ip, dns and properties charged by `DepositPerByte`.
+        fn record_bytes(geode_record: &GeodeOf<T>) -> u32 {
+            let props_len: usize = geode_record
+                .props
+                .iter()
+                .map(|(name, value)| name.len() + value.len())
+                .sum();
+            (geode_record.ip.len() + geode_record.dns.len() + props_len) as u32
+        }
+
+        fn byte_deposit(bytes: u32) -> BalanceOf<T> {
+            T::DepositPerByte::get().saturating_mul(bytes.into())
+        }
+
+        /// Reserve more or return part of the geode's deposit after its record grows or
+        /// shrinks from `old_bytes` to `new_bytes`.
+        fn adjust_deposit(
+            geode: &T::AccountId,
+            provider: &T::AccountId,
+            old_bytes: u32,
+            new_bytes: u32,
+        ) -> DispatchResult {
+            let old_deposit = Self::byte_deposit(old_bytes);
+            let new_deposit = Self::byte_deposit(new_bytes);
+            if new_deposit > old_deposit {
+                let extra = new_deposit - old_deposit;
+                <T as Config>::Currency::reserve(provider, extra)?;
+                <ReservedDeposits<T>>::mutate(geode, |deposit| {
+                    *deposit = deposit.saturating_add(extra)
+                });
+            } else {
+                // never return more than what is still reserved, e.g. after a slash
+                let excess = (old_deposit - new_deposit).min(<ReservedDeposits<T>>::get(geode));
+                <T as Config>::Currency::unreserve(provider, excess);
+                <ReservedDeposits<T>>::mutate(geode, |deposit| {
+                    *deposit = deposit.saturating_sub(excess)
+                });
+            }
+            Ok(())
+        }
+
+        /// Check that the list of geodes is not empty nor too long, has no duplicate and is
+        /// owned by the provider.
+        fn ensure_provider_geodes(
+            provider: &T::AccountId,
+            geodes: &[T::AccountId],
+        ) -> Result<(), Error<T>> {
+            ensure!(
+                !geodes.is_empty() && geodes.len() <= MAX_GEODES_PER_CALL,
+                Error::<T>::InvalidInput
+            );
+            let unique: BTreeSet<&T::AccountId> = geodes.iter().collect();
+            ensure!(unique.len() == geodes.len(), Error::<T>::InvalidInput);
+            for geode in geodes.iter() {
+                ensure!(<Geodes<T>>::contains_key(&geode), Error::<T>::InvalidGeode);
+                ensure!(
+                    <Geodes<T>>::get(&geode).provider == *provider,
+                    Error::<T>::NoRight
+                );
+            }
+            Ok(())
+        }
+
+        /// Add a geode to the provider's geodes, the provider record is created if it doesn't
+        /// exist yet.
+        fn add_provider_geode(provider: &T::AccountId, geode: &T::AccountId) {
+            if !<Providers<T>>::contains_key(&provider) {
+                <Providers<T>>::insert(
+                    &provider,
+                    ProviderOf::<T> {
+                        reputation: DEFAULT_PROVIDER_REPUTATION,
+                        ..Default::default()
+                    },
+                );
+            }
+            let mut geodes = <ProviderGeodes<T>>::get(&provider);
+            geodes.insert(geode.clone());
+            <Providers<T>>::mutate(&provider, |record| record.geode_num = geodes.len() as u32);
+            <ProviderGeodes<T>>::insert(&provider, geodes);
+        }
+
+        fn remove_provider_geode(provider: &T::AccountId, geode: &T::AccountId) {
+            if !<Providers<T>>::contains_key(&provider) {
+                return;
+            }
+            let mut geodes = <ProviderGeodes<T>>::get(&provider);
+            geodes.remove(geode);
+            <Providers<T>>::mutate(&provider, |record| record.geode_num = geodes.len() as u32);
+            if geodes.is_empty() {
+                <ProviderGeodes<T>>::remove(&provider);
+            } else {
+                <ProviderGeodes<T>>::insert(&provider, geodes);
+            }
+        }
+
+        fn insert_into_state_map(state: &GeodeState, geode: &T::AccountId, block: BlockNumber) {
+            match state {
+                GeodeState::Registered => <RegisteredGeodes<T>>::insert(geode, block),
+                GeodeState::Attested => <AttestedGeodes<T>>::insert(geode, block),
+                GeodeState::Instantiated => <InstantiatedGeodes<T>>::insert(geode, block),
+                GeodeState::DegradedInstantiated => {
+                    <DegradedInstantiatedGeodes<T>>::insert(geode, block)
                 }
-                GeodeState::Instantiated => {
-                    to_state = GeodeState::DegradedInstantiated;
-                    <InstantiatedGeodes<T>>::remove(&geode);
+                GeodeState::Offline => <OfflineGeodes<T>>::insert(geode, block),
+                GeodeState::Unknown => <UnknownGeodes<T>>::insert(geode, block),
+                GeodeState::Null => {}
+            }
+            if state.is_timed() {
+                <StateQueues<T>>::append(state, block, geode);
+            }
+        }
+
+        fn remove_from_state_map(state: &GeodeState, geode: &T::AccountId) {
+            match state {
+                GeodeState::Registered => <RegisteredGeodes<T>>::remove(geode),
+                GeodeState::Attested => <AttestedGeodes<T>>::remove(geode),
+                GeodeState::Instantiated => <InstantiatedGeodes<T>>::remove(geode),
+                GeodeState::DegradedInstantiated => <DegradedInstantiatedGeodes<T>>::remove(geode),
+                GeodeState::Offline => <OfflineGeodes<T>>::remove(geode),
+                GeodeState::Unknown => <UnknownGeodes<T>>::remove(geode),
+                GeodeState::Null => {}
+            }
+        }
+
+        /// Block number the geode entered `state`, if the geode is currently in it.
+        pub fn state_since(geode: &T::AccountId, state: &GeodeState) -> Option<BlockNumber> {
+            if !<Geodes<T>>::contains_key(geode) || <Geodes<T>>::get(geode).state != *state {
+                return None;
+            }
+            match state {
+                GeodeState::Registered => Some(<RegisteredGeodes<T>>::get(geode)),
+                GeodeState::Attested => Some(<AttestedGeodes<T>>::get(geode)),
+                GeodeState::Instantiated => Some(<InstantiatedGeodes<T>>::get(geode)),
+                GeodeState::DegradedInstantiated => {
+                    Some(<DegradedInstantiatedGeodes<T>>::get(geode))
                 }
+                GeodeState::Offline => Some(<OfflineGeodes<T>>::get(geode)),
+                GeodeState::Unknown => Some(<UnknownGeodes<T>>::get(geode)),
+                GeodeState::Null => None,
+            }
+        }
+
+        /// Degrade a geode lacking of attestors, Attested geodes go back to Registered and
+        /// Instantiated geodes turn DegradedInstantiated. Geodes in other states are untouched.
+        pub fn degrade_geode(geode: &T::AccountId, cause: TransitionCause) {
+            let to_state = match <Geodes<T>>::get(&geode).state {
+                GeodeState::Attested => GeodeState::Registered,
+                GeodeState::Instantiated => GeodeState::DegradedInstantiated,
                 _ => {
                     return;
                 }
+            };
+            Self::transit(geode, to_state, cause).ok();
+        }
+
+        /// Put an attested geode into service for an order, moving it to Instantiated.
+        pub fn instantiate_geode(geode: &T::AccountId, order: T::Hash) -> Result<(), Error<T>> {
+            ensure!(<Geodes<T>>::contains_key(&geode), Error::<T>::InvalidGeode);
+            ensure!(
+                <Geodes<T>>::get(&geode).order.is_none(),
+                Error::<T>::GeodeInOrder
+            );
+            Self::transit(geode, GeodeState::Instantiated, TransitionCause::Order)?;
+            <Geodes<T>>::mutate(&geode, |geode_record| geode_record.order = Some(order));
+            Ok(())
+        }
+
+        /// Release a geode from the order it is serving. Instantiated geodes go back to
+        /// Attested and DegradedInstantiated geodes go back to Registered, geodes in other
+        /// states only get the order cleaned.
+        pub fn release_geode(geode: &T::AccountId, order: &T::Hash) {
+            if !<Geodes<T>>::contains_key(&geode) {
+                return;
             }
-            geode_record.state = to_state.clone();
+            let mut geode_record = <Geodes<T>>::get(&geode);
+            if geode_record.order.as_ref() != Some(order) {
+                return;
+            }
+            geode_record.order = None;
+            let state = geode_record.state.clone();
             <Geodes<T>>::insert(&geode, geode_record);
-            <GeodeUpdateCounters<T>>::insert(&geode, <GeodeUpdateCounters<T>>::get(&geode) + 1);
-            let block_number =
-                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
-            match to_state {
-                GeodeState::Registered => {
-                    <RegisteredGeodes<T>>::insert(&geode, block_number);
+
+            match state {
+                GeodeState::Instantiated => {
+                    Self::transit(geode, GeodeState::Attested, TransitionCause::Order).ok();
                 }
                 GeodeState::DegradedInstantiated => {
-                    <DegradedInstantiatedGeodes<T>>::insert(&geode, block_number);
+                    Self::transit(geode, GeodeState::Registered, TransitionCause::Order).ok();
+                }
+                _ => {
+                    <GeodeUpdateCounters<T>>::insert(
+                        &geode,
+                        <GeodeUpdateCounters<T>>::get(&geode) + 1,
+                    );
                 }
-                _ => {}
             }
         }
 
-        pub fn reset_degraded_block_num() {
+        /// Restart the time the geode has been in its current state from now, and queue it
+        /// again if the state is timed.
+        pub fn renew_state(geode: &T::AccountId) {
             let block_number =
                 <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
-            // reset Registered
-            let mut registered_geodes = Vec::new();
-            <RegisteredGeodes<T>>::iter()
-                .map(|(id, _)| {
-                    registered_geodes.push(id);
-                })
-                .all(|_| true);
-            for id in registered_geodes {
-                <RegisteredGeodes<T>>::insert(id, block_number);
-            }
-            // reset DeprecatedInstantiated
-            let mut degraded_instantiated_geodes = Vec::new();
-            <DegradedInstantiatedGeodes<T>>::iter()
-                .map(|(id, _)| {
-                    degraded_instantiated_geodes.push(id);
-                })
-                .all(|_| true);
-            for id in degraded_instantiated_geodes {
-                <DegradedInstantiatedGeodes<T>>::insert(id, block_number);
-            }
+            let state = <Geodes<T>>::get(geode).state;
+            Self::insert_into_state_map(&state, geode, block_number);
         }
 
         pub fn detach_geode(
             option: DetachOption,
             geode: T::AccountId,
             who: Option<T::AccountId>,
+            cause: TransitionCause,
         ) -> Result<(), Error<T>> {
-            if <Geodes<T>>::contains_key(&geode) {
-                let mut geode_use = <Geodes<T>>::get(&geode);
+            ensure!(<Geodes<T>>::contains_key(&geode), Error::<T>::InvalidGeode);
 
-                if let Some(who) = who {
-                    ensure!(geode_use.provider == who, Error::<T>::NoRight)
-                }
+            if let Some(who) = who {
+                ensure!(
+                    <Geodes<T>>::get(&geode).provider == who,
+                    Error::<T>::NoRight
+                )
+            }
 
-                let prev_state = geode_use.state.clone();
-
-                match option {
-                    DetachOption::Remove => {
-                        ensure!(
-                            geode_use.state == GeodeState::Registered
-                                || geode_use.state == GeodeState::Attested
-                                || geode_use.state == GeodeState::Unknown,
-                            Error::<T>::InvalidGeodeState
-                        );
-                        <Geodes<T>>::remove(&geode);
-                        Self::deposit_event(Event::GeodeRemove(geode.clone()));
-                    }
-                    DetachOption::Offline => {
-                        ensure!(
-                            geode_use.state == GeodeState::Registered
-                                || geode_use.state == GeodeState::Attested,
-                            Error::<T>::InvalidGeodeState
-                        );
-                        geode_use.state = GeodeState::Offline;
-                        <Geodes<T>>::insert(&geode, &geode_use);
-                        let block_number = <frame_system::Pallet<T>>::block_number();
-                        <OfflineGeodes<T>>::insert(
-                            &geode,
-                            block_number.saturated_into::<BlockNumber>(),
-                        );
-                        <GeodeUpdateCounters<T>>::insert(
-                            &geode,
-                            <GeodeUpdateCounters<T>>::get(&geode) + 1,
-                        );
-                        Self::deposit_event(Event::GeodeStateUpdate(
-                            geode.clone(),
-                            GeodeState::Offline,
-                        ));
-                    }
-                    DetachOption::Unknown => {
-                        geode_use.state = GeodeState::Unknown;
-                        <Geodes<T>>::insert(&geode, &geode_use);
-                        let block_number = <frame_system::Pallet<T>>::block_number();
-                        <UnknownGeodes<T>>::insert(
-                            &geode,
-                            block_number.saturated_into::<BlockNumber>(),
-                        );
-                        <GeodeUpdateCounters<T>>::insert(
-                            &geode,
-                            <GeodeUpdateCounters<T>>::get(&geode) + 1,
-                        );
-                        Self::deposit_event(Event::GeodeStateUpdate(
-                            geode.clone(),
-                            GeodeState::Unknown,
-                        ));
-                    }
-                }
+            let provider = <Geodes<T>>::get(&geode).provider;
+            let remove = option == DetachOption::Remove;
+            // the deposit has to stay until the slash is over
+            ensure!(
+                !remove || !T::Slashes::slash_pending(&geode),
+                Error::<T>::GeodeSlashPending
+            );
+            let to_state = match option {
+                DetachOption::Remove => GeodeState::Null,
+                DetachOption::Offline => GeodeState::Offline,
+                DetachOption::Unknown => GeodeState::Unknown,
+            };
+            Self::transit(&geode, to_state, cause)?;
 
-                match prev_state {
-                    GeodeState::Registered => {
-                        <RegisteredGeodes<T>>::remove(&geode);
-                    }
-                    GeodeState::Attested => {
-                        <AttestedGeodes<T>>::remove(&geode);
-                    }
-                    GeodeState::Unknown => {
-                        <UnknownGeodes<T>>::remove(&geode);
-                    }
-                    GeodeState::Instantiated => {
-                        <InstantiatedGeodes<T>>::remove(&geode);
-                    }
-                    GeodeState::DegradedInstantiated => {
-                        <DegradedInstantiatedGeodes<T>>::remove(&geode);
-                    }
-                    GeodeState::Offline => {
-                        <OfflineGeodes<T>>::remove(&geode);
-                    }
-                    _ => {
-                        // shouldn't happen
-                    }
-                }
-                // clean record on attestors
-                pallet_attestor::Module::<T>::detach_geode_from_attestors(&geode);
-            } else {
-                return Err(Error::<T>::InvalidGeode);
+            if remove {
+                // the deposit is only forfeited by `slash_deposit`, whatever is left of it is
+                // returned once the geode is removed
+                let deposit = <ReservedDeposits<T>>::take(&geode);
+                <T as Config>::Currency::unreserve(&provider, deposit);
             }
 
+            // clean record on attestors
+            pallet_attestor::Module::<T>::detach_geode_from_attestors(&geode);
+
             Ok(())
         }
 
+        /// Slash the whole deposit reserved for a misbehaving geode, the slashed imbalance is
+        /// returned to the caller to handle.
+        pub fn slash_deposit(geode: &T::AccountId) -> NegativeImbalanceOf<T> {
+            let deposit = <ReservedDeposits<T>>::take(&geode);
+            let provider = <Geodes<T>>::get(&geode).provider;
+            let (imbalance, remaining) =
+                <T as Config>::Currency::slash_reserved(&provider, deposit);
+            Self::deposit_event(Event::DepositSlash(geode.clone(), deposit - remaining));
+            imbalance
+        }
+
+        /// Slash up to `amount` of the provider's stake, the slashed imbalance is returned to
+        /// the caller to handle.
+        pub fn slash_stake(
+            provider: &T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> NegativeImbalanceOf<T> {
+            let amount = amount.min(<Providers<T>>::get(provider).stake);
+            let (imbalance, remaining) = <T as Config>::Currency::slash_reserved(provider, amount);
+            let slashed = amount - remaining;
+            <Providers<T>>::mutate_exists(provider, |provider| {
+                if let Some(provider) = provider {
+                    provider.stake -= slashed;
+                }
+            });
+            Self::deposit_event(Event::StakeSlash(provider.clone(), slashed));
+            imbalance
+        }
+
+        /// Append a state transition to the geode's history, dropping the oldest one once
+        /// the history is full.
+        pub fn record_transition(
+            geode: &T::AccountId,
+            from: GeodeState,
+            to: GeodeState,
+            cause: TransitionCause,
+        ) {
+            let block = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            <GeodeHistory<T>>::mutate(geode, |history| {
+                if history.len() >= MAX_GEODE_HISTORY_LEN {
+                    history.remove(0);
+                }
+                history.push(StateTransition {
+                    from,
+                    to,
+                    block,
+                    cause,
+                });
+            });
+        }
+
         /// clean all the storage, USE WITH CARE!
         pub fn clean_storage() {
+            // return ReservedDeposits
+            {
+                let mut deposits = Vec::new();
+                <ReservedDeposits<T>>::iter()
+                    .map(|(key, deposit)| {
+                        deposits.push((key, deposit));
+                    })
+                    .all(|_| true);
+                for (geode, deposit) in deposits.iter() {
+                    <T as Config>::Currency::unreserve(&<Geodes<T>>::get(geode).provider, *deposit);
+                    <ReservedDeposits<T>>::remove(geode);
+                }
+            }
+
             // clean Geodes
             {
                 let mut geodes = Vec::new();
@@ -604,6 +1442,19 @@ pub mod pallet {
                 }
             }
 
+            // clean StateQueues
+            {
+                let mut state_queues = Vec::new();
+                <StateQueues<T>>::iter()
+                    .map(|(state, block, _)| {
+                        state_queues.push((state, block));
+                    })
+                    .all(|_| true);
+                for (state, block) in state_queues.iter() {
+                    <StateQueues<T>>::remove(state, block);
+                }
+            }
+
             // clean GeodeUpdateCounters
             {
                 let mut geode_update_counters = Vec::new();
@@ -616,6 +1467,60 @@ pub mod pallet {
                     <GeodeUpdateCounters<T>>::remove(geode_update_counter);
                 }
             }
+
+            // clean ProviderGeodes, providers are kept as they hold stake
+            {
+                let mut providers = Vec::new();
+                <ProviderGeodes<T>>::iter()
+                    .map(|(key, _)| {
+                        providers.push(key);
+                    })
+                    .all(|_| true);
+                for provider in providers.iter() {
+                    <ProviderGeodes<T>>::remove(provider);
+                    <Providers<T>>::mutate(provider, |record| record.geode_num = 0);
+                }
+            }
+
+            // clean GeodeHistory
+            {
+                let mut geode_histories = Vec::new();
+                <GeodeHistory<T>>::iter()
+                    .map(|(key, _)| {
+                        geode_histories.push(key);
+                    })
+                    .all(|_| true);
+                for geode_history in geode_histories.iter() {
+                    <GeodeHistory<T>>::remove(geode_history);
+                }
+            }
+
+            // clean HistoryPruneQueue
+            {
+                let mut prune_queue = Vec::new();
+                <HistoryPruneQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        prune_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in prune_queue.iter() {
+                    <HistoryPruneQueue<T>>::remove(block);
+                }
+            }
+        }
+    }
+
+    impl<T: Config> GeodeRegistry<T::AccountId> for Pallet<T> {
+        fn provider(geode: &T::AccountId) -> Option<T::AccountId> {
+            if <Geodes<T>>::contains_key(geode) {
+                Some(<Geodes<T>>::get(geode).provider)
+            } else {
+                None
+            }
+        }
+
+        fn state(geode: &T::AccountId) -> Option<GeodeState> {
+            Self::geode_state(geode.clone())
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate as pallet_geode;
 use frame_support::parameter_types;
 use frame_system as system;
-use sp_core::H256;
+use sp_core::{sr25519, Pair, H256};
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
@@ -84,10 +84,30 @@ impl pallet_attestor::Config for Test {
     type Event = Event;
     type Currency = Balances;
     type Call = Call;
+    type OnHeartbeat = ();
+    type Assignments = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxDnsLength: u32 = 253;
+    pub const MaxProps: u32 = 8;
+    pub const MaxPropLength: u32 = 32;
+    pub static DepositPerByte: u128 = 0;
 }
 
 impl pallet_geode::Config for Test {
     type Event = Event;
+    type Currency = Balances;
+    type MaxDnsLength = MaxDnsLength;
+    type MaxProps = MaxProps;
+    type MaxPropLength = MaxPropLength;
+    type DepositPerByte = DepositPerByte;
+    type OnGeodeRegister = ();
+    type OnGeodePropsUpdate = ();
+    type OnGeodeRemove = ();
+    type Slashes = ();
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -119,9 +139,12 @@ pub fn events() -> Vec<Event> {
 
 pub fn register_attestor(attestor_account: <Test as system::Config>::AccountId) {
     let url = vec![1];
-    let pubkey = vec![2];
+    let pair = sr25519::Pair::from_seed(&[200; 32]);
+    let pubkey = pair.public().0.to_vec();
     let min_stake = 100;
     let attestor_account = 1;
+    let block_number = System::block_number() as u32;
+    let payload = AttestorModule::key_proof_payload(&attestor_account, &pubkey, block_number);
 
     // set the min stake balance
     AttestorModule::set_att_stake_min(Origin::root(), min_stake);
@@ -131,6 +154,8 @@ pub fn register_attestor(attestor_account: <Test as system::Config>::AccountId)
         Origin::signed(attestor_account),
         url.clone(),
         pubkey.clone(),
+        block_number,
+        pair.sign(&payload).0,
     );
 }
 
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: Apache-2.0
+
+/// Max length of a hostname as defined by RFC 1035.
+pub const MAX_HOSTNAME_LEN: usize = 253;
+/// Max length of a single hostname label.
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// An ip is either unset, or the raw bytes of an IPv4 (4 bytes) or IPv6 (16 bytes) address.
+pub fn validate_ip(ip: &[u8]) -> bool {
+    matches!(ip.len(), 0 | 4 | 16)
+}
+
+/// A dns is either unset, or a hostname made of dot separated labels. Each label is 1 to 63
+/// letters, digits or hyphens and doesn't start or end with a hyphen.
+pub fn validate_dns(dns: &[u8]) -> bool {
+    if dns.is_empty() {
+        return true;
+    }
+    if dns.len() > MAX_HOSTNAME_LEN {
+        return false;
+    }
+    dns.split(|c| *c == b'.').all(|label| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LEN
+            && label.first() != Some(&b'-')
+            && label.last() != Some(&b'-')
+            && label
+                .iter()
+                .all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_ip_should_work() {
+        assert!(validate_ip(&[]));
+        assert!(validate_ip(&[127, 0, 0, 1]));
+        assert!(validate_ip(&[0u8; 16]));
+        assert!(!validate_ip(b"127.0.0.1"));
+        assert!(!validate_ip(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn validate_dns_should_work() {
+        assert!(validate_dns(b""));
+        assert!(validate_dns(b"localhost"));
+        assert!(validate_dns(b"geode-1.ata.network"));
+        assert!(!validate_dns(b"geode..network"));
+        assert!(!validate_dns(b"-geode.network"));
+        assert!(!validate_dns(b"geode-.network"));
+        assert!(!validate_dns(b"geode.network."));
+        assert!(!validate_dns(b"geode_1.network"));
+        assert!(!validate_dns(&[b'a'; MAX_LABEL_LEN + 1]));
+    }
+}
@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use codec::{Decode, Encode};
+use core::cmp::Ordering;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+#[cfg(feature = "std")]
+use sp_std::str::FromStr;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+/// Well known geode properties. They are stored in `Geode::props` under `PropKey::name`
+/// and their values are checked against the schema when written.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
+pub enum PropKey {
+    /// Number of cpu cores.
+    Cores,
+    /// Memory size in MiB.
+    Ram,
+    /// Storage size in MiB.
+    Storage,
+    /// Region the geode is located, e.g. `eu`.
+    Region,
+    /// SGX enclave page cache size in MiB.
+    EpcSize,
+    /// SGX TCB level reported by attestation.
+    TcbLevel,
+}
+
+impl PropKey {
+    pub const ALL: [PropKey; 6] = [
+        PropKey::Cores,
+        PropKey::Ram,
+        PropKey::Storage,
+        PropKey::Region,
+        PropKey::EpcSize,
+        PropKey::TcbLevel,
+    ];
+
+    pub fn name(&self) -> &'static [u8] {
+        match self {
+            PropKey::Cores => b"cores",
+            PropKey::Ram => b"ram",
+            PropKey::Storage => b"storage",
+            PropKey::Region => b"region",
+            PropKey::EpcSize => b"epc_size",
+            PropKey::TcbLevel => b"tcb_level",
+        }
+    }
+
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        Self::ALL.iter().find(|key| key.name() == name).copied()
+    }
+
+    /// Parse the raw property value according to the schema.
+    pub fn parse(&self, raw: &[u8]) -> Option<PropValue> {
+        match self {
+            PropKey::Cores | PropKey::Ram | PropKey::Storage | PropKey::EpcSize => {
+                let s = core::str::from_utf8(raw).ok()?;
+                s.parse::<u64>().ok().map(PropValue::Number)
+            }
+            PropKey::Region => {
+                if raw.is_empty() {
+                    None
+                } else {
+                    Some(PropValue::Text(raw.to_vec()))
+                }
+            }
+            PropKey::TcbLevel => TcbLevel::from_name(raw).map(PropValue::Tcb),
+        }
+    }
+}
+
+/// SGX TCB level, ordered from the worst to the best.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Encode, Decode, RuntimeDebug)]
+pub enum TcbLevel {
+    OutOfDate,
+    ConfigurationNeeded,
+    SwHardeningNeeded,
+    UpToDate,
+}
+
+impl TcbLevel {
+    pub fn name(&self) -> &'static [u8] {
+        match self {
+            TcbLevel::OutOfDate => b"OutOfDate",
+            TcbLevel::ConfigurationNeeded => b"ConfigurationNeeded",
+            TcbLevel::SwHardeningNeeded => b"SWHardeningNeeded",
+            TcbLevel::UpToDate => b"UpToDate",
+        }
+    }
+
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        [
+            TcbLevel::OutOfDate,
+            TcbLevel::ConfigurationNeeded,
+            TcbLevel::SwHardeningNeeded,
+            TcbLevel::UpToDate,
+        ]
+        .iter()
+        .find(|level| level.name() == name)
+        .copied()
+    }
+}
+
+/// Typed property value.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub enum PropValue {
+    Number(u64),
+    Text(Vec<u8>),
+    Tcb(TcbLevel),
+}
+
+impl PropValue {
+    /// Only values of the same kind are comparable, texts are only comparable for equality.
+    fn compare(&self, other: &PropValue) -> Option<Ordering> {
+        match (self, other) {
+            (PropValue::Number(a), PropValue::Number(b)) => Some(a.cmp(b)),
+            (PropValue::Tcb(a), PropValue::Tcb(b)) => Some(a.cmp(b)),
+            (PropValue::Text(a), PropValue::Text(b)) if a == b => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// A single condition on a property, e.g. `cores >= 4`.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct Requirement {
+    pub key: PropKey,
+    pub op: Operator,
+    pub value: PropValue,
+}
+
+impl Requirement {
+    pub fn matches(&self, props: &BTreeMap<Vec<u8>, Vec<u8>>) -> bool {
+        let actual = match props
+            .get(self.key.name())
+            .and_then(|raw| self.key.parse(raw))
+        {
+            Some(actual) => actual,
+            None => return false,
+        };
+        match (self.op, actual.compare(&self.value)) {
+            (Operator::Ne, ordering) => ordering != Some(Ordering::Equal),
+            (_, None) => false,
+            (Operator::Eq, Some(ordering)) => ordering == Ordering::Equal,
+            (Operator::Gt, Some(ordering)) => ordering == Ordering::Greater,
+            (Operator::Ge, Some(ordering)) => ordering != Ordering::Less,
+            (Operator::Lt, Some(ordering)) => ordering == Ordering::Less,
+            (Operator::Le, Some(ordering)) => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// A conjunction of requirements a geode's properties have to satisfy.
+/// An empty selector matches every geode.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+pub struct Selector {
+    pub requirements: Vec<Requirement>,
+}
+
+impl Selector {
+    pub fn matches(&self, props: &BTreeMap<Vec<u8>, Vec<u8>>) -> bool {
+        self.requirements.iter().all(|req| req.matches(props))
+    }
+
+    /// Whether the selector has at most `max_requirements` requirements and no text value
+    /// longer than `max_value_length`.
+    pub fn is_bounded(&self, max_requirements: usize, max_value_length: usize) -> bool {
+        self.requirements.len() <= max_requirements
+            && self.requirements.iter().all(|req| match &req.value {
+                PropValue::Text(text) => text.len() <= max_value_length,
+                _ => true,
+            })
+    }
+}
+
+/// Check that every well known property in `props` follows the schema.
+pub fn validate_props(props: &BTreeMap<Vec<u8>, Vec<u8>>) -> bool {
+    props.iter().all(|(name, value)| validate_prop(name, value))
+}
+
+/// Check that a property follows the schema if it is a well known one.
+pub fn validate_prop(name: &[u8], value: &[u8]) -> bool {
+    match PropKey::from_name(name) {
+        Some(key) => key.parse(value).is_some(),
+        None => true,
+    }
+}
+
+/// Parse a selector such as `cores>=4,region=eu,tcb_level>=SWHardeningNeeded`.
+#[cfg(feature = "std")]
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const OPERATORS: [(&str, Operator); 6] = [
+            (">=", Operator::Ge),
+            ("<=", Operator::Le),
+            ("!=", Operator::Ne),
+            (">", Operator::Gt),
+            ("<", Operator::Lt),
+            ("=", Operator::Eq),
+        ];
+
+        let mut requirements = Vec::new();
+        for term in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (pos, token, op) = OPERATORS
+                .iter()
+                .filter_map(|(token, op)| term.find(token).map(|pos| (pos, *token, *op)))
+                .min_by_key(|(pos, token, _)| (*pos, usize::MAX - token.len()))
+                .ok_or_else(|| format!("missing operator in `{}`", term))?;
+            let name = term[..pos].trim();
+            let raw = term[pos + token.len()..].trim();
+            let key = PropKey::from_name(name.as_bytes())
+                .ok_or_else(|| format!("unknown property `{}`", name))?;
+            let value = key
+                .parse(raw.as_bytes())
+                .ok_or_else(|| format!("invalid value `{}` for `{}`", raw, name))?;
+            requirements.push(Requirement { key, op, value });
+        }
+        Ok(Selector { requirements })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(list: &[(&str, &str)]) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        list.iter()
+            .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_selector_should_work() {
+        let selector =
+            Selector::from_str("cores>=4, region=eu,tcb_level>SWHardeningNeeded").unwrap();
+        assert_eq!(
+            selector.requirements,
+            vec![
+                Requirement {
+                    key: PropKey::Cores,
+                    op: Operator::Ge,
+                    value: PropValue::Number(4),
+                },
+                Requirement {
+                    key: PropKey::Region,
+                    op: Operator::Eq,
+                    value: PropValue::Text(b"eu".to_vec()),
+                },
+                Requirement {
+                    key: PropKey::TcbLevel,
+                    op: Operator::Gt,
+                    value: PropValue::Tcb(TcbLevel::SwHardeningNeeded),
+                },
+            ]
+        );
+
+        assert!(Selector::from_str("cores>=four").is_err());
+        assert!(Selector::from_str("colour=red").is_err());
+        assert!(Selector::from_str("cores").is_err());
+    }
+
+    #[test]
+    fn selector_matches_should_work() {
+        let geode = props(&[("cores", "8"), ("region", "eu"), ("tcb_level", "UpToDate")]);
+        let matches = |s: &str| Selector::from_str(s).unwrap().matches(&geode);
+
+        assert!(matches(""));
+        assert!(matches("cores>=4,region=eu"));
+        assert!(matches("cores=8,region!=us"));
+        assert!(matches("tcb_level>=SWHardeningNeeded"));
+        assert!(!matches("cores>8"));
+        assert!(!matches("cores>=4,region=us"));
+        // missing property never matches
+        assert!(!matches("ram>=1024"));
+        // text can't be ordered
+        assert!(!matches("region>eu"));
+    }
+
+    #[test]
+    fn validate_props_should_work() {
+        assert!(validate_props(&props(&[
+            ("cores", "8"),
+            ("anything", "goes")
+        ])));
+        assert!(!validate_props(&props(&[("cores", "eight")])));
+        assert!(!validate_props(&props(&[("tcb_level", "Great")])));
+        assert!(!validate_props(&props(&[("region", "")])));
+    }
+}
@@ -1,5 +1,10 @@
 use crate as pallet_geode;
-use crate::{mock::*, Error};
+use crate::{
+    mock::*, Error, GeodeRegistry, GeodeState, Selector, StateTransition, TransitionCause,
+    DEFAULT_PROVIDER_REPUTATION, GEODE_STATE_TRANSITIONS, HISTORY_RETENTION_BLOCK_NUMBER,
+    MAX_GEODE_HISTORY_LEN, MAX_PROVIDER_METADATA_LENGTH, MAX_PROVIDER_URL_LENGTH,
+};
+use frame_support::traits::{Hooks, Imbalance, ReservableCurrency};
 use frame_support::{assert_noop, assert_ok};
 
 #[test]
@@ -138,7 +143,7 @@ fn it_works_update_geode_dns() {
             geode
         ));
 
-        let dns = vec![1_u8];
+        let dns = b"geode.ata.network".to_vec();
         assert_ok!(GeodeModule::update_geode_dns(
             Origin::signed(provider),
             geode_id,
@@ -146,3 +151,780 @@ fn it_works_update_geode_dns() {
         ));
     });
 }
+
+#[test]
+fn it_update_geode_props_invalid_property() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+        let provider = 4;
+
+        provider_register_geode(provider, geode_id);
+
+        assert_noop!(
+            GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                geode_id,
+                b"cores".to_vec(),
+                b"many".to_vec()
+            ),
+            Error::<Test>::InvalidProperty
+        );
+    });
+}
+
+#[test]
+fn it_works_select_geodes() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+
+        for (geode_id, cores, region) in vec![(3, "8", "eu"), (5, "2", "eu"), (6, "8", "us")] {
+            provider_register_geode(provider, geode_id);
+            assert_ok!(GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                geode_id,
+                b"cores".to_vec(),
+                cores.as_bytes().to_vec()
+            ));
+            assert_ok!(GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                geode_id,
+                b"region".to_vec(),
+                region.as_bytes().to_vec()
+            ));
+        }
+
+        let selector = "cores>=4,region=eu".parse::<Selector>().unwrap();
+        let selected: Vec<_> = GeodeModule::select_geodes(GeodeState::Registered, selector.clone())
+            .into_iter()
+            .map(|geode| geode.id)
+            .collect();
+        assert_eq!(selected, vec![3]);
+
+        assert!(GeodeModule::select_geodes(GeodeState::Attested, selector).is_empty());
+    });
+}
+
+#[test]
+fn it_works_geode_history() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+        let removed_geode_id = 5;
+        let provider = 4;
+
+        provider_register_geode(provider, geode_id);
+        provider_register_geode(provider, removed_geode_id);
+        System::set_block_number(2);
+        assert_ok!(GeodeModule::turn_geode_offline(
+            Origin::signed(provider),
+            geode_id
+        ));
+        assert_ok!(GeodeModule::geode_remove(
+            Origin::signed(provider),
+            removed_geode_id
+        ));
+
+        let transition = |from, to, block| StateTransition {
+            from,
+            to,
+            block,
+            cause: TransitionCause::ProviderAction,
+        };
+        assert_eq!(
+            GeodeModule::geode_history(geode_id),
+            vec![
+                transition(GeodeState::Null, GeodeState::Registered, 1),
+                transition(GeodeState::Registered, GeodeState::Offline, 2),
+            ]
+        );
+        // history is kept after the geode is removed
+        assert_eq!(
+            GeodeModule::geode_history(removed_geode_id),
+            vec![
+                transition(GeodeState::Null, GeodeState::Registered, 1),
+                transition(GeodeState::Registered, GeodeState::Null, 2),
+            ]
+        );
+
+        // and across registrations
+        System::set_block_number(3);
+        provider_register_geode(provider, removed_geode_id);
+        assert_eq!(
+            GeodeModule::geode_history(removed_geode_id),
+            vec![
+                transition(GeodeState::Null, GeodeState::Registered, 1),
+                transition(GeodeState::Registered, GeodeState::Null, 2),
+                transition(GeodeState::Null, GeodeState::Registered, 3),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_works_geode_history_pruned() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+        let registered_again_id = 5;
+        let provider = 4;
+
+        provider_register_geode(provider, geode_id);
+        provider_register_geode(provider, registered_again_id);
+        System::set_block_number(2);
+        assert_ok!(GeodeModule::provider_remove_geodes(
+            Origin::signed(provider),
+            vec![geode_id, registered_again_id]
+        ));
+        System::set_block_number(3);
+        provider_register_geode(provider, registered_again_id);
+
+        // kept until the retention ends
+        let end = 2 + HISTORY_RETENTION_BLOCK_NUMBER as u64;
+        GeodeModule::on_initialize(end - 1);
+        assert_eq!(GeodeModule::geode_history(geode_id).len(), 2);
+
+        // pruned for removed geodes only
+        GeodeModule::on_initialize(end);
+        assert!(GeodeModule::geode_history(geode_id).is_empty());
+        assert_eq!(GeodeModule::geode_history(registered_again_id).len(), 3);
+        assert!(GeodeModule::history_prune_queue(2).is_empty());
+    });
+}
+
+#[test]
+fn it_works_geode_history_bounded() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+
+        for block in 0..(MAX_GEODE_HISTORY_LEN as u64 + 5) {
+            System::set_block_number(block);
+            GeodeModule::record_transition(
+                &geode_id,
+                GeodeState::Attested,
+                GeodeState::Registered,
+                TransitionCause::AttestorRemoval,
+            );
+        }
+
+        let history = GeodeModule::geode_history(geode_id);
+        assert_eq!(history.len(), MAX_GEODE_HISTORY_LEN);
+        assert_eq!(history[0].block, 5);
+    });
+}
+
+const ALL_STATES: [GeodeState; 7] = [
+    GeodeState::Null,
+    GeodeState::Registered,
+    GeodeState::Attested,
+    GeodeState::Instantiated,
+    GeodeState::DegradedInstantiated,
+    GeodeState::Offline,
+    GeodeState::Unknown,
+];
+
+/// Put a geode directly into `state`, bypassing the transition checks.
+fn force_geode_state(geode_id: u64, provider: u64, state: GeodeState) {
+    pallet_geode::Geodes::<Test>::insert(
+        geode_id,
+        pallet_geode::Geode {
+            id: geode_id,
+            provider,
+            state: state.clone(),
+            ..Default::default()
+        },
+    );
+    match state {
+        GeodeState::Registered => pallet_geode::RegisteredGeodes::<Test>::insert(geode_id, 1),
+        GeodeState::Attested => pallet_geode::AttestedGeodes::<Test>::insert(geode_id, 1),
+        GeodeState::Instantiated => pallet_geode::InstantiatedGeodes::<Test>::insert(geode_id, 1),
+        GeodeState::DegradedInstantiated => {
+            pallet_geode::DegradedInstantiatedGeodes::<Test>::insert(geode_id, 1)
+        }
+        GeodeState::Offline => pallet_geode::OfflineGeodes::<Test>::insert(geode_id, 1),
+        GeodeState::Unknown => pallet_geode::UnknownGeodes::<Test>::insert(geode_id, 1),
+        GeodeState::Null => {}
+    }
+}
+
+/// Return the states of all the per-state maps containing the geode.
+fn geode_state_maps(geode_id: u64) -> Vec<GeodeState> {
+    let mut states = Vec::new();
+    if pallet_geode::RegisteredGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::Registered);
+    }
+    if pallet_geode::AttestedGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::Attested);
+    }
+    if pallet_geode::InstantiatedGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::Instantiated);
+    }
+    if pallet_geode::DegradedInstantiatedGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::DegradedInstantiated);
+    }
+    if pallet_geode::OfflineGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::Offline);
+    }
+    if pallet_geode::UnknownGeodes::<Test>::contains_key(geode_id) {
+        states.push(GeodeState::Unknown);
+    }
+    states
+}
+
+#[test]
+fn it_works_transit_all_transitions() {
+    for from in ALL_STATES.iter() {
+        for to in ALL_STATES.iter() {
+            new_test_ext().execute_with(|| {
+                let geode_id = 3;
+                let provider = 4;
+                force_geode_state(geode_id, provider, from.clone());
+                System::set_block_number(2);
+
+                let allowed = GEODE_STATE_TRANSITIONS
+                    .iter()
+                    .any(|(f, t)| f == from && t == to);
+                assert_eq!(from.can_transit_to(to), allowed);
+
+                if !allowed {
+                    assert_noop!(
+                        GeodeModule::transit(&geode_id, to.clone(), TransitionCause::Order),
+                        Error::<Test>::IllegalTransition
+                    );
+                    return;
+                }
+
+                assert_ok!(GeodeModule::transit(
+                    &geode_id,
+                    to.clone(),
+                    TransitionCause::Order
+                ));
+                if *to == GeodeState::Null {
+                    assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_id));
+                    assert!(geode_state_maps(geode_id).is_empty());
+                } else {
+                    assert_eq!(GeodeModule::geodes(geode_id).state, *to);
+                    assert_eq!(geode_state_maps(geode_id), vec![to.clone()]);
+                    assert_eq!(GeodeModule::geode_update_counters(geode_id), 1);
+                }
+                assert_eq!(
+                    GeodeModule::geode_history(geode_id),
+                    vec![StateTransition {
+                        from: from.clone(),
+                        to: to.clone(),
+                        block: 2,
+                        cause: TransitionCause::Order,
+                    }]
+                );
+            });
+        }
+    }
+}
+
+#[test]
+fn it_transit_invalid_geode() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            GeodeModule::transit(&3, GeodeState::Registered, TransitionCause::ProviderAction),
+            Error::<Test>::InvalidGeode
+        );
+    });
+}
+
+#[test]
+fn it_works_geode_registry() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+        let provider = 4;
+        assert_eq!(
+            <GeodeModule as GeodeRegistry<u64>>::provider(&geode_id),
+            None
+        );
+        assert_eq!(<GeodeModule as GeodeRegistry<u64>>::state(&geode_id), None);
+
+        force_geode_state(geode_id, provider, GeodeState::Attested);
+        assert_eq!(
+            <GeodeModule as GeodeRegistry<u64>>::provider(&geode_id),
+            Some(provider)
+        );
+        assert_eq!(
+            <GeodeModule as GeodeRegistry<u64>>::state(&geode_id),
+            Some(GeodeState::Attested)
+        );
+    });
+}
+
+#[test]
+fn it_works_turn_geode_online() {
+    new_test_ext().execute_with(|| {
+        let geode_id = 3;
+        let provider = 4;
+        provider_register_geode(provider, geode_id);
+
+        // only offline geodes can be turned online
+        assert_noop!(
+            GeodeModule::turn_geode_online(Origin::signed(provider), geode_id),
+            Error::<Test>::InvalidGeodeState
+        );
+
+        assert_ok!(GeodeModule::turn_geode_offline(
+            Origin::signed(provider),
+            geode_id
+        ));
+        assert_noop!(
+            GeodeModule::turn_geode_online(Origin::signed(1), geode_id),
+            Error::<Test>::NoRight
+        );
+        assert_ok!(GeodeModule::turn_geode_online(
+            Origin::signed(provider),
+            geode_id
+        ));
+        assert_eq!(GeodeModule::geodes(geode_id).state, GeodeState::Registered);
+        assert_eq!(geode_state_maps(geode_id), vec![GeodeState::Registered]);
+    });
+}
+
+#[test]
+fn it_works_provider_register() {
+    new_test_ext().execute_with(|| {
+        let provider = 2;
+        let stake = 1000;
+
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(provider),
+            b"provider".to_vec(),
+            b"https://provider.example".to_vec(),
+            stake
+        ));
+        assert_noop!(
+            GeodeModule::provider_register(Origin::signed(provider), vec![], vec![], 0),
+            Error::<Test>::AlreadyProvider
+        );
+        assert_eq!(Balances::reserved_balance(provider), stake);
+
+        // metadata and url are bounded
+        assert_noop!(
+            GeodeModule::provider_register(
+                Origin::signed(3),
+                vec![1; MAX_PROVIDER_METADATA_LENGTH + 1],
+                vec![],
+                0
+            ),
+            Error::<Test>::ProviderInfoTooLong
+        );
+        assert_noop!(
+            GeodeModule::provider_update(
+                Origin::signed(provider),
+                vec![],
+                vec![1; MAX_PROVIDER_URL_LENGTH + 1]
+            ),
+            Error::<Test>::ProviderInfoTooLong
+        );
+
+        assert_ok!(GeodeModule::provider_update(
+            Origin::signed(provider),
+            b"new provider".to_vec(),
+            vec![]
+        ));
+        assert_ok!(GeodeModule::provider_bond(Origin::signed(provider), stake));
+        let record = GeodeModule::providers(provider);
+        assert_eq!(record.metadata, b"new provider".to_vec());
+        assert_eq!(record.stake, 2 * stake);
+        assert_eq!(record.reputation, DEFAULT_PROVIDER_REPUTATION);
+        assert_eq!(Balances::reserved_balance(provider), 2 * stake);
+
+        // can't deregister while owning geodes
+        provider_register_geode(provider, 3);
+        assert_eq!(GeodeModule::providers(provider).geode_num, 1);
+        assert_noop!(
+            GeodeModule::provider_deregister(Origin::signed(provider)),
+            Error::<Test>::ProviderHasGeodes
+        );
+
+        assert_ok!(GeodeModule::geode_remove(Origin::signed(provider), 3));
+        assert_eq!(GeodeModule::providers(provider).geode_num, 0);
+        assert_ok!(GeodeModule::provider_deregister(Origin::signed(provider)));
+        assert!(!pallet_geode::Providers::<Test>::contains_key(provider));
+        assert_eq!(Balances::reserved_balance(provider), 0);
+    });
+}
+
+#[test]
+fn it_works_geodes_of_provider() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+
+        // the provider record is created with the first geode
+        provider_register_geode(provider, 3);
+        provider_register_geode(provider, 5);
+        provider_register_geode(6, 7);
+
+        let record = GeodeModule::providers(provider);
+        assert_eq!(record.geode_num, 2);
+        assert_eq!(record.reputation, DEFAULT_PROVIDER_REPUTATION);
+        let geodes: Vec<u64> = GeodeModule::geodes_of_provider(provider)
+            .into_iter()
+            .map(|geode| geode.id)
+            .collect();
+        assert_eq!(geodes, vec![3, 5]);
+    });
+}
+
+#[test]
+fn it_works_provider_offline_geodes() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+        provider_register_geode(provider, 3);
+        provider_register_geode(provider, 5);
+        provider_register_geode(6, 7);
+
+        assert_noop!(
+            GeodeModule::provider_offline_geodes(Origin::signed(provider), vec![]),
+            Error::<Test>::InvalidInput
+        );
+        assert_noop!(
+            GeodeModule::provider_offline_geodes(Origin::signed(provider), vec![3, 3]),
+            Error::<Test>::InvalidInput
+        );
+        // nothing changes if one of the geodes is not owned
+        assert_noop!(
+            GeodeModule::provider_offline_geodes(Origin::signed(provider), vec![3, 7]),
+            Error::<Test>::NoRight
+        );
+
+        assert_ok!(GeodeModule::provider_offline_geodes(
+            Origin::signed(provider),
+            vec![3, 5]
+        ));
+        assert_eq!(GeodeModule::geodes(3).state, GeodeState::Offline);
+        assert_eq!(GeodeModule::geodes(5).state, GeodeState::Offline);
+
+        // offline geodes can't be turned offline again
+        assert_noop!(
+            GeodeModule::provider_offline_geodes(Origin::signed(provider), vec![3]),
+            Error::<Test>::IllegalTransition
+        );
+    });
+}
+
+#[test]
+fn it_works_provider_remove_geodes() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+        provider_register_geode(provider, 3);
+        provider_register_geode(provider, 5);
+        force_geode_state(6, provider, GeodeState::Instantiated);
+        assert_ok!(GeodeModule::turn_geode_offline(Origin::signed(provider), 5));
+
+        // geode serving an order can't be removed, so none of them is removed
+        assert_noop!(
+            GeodeModule::provider_remove_geodes(Origin::signed(provider), vec![3, 5, 6]),
+            Error::<Test>::IllegalTransition
+        );
+
+        // offline geode is removed without getting online first
+        assert_ok!(GeodeModule::provider_remove_geodes(
+            Origin::signed(provider),
+            vec![3, 5]
+        ));
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(3));
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(5));
+        assert_eq!(GeodeModule::providers(provider).geode_num, 0);
+        assert!(GeodeModule::geodes_of_provider(provider).is_empty());
+    });
+}
+
+#[test]
+fn it_works_provider_transfer_geodes() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+        let new_provider = 6;
+        provider_register_geode(provider, 3);
+        provider_register_geode(provider, 5);
+
+        assert_noop!(
+            GeodeModule::provider_transfer_geodes(Origin::signed(provider), vec![3], provider),
+            Error::<Test>::InvalidInput
+        );
+        // geodes are only transferred to registered providers
+        assert_noop!(
+            GeodeModule::provider_transfer_geodes(Origin::signed(provider), vec![3], new_provider),
+            Error::<Test>::InvalidProvider
+        );
+        assert!(!pallet_geode::Providers::<Test>::contains_key(new_provider));
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(new_provider),
+            vec![],
+            vec![],
+            0
+        ));
+        assert_noop!(
+            GeodeModule::provider_transfer_geodes(Origin::signed(new_provider), vec![3], provider),
+            Error::<Test>::NoRight
+        );
+
+        assert_ok!(GeodeModule::provider_transfer_geodes(
+            Origin::signed(provider),
+            vec![3],
+            new_provider
+        ));
+        assert_eq!(GeodeModule::geodes(3).provider, new_provider);
+        assert_eq!(GeodeModule::providers(provider).geode_num, 1);
+        assert_eq!(GeodeModule::providers(new_provider).geode_num, 1);
+        assert_eq!(GeodeModule::geodes_of_provider(new_provider)[0].id, 3);
+
+        // the new provider manages the geode now
+        assert_noop!(
+            GeodeModule::turn_geode_offline(Origin::signed(provider), 3),
+            Error::<Test>::NoRight
+        );
+        assert_ok!(GeodeModule::turn_geode_offline(
+            Origin::signed(new_provider),
+            3
+        ));
+    });
+}
+
+#[test]
+fn it_works_geode_deposit() {
+    new_test_ext().execute_with(|| {
+        let provider = 2;
+        let deposit = 1000;
+
+        assert_noop!(
+            GeodeModule::set_geode_deposit(Origin::signed(provider), deposit),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+
+        provider_register_geode(provider, 3);
+        provider_register_geode(provider, 5);
+        assert_eq!(GeodeModule::reserved_deposits(3), deposit);
+        assert_eq!(Balances::reserved_balance(provider), 2 * deposit);
+
+        // providers without enough balance can't register geodes
+        let mut geode = GeodeModule::geodes(3);
+        geode.id = 7;
+        assert!(GeodeModule::provider_register_geode(Origin::signed(4), geode).is_err());
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(7));
+
+        // deposit returned on clean removal
+        assert_ok!(GeodeModule::geode_remove(Origin::signed(provider), 3));
+        assert_eq!(Balances::reserved_balance(provider), deposit);
+
+        // deposit returned as well when liveness removes the geode
+        assert_ok!(GeodeModule::detach_geode(
+            pallet_geode::DetachOption::Remove,
+            5,
+            None,
+            TransitionCause::LivenessExpiry
+        ));
+        assert_eq!(Balances::reserved_balance(provider), 0);
+        assert_eq!(Balances::free_balance(provider), INIT_BALANCE);
+
+        // only a slash forfeits the deposit
+        provider_register_geode(provider, 3);
+        drop(GeodeModule::slash_deposit(&3));
+        assert_eq!(GeodeModule::reserved_deposits(3), 0);
+        assert_ok!(GeodeModule::detach_geode(
+            pallet_geode::DetachOption::Remove,
+            3,
+            None,
+            TransitionCause::Misconduct
+        ));
+        assert_eq!(Balances::reserved_balance(provider), 0);
+        assert_eq!(Balances::free_balance(provider), INIT_BALANCE - deposit);
+    });
+}
+
+#[test]
+fn it_works_slash_stake() {
+    new_test_ext().execute_with(|| {
+        let provider = 2;
+        let stake = 1000;
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(provider),
+            vec![],
+            vec![],
+            stake
+        ));
+
+        drop(GeodeModule::slash_stake(&provider, 400));
+        assert_eq!(GeodeModule::providers(provider).stake, stake - 400);
+        assert_eq!(Balances::reserved_balance(provider), stake - 400);
+
+        // never more than the stake
+        let imbalance = GeodeModule::slash_stake(&provider, stake);
+        assert_eq!(imbalance.peek(), stake - 400);
+        assert_eq!(GeodeModule::providers(provider).stake, 0);
+        assert_eq!(Balances::free_balance(provider), INIT_BALANCE - stake);
+
+        // unknown providers have nothing to slash
+        assert_eq!(GeodeModule::slash_stake(&3, stake).peek(), 0);
+        assert!(!pallet_geode::Providers::<Test>::contains_key(3));
+    });
+}
+
+#[test]
+fn it_works_transfer_geode_deposit() {
+    new_test_ext().execute_with(|| {
+        let provider = 2;
+        let new_provider = 1;
+        let deposit = 1000;
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+        provider_register_geode(provider, 3);
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(new_provider),
+            vec![],
+            vec![],
+            0
+        ));
+
+        assert_ok!(GeodeModule::provider_transfer_geodes(
+            Origin::signed(provider),
+            vec![3],
+            new_provider
+        ));
+        assert_eq!(Balances::reserved_balance(provider), 0);
+        assert_eq!(Balances::reserved_balance(new_provider), deposit);
+
+        assert_ok!(GeodeModule::geode_remove(Origin::signed(new_provider), 3));
+        assert_eq!(Balances::reserved_balance(new_provider), 0);
+        assert_eq!(Balances::free_balance(new_provider), INIT_BALANCE + deposit);
+
+        // a short deposit fails the whole transfer instead of moving part of it
+        provider_register_geode(provider, 5);
+        provider_register_geode(provider, 7);
+        drop(Balances::slash_reserved(&provider, deposit + 1));
+        assert_noop!(
+            GeodeModule::provider_transfer_geodes(
+                Origin::signed(provider),
+                vec![5, 7],
+                new_provider
+            ),
+            Error::<Test>::InsufficientDeposit
+        );
+        assert_eq!(GeodeModule::geodes(5).provider, provider);
+        assert_eq!(Balances::reserved_balance(provider), deposit - 1);
+    });
+}
+
+#[test]
+fn it_provider_register_geode_invalid_record() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+        let geode = |ip: Vec<u8>, dns: &[u8]| pallet_geode::Geode {
+            id: 3,
+            provider,
+            ip,
+            dns: dns.to_vec(),
+            ..Default::default()
+        };
+
+        assert_noop!(
+            GeodeModule::provider_register_geode(
+                Origin::signed(provider),
+                geode(b"127.0.0.1".to_vec(), b"")
+            ),
+            Error::<Test>::InvalidIp
+        );
+        assert_noop!(
+            GeodeModule::provider_register_geode(
+                Origin::signed(provider),
+                geode(vec![127, 0, 0, 1], b"geode..network")
+            ),
+            Error::<Test>::InvalidDns
+        );
+        assert_ok!(GeodeModule::provider_register_geode(
+            Origin::signed(provider),
+            geode(vec![127, 0, 0, 1], b"geode.ata.network")
+        ));
+
+        assert_noop!(
+            GeodeModule::update_geode_dns(Origin::signed(provider), 3, vec![b'a'; 254]),
+            Error::<Test>::InvalidDns
+        );
+    });
+}
+
+#[test]
+fn it_update_geode_props_limits() {
+    new_test_ext().execute_with(|| {
+        let provider = 4;
+        provider_register_geode(provider, 3);
+
+        assert_noop!(
+            GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                3,
+                b"name".to_vec(),
+                vec![b'a'; MaxPropLength::get() as usize + 1]
+            ),
+            Error::<Test>::PropTooLong
+        );
+
+        for i in 0..MaxProps::get() as u8 {
+            assert_ok!(GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                3,
+                vec![i],
+                vec![i]
+            ));
+        }
+        assert_noop!(
+            GeodeModule::update_geode_props(
+                Origin::signed(provider),
+                3,
+                b"name".to_vec(),
+                b"value".to_vec()
+            ),
+            Error::<Test>::TooManyProps
+        );
+        // updating an existing property is still fine
+        assert_ok!(GeodeModule::update_geode_props(
+            Origin::signed(provider),
+            3,
+            vec![0],
+            b"value".to_vec()
+        ));
+    });
+}
+
+#[test]
+fn it_works_geode_byte_deposit() {
+    new_test_ext().execute_with(|| {
+        let provider = 2;
+        let deposit = 1000;
+        DepositPerByte::set(&2);
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+
+        let geode = pallet_geode::Geode {
+            id: 3,
+            provider,
+            ip: vec![127, 0, 0, 1],
+            ..Default::default()
+        };
+        assert_ok!(GeodeModule::provider_register_geode(
+            Origin::signed(provider),
+            geode
+        ));
+        assert_eq!(GeodeModule::reserved_deposits(3), deposit + 4 * 2);
+
+        assert_ok!(GeodeModule::update_geode_dns(
+            Origin::signed(provider),
+            3,
+            b"geode.network".to_vec()
+        ));
+        assert_eq!(GeodeModule::reserved_deposits(3), deposit + 17 * 2);
+
+        // shorter dns returns part of the deposit
+        assert_ok!(GeodeModule::update_geode_dns(
+            Origin::signed(provider),
+            3,
+            b"geode".to_vec()
+        ));
+        assert_eq!(GeodeModule::reserved_deposits(3), deposit + 9 * 2);
+        assert_eq!(Balances::reserved_balance(provider), deposit + 9 * 2);
+
+        assert_ok!(GeodeModule::geode_remove(Origin::signed(provider), 3));
+        assert_eq!(Balances::reserved_balance(provider), 0);
+        assert_eq!(Balances::free_balance(provider), INIT_BALANCE);
+    });
+}
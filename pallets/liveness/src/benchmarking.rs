@@ -0,0 +1,512 @@
+//! Benchmarking setup for pallet-liveness
+
+use super::*;
+
+use crate::Pallet as Liveness;
+use codec::{Decode, Encode};
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use frame_support::storage::StoragePrefixedMap;
+use frame_support::traits::{Currency, Get, Hooks};
+use frame_system::RawOrigin;
+use pallet_enclave::MeasurementRegistry;
+use primitives::BlockNumber;
+use sp_core::{crypto::KeyTypeId, sr25519};
+use sp_runtime::{
+    traits::{Bounded, SaturatedConversion},
+    Percent,
+};
+use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+
+const SEED: u32 = 0;
+const MAX_ITEMS: u32 = 100;
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"live");
+
+fn register_attestor<T: Config>(index: u32) -> T::AccountId {
+    let attestor: T::AccountId = account("attestor", index, SEED);
+    register_attestor_as::<T>(&attestor);
+    attestor
+}
+
+fn register_attestor_as<T: Config>(attestor: &T::AccountId) {
+    <T as pallet_attestor::Config>::Currency::make_free_balance_be(
+        attestor,
+        pallet_attestor::BalanceOf::<T>::max_value() / 2u32.into(),
+    );
+    let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+    let block_number = frame_system::Pallet::<T>::block_number().saturated_into::<BlockNumber>();
+    let payload = pallet_attestor::Pallet::<T>::key_proof_payload(attestor, &key.0, block_number);
+    pallet_attestor::Pallet::<T>::attestor_register(
+        RawOrigin::Signed(attestor.clone()).into(),
+        vec![1u8; 64],
+        key.0.to_vec(),
+        block_number,
+        sp_io::crypto::sr25519_sign(KEY_TYPE, &key, &payload)
+            .unwrap()
+            .0,
+    )
+    .unwrap();
+}
+
+fn register_geode<T: Config>(index: u32) -> T::AccountId {
+    let id = account("geode", index, SEED);
+    register_geode_as::<T>(&id);
+    id
+}
+
+fn register_geode_as<T: Config>(id: &T::AccountId) {
+    let provider: T::AccountId = account("provider", 0, SEED);
+    <T as pallet_geode::Config>::Currency::make_free_balance_be(
+        &provider,
+        pallet_geode::BalanceOf::<T>::max_value() / 2u32.into(),
+    );
+    let geode = pallet_geode::GeodeOf::<T> {
+        id: id.clone(),
+        provider: provider.clone(),
+        ..Default::default()
+    };
+    pallet_geode::Pallet::<T>::provider_register_geode(RawOrigin::Signed(provider).into(), geode)
+        .unwrap();
+}
+
+/// An sr25519 key in the keystore and the account it decodes to.
+fn keyed_account<T: Config>() -> (sr25519::Public, T::AccountId) {
+    let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+    let account = T::AccountId::decode(&mut &key.0[..]).unwrap_or_default();
+    (key, account)
+}
+
+/// A report of the geode's enclave the verifier accepts, with the root certificate set and
+/// the enclave build approved.
+fn attestation_report<T: Config>(geode: &T::AccountId) -> AttestationReport {
+    let enclave = EnclaveIdentity {
+        mr_enclave: [1u8; 32],
+        report_data: geode.encode(),
+        ..Default::default()
+    };
+    let (root_cert, report) = T::ReportVerifier::benchmark_report(enclave.to_quote());
+    RaRootCert::<T>::put(root_cert);
+    T::MeasurementRegistry::approve_measurement(&enclave.measurement());
+    report
+}
+
+/// Assign the attestor to the geode and attest it.
+fn attest<T: Config>(attestor: &T::AccountId, geode: &T::AccountId) {
+    Liveness::<T>::assign_attestor(geode, attestor);
+    Liveness::<T>::attestor_attest_geode(
+        RawOrigin::Signed(attestor.clone()).into(),
+        geode.clone(),
+        attestation_report::<T>(geode),
+    )
+    .unwrap();
+}
+
+fn set_block_number<T: Config>(block_number: BlockNumber) {
+    frame_system::Pallet::<T>::set_block_number(block_number.saturated_into());
+}
+
+/// Move every expiry queue to `block`, so that the hook only visits the queue under test.
+fn start_queues<T: Config>(block: BlockNumber) {
+    for queue in [
+        ExpiryQueue::Reports,
+        ExpiryQueue::RegisteredGeodes,
+        ExpiryQueue::UnknownGeodes,
+        ExpiryQueue::DegradedInstantiatedGeodes,
+        ExpiryQueue::Attestors,
+        ExpiryQueue::Slashes,
+        ExpiryQueue::Assignments,
+        ExpiryQueue::Unbonding,
+    ]
+    .iter()
+    {
+        ExpiryCursors::<T>::insert(queue, block);
+    }
+}
+
+fn run_hook<T: Config>(now: BlockNumber) {
+    Liveness::<T>::on_initialize(now.saturated_into());
+}
+
+benchmarks! {
+    report_misconduct {
+        let (attestor_key, attestor) = keyed_account::<T>();
+        let (geode_key, geode) = keyed_account::<T>();
+        register_attestor_as::<T>(&attestor);
+        register_geode_as::<T>(&geode);
+        attest::<T>(&attestor, &geode);
+
+        // the worst case verifies both signatures, the enclave signed the wrong nonce
+        let msg = vec![1u8; 32];
+        let response = SignedResponse {
+            signature: sp_io::crypto::sr25519_sign(KEY_TYPE, &geode_key, &json_bytes(&msg))
+                .unwrap(),
+            msg,
+        };
+        let mut evidence = Evidence {
+            geode_key,
+            transcript: Transcript {
+                request: vec![0u8; 32],
+                response: Some(response),
+                sent_at: 0,
+                received_at: 0,
+            },
+            attestor_key,
+            attestor_signature: Default::default(),
+        };
+        evidence.attestor_signature = sp_io::crypto::sr25519_sign(
+            KEY_TYPE,
+            &attestor_key,
+            &evidence.attestor_payload(
+                &frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero()),
+                &attestor,
+                ReportType::Challenge as u8,
+            ),
+        )
+        .unwrap();
+    }: _(RawOrigin::Signed(attestor), geode.clone(), ReportType::Challenge as u8, evidence)
+    verify {
+        // the only attestor reporting satisfies the approval ratio
+        assert_eq!(
+            pallet_geode::Geodes::<T>::get(&geode).state,
+            pallet_geode::GeodeState::Unknown
+        );
+    }
+
+    attestor_attest_geode {
+        let attestor = register_attestor::<T>(0);
+        let geode = register_geode::<T>(0);
+        Liveness::<T>::assign_attestor(&geode, &attestor);
+        let report = attestation_report::<T>(&geode);
+    }: _(RawOrigin::Signed(attestor.clone()), geode.clone(), report)
+    verify {
+        assert!(pallet_attestor::GeodeAttestors::<T>::get(&geode).contains(&attestor));
+    }
+
+    attestor_exit {
+        let g in 0 .. pallet_attestor::MAX_ATTESTOR_GEODES;
+        let attestor = register_attestor::<T>(0);
+        for i in 0..g {
+            let geode = register_geode::<T>(i);
+            attest::<T>(&attestor, &geode);
+        }
+    }: _(RawOrigin::Signed(attestor.clone()))
+    verify {
+        assert!(Unbonding::<T>::contains_key(&attestor));
+    }
+
+    set_min_attestor_num {
+    }: _(RawOrigin::Root, 2)
+    verify {
+        assert_eq!(MinAttestorNum::<T>::get(), 2);
+    }
+
+    set_attestor_target_classes {
+        let c in 0 .. MAX_TARGET_CLASSES;
+        let classes = (0..c)
+            .map(|i| (pallet_geode::Selector::default(), i + 1))
+            .collect::<Vec<_>>();
+    }: _(RawOrigin::Root, classes.clone())
+    verify {
+        assert_eq!(AttestorTargetClasses::<T>::get(), classes);
+    }
+
+    set_geode_attestor_target {
+        // the worst case degrades an attested geode and queues it for assignment
+        let attestor = register_attestor::<T>(0);
+        let geode = register_geode::<T>(0);
+        attest::<T>(&attestor, &geode);
+    }: _(RawOrigin::Signed(account("provider", 0, SEED)), geode.clone(), 2)
+    verify {
+        assert_eq!(
+            pallet_geode::Geodes::<T>::get(&geode).state,
+            pallet_geode::GeodeState::Registered
+        );
+    }
+
+    set_liveness_params {
+        let params = LivenessParams {
+            report_expiry: 20,
+            ..Default::default()
+        };
+    }: _(RawOrigin::Root, params.clone())
+    verify {
+        assert_eq!(LivenessParameters::<T>::get(), params);
+    }
+
+    set_slash_params {
+        let params = SlashParams {
+            challenge_slash: Percent::from_percent(20),
+            ..Default::default()
+        };
+    }: _(RawOrigin::Root, params.clone())
+    verify {
+        assert_eq!(SlashParameters::<T>::get(), params);
+    }
+
+    set_ra_root_cert {
+        let l in 0 .. MAX_REPORT_LENGTH;
+        let cert = vec![1u8; l as usize];
+    }: _(RawOrigin::Root, cert.clone())
+    verify {
+        assert_eq!(RaRootCert::<T>::get(), cert);
+    }
+
+    set_ra_params {
+        let params = RaParams {
+            allow_conditional: true,
+            ..Default::default()
+        };
+    }: _(RawOrigin::Root, params.clone())
+    verify {
+        assert_eq!(RaParameters::<T>::get(), params);
+    }
+
+    overturn_report {
+        let a in 1 .. MAX_REPORTERS;
+        let geode: T::AccountId = account("geode", 0, SEED);
+        let mut attestors = BTreeSet::new();
+        for i in 0..a {
+            attestors.insert(register_attestor::<T>(i));
+        }
+        let key = (geode.clone(), ReportType::Challenge as u8);
+        Reports::<T>::insert(&key, ReportOf::<T> { start: 1, attestors });
+    }: _(RawOrigin::Root, geode, ReportType::Challenge as u8)
+    verify {
+        assert!(!Reports::<T>::contains_key(&key));
+    }
+
+    appeal_slash {
+        let (geode_key, geode) = keyed_account::<T>();
+        register_geode_as::<T>(&geode);
+        let msg = vec![1u8; 32];
+        let slash = PendingSlashOf::<T> {
+            report_type: ReportType::Challenge,
+            provider: account("provider", 0, SEED),
+            request: msg.clone(),
+            ..Default::default()
+        };
+        PendingSlashes::<T>::insert(&geode, slash);
+
+        let counter_evidence = CounterEvidence {
+            geode_key,
+            transcript: Transcript {
+                request: msg.clone(),
+                response: Some(SignedResponse {
+                    signature: sp_io::crypto::sr25519_sign(KEY_TYPE, &geode_key, &json_bytes(&msg))
+                        .unwrap(),
+                    msg,
+                }),
+                sent_at: 0,
+                received_at: 0,
+            },
+        };
+    }: _(RawOrigin::Signed(account("provider", 0, SEED)), geode.clone(), counter_evidence)
+    verify {
+        assert_eq!(PendingSlashes::<T>::get(&geode).status, SlashStatus::Appealed);
+    }
+
+    resolve_appeal {
+        let a in 1 .. MAX_REPORTERS;
+        let geode = register_geode::<T>(0);
+        pallet_geode::Pallet::<T>::detach_geode(
+            pallet_geode::DetachOption::Unknown,
+            geode.clone(),
+            None,
+            pallet_geode::TransitionCause::Misconduct,
+        )?;
+        let mut reporters = BTreeSet::new();
+        for i in 0..a {
+            reporters.insert(register_attestor::<T>(i));
+        }
+        let slash = PendingSlashOf::<T> {
+            report_type: ReportType::Challenge,
+            provider: account("provider", 0, SEED),
+            reporters,
+            status: SlashStatus::Appealed,
+            ..Default::default()
+        };
+        PendingSlashes::<T>::insert(&geode, slash);
+        // reversing also moves the geode back to Registered
+    }: _(RawOrigin::Root, geode.clone(), false)
+    verify {
+        assert_eq!(
+            pallet_geode::Geodes::<T>::get(&geode).state,
+            pallet_geode::GeodeState::Registered
+        );
+    }
+
+    assign_attestors {
+        let a in 1 .. MAX_ITEMS;
+        for i in 0..a {
+            register_attestor::<T>(i);
+        }
+        let geode = register_geode::<T>(0);
+        MinAttestorNum::<T>::put(a);
+    }: {
+        Liveness::<T>::assign_attestors(&geode, 1);
+    }
+    verify {
+        assert_eq!(GeodeAssignments::<T>::get(&geode).len() as u32, a);
+    }
+
+    on_initialize {
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        start_queues::<T>(2);
+        set_block_number::<T>(2);
+    }: {
+        run_hook::<T>(2);
+    }
+
+    expire_report {
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        let mut attestors = BTreeSet::new();
+        attestors.insert(account("reporter", 0, SEED));
+        let key = (account::<T::AccountId>("reported", 0, SEED), ReportType::Challenge as u8);
+        Reports::<T>::insert(&key, ReportOf::<T> { start: 1, attestors });
+        ReportQueue::<T>::append(1, &key);
+
+        let now: BlockNumber = LivenessParameters::<T>::get().report_expiry + 2;
+        start_queues::<T>(now);
+        ExpiryCursors::<T>::insert(ExpiryQueue::Reports, 1);
+        set_block_number::<T>(now);
+    }: {
+        run_hook::<T>(now);
+    }
+    verify {
+        assert!(!Reports::<T>::contains_key(&key));
+    }
+
+    apply_slash {
+        let a in 1 .. MAX_REPORTERS;
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        let geode = register_geode::<T>(0);
+        pallet_geode::Pallet::<T>::detach_geode(
+            pallet_geode::DetachOption::Unknown,
+            geode.clone(),
+            None,
+            pallet_geode::TransitionCause::Misconduct,
+        )?;
+        // attestors which didn't report are slashed as well
+        let mut attestors = BTreeSet::new();
+        for i in 0..a {
+            attestors.insert(register_attestor::<T>(i));
+        }
+        let slash = PendingSlashOf::<T> {
+            report_type: ReportType::Challenge,
+            provider: account("provider", 0, SEED),
+            attestors,
+            start: 1,
+            status: SlashStatus::Pending,
+            ..Default::default()
+        };
+        PendingSlashes::<T>::insert(&geode, slash);
+        SlashQueue::<T>::append(1, &geode);
+
+        let now: BlockNumber = LivenessParameters::<T>::get().appeal_window + 2;
+        start_queues::<T>(now);
+        ExpiryCursors::<T>::insert(ExpiryQueue::Slashes, 1);
+        set_block_number::<T>(now);
+    }: {
+        run_hook::<T>(now);
+    }
+    verify {
+        assert!(!PendingSlashes::<T>::contains_key(&geode));
+    }
+
+    expire_geode {
+        let a in 0 .. MAX_REPORTERS;
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        // the geode stays Registered with one attestor short of its target
+        let geode = register_geode::<T>(0);
+        MinAttestorNum::<T>::put(a + 1);
+        for i in 0..a {
+            let attestor = register_attestor::<T>(i);
+            attest::<T>(&attestor, &geode);
+        }
+
+        let now: BlockNumber = LivenessParameters::<T>::get().attestation_expiry + 2;
+        start_queues::<T>(now);
+        ExpiryCursors::<T>::insert(ExpiryQueue::RegisteredGeodes, 1);
+        set_block_number::<T>(now);
+    }: {
+        run_hook::<T>(now);
+    }
+    verify {
+        assert!(!pallet_geode::Geodes::<T>::contains_key(&geode));
+    }
+
+    remove_attestor {
+        let g in 0 .. pallet_attestor::MAX_ATTESTOR_GEODES;
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        // every geode is left short of attestors
+        let attestor = register_attestor::<T>(0);
+        for i in 0..g {
+            let geode = register_geode::<T>(i);
+            attest::<T>(&attestor, &geode);
+        }
+
+        let now: BlockNumber = LivenessParameters::<T>::get().attestor_notify_timeout + 2;
+        start_queues::<T>(now);
+        ExpiryCursors::<T>::insert(ExpiryQueue::Attestors, 1);
+        set_block_number::<T>(now);
+    }: {
+        run_hook::<T>(now);
+    }
+    verify {
+        assert!(!pallet_attestor::Attestors::<T>::contains_key(&attestor));
+    }
+
+    check_geode {
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        // the geode falls short of its raised target
+        let attestor = register_attestor::<T>(0);
+        let geode = register_geode::<T>(0);
+        attest::<T>(&attestor, &geode);
+        GeodeAttestorTargets::<T>::insert(&geode, 2);
+        TargetSweep::<T>::put(pallet_geode::Geodes::<T>::final_prefix().to_vec());
+
+        start_queues::<T>(2);
+        set_block_number::<T>(2);
+    }: {
+        run_hook::<T>(2);
+    }
+    verify {
+        assert_ne!(
+            pallet_geode::Geodes::<T>::get(&geode).state,
+            pallet_geode::GeodeState::Attested
+        );
+    }
+
+    revoke_geode {
+        let a in 1 .. MAX_REPORTERS;
+        set_block_number::<T>(1);
+        DegradeMode::<T>::put(false);
+        let geode = register_geode::<T>(0);
+        MinAttestorNum::<T>::put(a);
+        for i in 0..a {
+            let attestor = register_attestor::<T>(i);
+            attest::<T>(&attestor, &geode);
+        }
+        let enclave = EnclaveIdentity {
+            mr_enclave: [1u8; 32],
+            report_data: geode.encode(),
+            ..Default::default()
+        };
+        RevocationSweep::<T>::put(vec![enclave.measurement()]);
+
+        start_queues::<T>(2);
+        set_block_number::<T>(2);
+    }: {
+        run_hook::<T>(2);
+    }
+    verify {
+        assert!(pallet_attestor::GeodeAttestors::<T>::get(&geode).is_empty());
+    }
+}
+
+impl_benchmark_test_suite!(Liveness, crate::mock::new_test_ext(), crate::mock::Test,);
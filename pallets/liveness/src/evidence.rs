@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use codec::{Decode, Encode};
+use sp_core::sr25519::{Public, Signature};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// A message signed by the geode's enclave with its sr25519 key.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct SignedResponse {
+    pub msg: Vec<u8>,
+    pub signature: Signature,
+}
+
+/// What an attestor sent to a geode and what the geode's enclave answered.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct Transcript {
+    /// Challenge nonce or service request sent by the attestor.
+    pub request: Vec<u8>,
+    /// Signed answer of the enclave, `None` if the geode never answered.
+    pub response: Option<SignedResponse>,
+    /// Unix time in milliseconds the request was sent, as declared by the attestor.
+    pub sent_at: u64,
+    /// Unix time in milliseconds the answer was received, or the attestor gave up waiting, as
+    /// declared by the attestor.
+    pub received_at: u64,
+}
+
+/// Evidence of a geode's misconduct, vouched by the reporting attestor.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct Evidence {
+    /// The geode's sr25519 key, it has to decode to the geode's account.
+    pub geode_key: Public,
+    pub transcript: Transcript,
+    /// The attestor's sr25519 key, it has to decode to the attestor's account or be one of
+    /// the attestor's keys valid at the current block.
+    pub attestor_key: Public,
+    /// The attestor's signature of `attestor_payload`, binding the evidence to the reporter and
+    /// the chain.
+    pub attestor_signature: Signature,
+}
+
+/// A transcript presented by a provider appealing its geode's slash, showing the geode's
+/// enclave answered the reported request. Its timestamps aren't checked, the appeal window
+/// is the deadline.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct CounterEvidence {
+    /// The geode's sr25519 key, it has to decode to the geode's account.
+    pub geode_key: Public,
+    pub transcript: Transcript,
+}
+
+/// Encode bytes the way serde_json does, i.e. as an array of numbers.
+pub fn json_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut json = Vec::with_capacity(bytes.len() * 4 + 2);
+    json.push(b'[');
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            json.push(b',');
+        }
+        if *byte >= 100 {
+            json.push(b'0' + byte / 100);
+        }
+        if *byte >= 10 {
+            json.push(b'0' + byte / 10 % 10);
+        }
+        json.push(b'0' + byte % 10);
+    }
+    json.push(b']');
+    json
+}
+
+impl SignedResponse {
+    /// The enclave signs the json encoding of the message, as `geode_crypto::sr25519_sign_msg`
+    /// does.
+    pub fn verify(&self, key: &Public) -> bool {
+        sp_io::crypto::sr25519_verify(&self.signature, &json_bytes(&self.msg), key)
+    }
+}
+
+impl Transcript {
+    /// Whether the timestamps are ordered and the evidence isn't older than `max_age`.
+    pub fn is_fresh(&self, now: u64, max_age: u64) -> bool {
+        self.sent_at <= self.received_at
+            && self.received_at <= now
+            && now.saturating_sub(self.sent_at) <= max_age
+    }
+
+    /// Whether the geode failed to answer in time, or answered a challenge without signing the
+    /// nonce it was sent. The timestamps are only declared by the attestor, so a signed answer
+    /// is never a failure for being late.
+    pub fn shows_failure(&self, is_challenge: bool, timeout: u64) -> bool {
+        match &self.response {
+            None => self.received_at.saturating_sub(self.sent_at) > timeout,
+            Some(response) => is_challenge && response.msg != self.request,
+        }
+    }
+}
+
+impl Evidence {
+    /// Payload the attestor signs: the genesis hash of the chain, the reporting attestor's
+    /// account, the report type and the geode key followed by the transcript, SCALE encoded.
+    pub fn attestor_payload<H: Encode, A: Encode>(
+        &self,
+        genesis_hash: &H,
+        reporter: &A,
+        report_type: u8,
+    ) -> Vec<u8> {
+        (
+            genesis_hash,
+            reporter,
+            report_type,
+            &self.geode_key,
+            &self.transcript,
+        )
+            .encode()
+    }
+
+    /// Whether the attestor signed the evidence as the `reporter` on the chain of
+    /// `genesis_hash`, and the enclave signed the response if any.
+    pub fn verify_signatures<H: Encode, A: Encode>(
+        &self,
+        genesis_hash: &H,
+        reporter: &A,
+        report_type: u8,
+    ) -> bool {
+        sp_io::crypto::sr25519_verify(
+            &self.attestor_signature,
+            &self.attestor_payload(genesis_hash, reporter, report_type),
+            &self.attestor_key,
+        ) && self
+            .transcript
+            .response
+            .as_ref()
+            .map_or(true, |response| response.verify(&self.geode_key))
+    }
+}
+
+impl CounterEvidence {
+    /// Whether the enclave signed an answer to the reported `request`, echoing it for a
+    /// challenge.
+    pub fn shows_liveness(&self, request: &[u8], is_challenge: bool) -> bool {
+        self.transcript.request == request
+            && self.transcript.response.as_ref().map_or(false, |response| {
+                response.verify(&self.geode_key) && (!is_challenge || response.msg == request)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::{sr25519::Pair, Pair as PairT};
+
+    #[test]
+    fn json_bytes_should_work() {
+        assert_eq!(json_bytes(&[]), b"[]".to_vec());
+        assert_eq!(
+            json_bytes(&[0, 7, 42, 100, 255]),
+            b"[0,7,42,100,255]".to_vec()
+        );
+    }
+
+    #[test]
+    fn shows_failure_should_work() {
+        let geode = Pair::from_seed(&[1; 32]);
+        let answer = |msg: &[u8]| SignedResponse {
+            msg: msg.to_vec(),
+            signature: geode.sign(&json_bytes(msg)),
+        };
+        let transcript = |response, received_at| Transcript {
+            request: vec![1, 2, 3],
+            response,
+            sent_at: 1_000,
+            received_at,
+        };
+
+        assert!(answer(&[1, 2, 3]).verify(&geode.public()));
+        assert!(!transcript(Some(answer(&[1, 2, 3])), 1_500).shows_failure(true, 1_000));
+        // claimed late, the attestor could have held the answer back
+        assert!(!transcript(Some(answer(&[1, 2, 3])), 2_500).shows_failure(true, 1_000));
+        // wrong nonce, only a failure for challenges
+        assert!(transcript(Some(answer(&[3, 2, 1])), 1_500).shows_failure(true, 1_000));
+        assert!(!transcript(Some(answer(&[3, 2, 1])), 1_500).shows_failure(false, 1_000));
+        // no answer, the attestor has to wait for the timeout
+        assert!(!transcript(None, 1_500).shows_failure(true, 1_000));
+        assert!(transcript(None, 2_500).shows_failure(true, 1_000));
+    }
+
+    #[test]
+    fn shows_liveness_should_work() {
+        let geode = Pair::from_seed(&[1; 32]);
+        let other = Pair::from_seed(&[2; 32]);
+        let counter = |signer: &Pair, response: bool| CounterEvidence {
+            geode_key: geode.public(),
+            transcript: Transcript {
+                request: vec![1, 2, 3],
+                response: if response {
+                    Some(SignedResponse {
+                        msg: vec![1, 2, 3],
+                        signature: signer.sign(&json_bytes(&[1, 2, 3])),
+                    })
+                } else {
+                    None
+                },
+                sent_at: 1_000,
+                received_at: 1_500,
+            },
+        };
+
+        assert!(counter(&geode, true).shows_liveness(&[1, 2, 3], true));
+        // signed by somebody else
+        assert!(!counter(&other, true).shows_liveness(&[1, 2, 3], true));
+        // an unanswered request proves nothing
+        assert!(!counter(&geode, false).shows_liveness(&[1, 2, 3], true));
+        // not the reported request
+        assert!(!counter(&geode, true).shows_liveness(&[3, 2, 1], false));
+    }
+}
@@ -8,14 +8,37 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod evidence;
+mod report;
+mod verifier;
+pub mod weights;
+
+pub use evidence::*;
+pub use report::*;
+pub use verifier::*;
+pub use weights::WeightInfo;
+
 #[frame_support::pallet]
 pub mod pallet {
+    use crate::evidence::{CounterEvidence, Evidence};
+    use crate::report::{
+        base64_decode, hex_encode, json_string_field, parse_timestamp, AttestationReport,
+        EnclaveIdentity, QuoteStatus, ReportVerifier,
+    };
+    use crate::weights::WeightInfo;
     use core::convert::{TryFrom, TryInto};
-    use frame_support::{debug, ensure};
+    use frame_support::traits::{Currency, Imbalance, OnUnbalanced, Randomness, UnixTime};
+    use frame_support::{debug, ensure, storage::StoragePrefixedMap};
     use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
     use frame_system::pallet_prelude::*;
+    use pallet_enclave::{Measurement, MeasurementRegistry, MeasurementStatus};
     use primitives::BlockNumber;
-    use sp_runtime::{Percent, RuntimeDebug, SaturatedConversion};
+    use sp_runtime::{
+        traits::{Hash, Zero},
+        Percent, RuntimeDebug, SaturatedConversion,
+    };
     use sp_std::borrow::ToOwned;
     use sp_std::collections::btree_set::BTreeSet;
     use sp_std::prelude::*;
@@ -23,13 +46,50 @@ pub mod pallet {
     #[cfg(feature = "std")]
     use serde::{Deserialize, Serialize};
 
+    // Default liveness parameters, see `LivenessParams`.
     pub const REPORT_APPROVAL_RATIO: Percent = Percent::from_percent(50);
+    pub const REPORT_QUORUM: u32 = 1;
     pub const REPORT_EXPIRY_BLOCK_NUMBER: BlockNumber = 10;
     pub const ATTESTATION_EXPIRY_BLOCK_NUMBER: BlockNumber = 30;
     pub const UNKNOWN_EXPIRY_BLOCK_NUMBER: BlockNumber = 5760;
     pub const DEGRADED_INSTANTIATED_EXPIRY_BLOCK_NUMBER: BlockNumber = 30;
     pub const ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER: BlockNumber = 12;
+    pub const APPEAL_WINDOW_BLOCK_NUMBER: BlockNumber = 100;
+    pub const APPEAL_DEADLINE_BLOCK_NUMBER: BlockNumber = 1000;
+    pub const ASSIGNMENT_RETRY_BLOCK_NUMBER: BlockNumber = 10;
+    pub const UNBONDING_PERIOD_BLOCK_NUMBER: BlockNumber = 200;
+    pub const RESPONSE_TIMEOUT_MS: u64 = 10_000;
+    pub const EVIDENCE_MAX_AGE_MS: u64 = 3_600_000;
+    // Default remote attestation parameters, see `RaParams`.
+    pub const REPORT_MAX_AGE_MS: u64 = 600_000;
+    // Default slash parameters, see `SlashParams`.
+    pub const CHALLENGE_SLASH_RATIO: Percent = Percent::from_percent(10);
+    pub const SERVICE_SLASH_RATIO: Percent = Percent::from_percent(5);
+    pub const REPORTERS_REWARD_RATIO: Percent = Percent::from_percent(10);
+    pub const HOLDER_COMPENSATION_RATIO: Percent = Percent::from_percent(40);
+    pub const MISSED_HEARTBEAT_SLASH_RATIO: Percent = Percent::from_percent(5);
+    pub const BAD_ATTESTATION_SLASH_RATIO: Percent = Percent::from_percent(10);
+    pub const FALSE_REPORT_SLASH_RATIO: Percent = Percent::from_percent(10);
     pub const DEFAULT_MIN_ATTESTOR_NUM: u32 = 1;
+    /// Number of reporters a report is assumed to have when weighing `overturn_report`, the
+    /// actual weight is refunded after dispatch.
+    pub const MAX_REPORTERS: u32 = 100;
+    /// Max number of property classes with their own attestor target.
+    pub const MAX_TARGET_CLASSES: u32 = 16;
+    /// Subject of the randomness attestors are assigned to geodes with.
+    pub const ASSIGNMENT_SUBJECT: &[u8] = b"liveness_assignment";
+    /// Subject of the randomness the nonces of IAS reports are drawn from.
+    pub const NONCE_SUBJECT: &[u8] = b"liveness_nonce";
+    /// Health of a geode which passed every challenge.
+    pub const MAX_HEALTH: u8 = 100;
+    /// Health a geode regains for each challenge it passed, and loses for each it failed.
+    pub const HEALTH_RECOVERY: u8 = 10;
+    pub const HEALTH_PENALTY: u8 = 25;
+    /// Max total length of the fields of an attestation report.
+    pub const MAX_REPORT_LENGTH: u32 = 16_384;
+    /// Share of the hook weight kept for assigning attestors, so that the other queues can't
+    /// starve it.
+    pub const ASSIGNMENT_WEIGHT_SHARE: Percent = Percent::from_percent(25);
 
     /// Geode state
     #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -71,6 +131,279 @@ pub mod pallet {
     }
 
     pub type ReportOf<T> = Report<<T as frame_system::Config>::AccountId>;
+    pub type BalanceOf<T> = pallet_geode::BalanceOf<T>;
+
+    /// How strict the remote attestation of geodes is.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub struct RaParams {
+        /// Accept platforms which need an update or a configuration change, e.g. with the
+        /// quote status `GROUP_OUT_OF_DATE`.
+        pub allow_conditional: bool,
+        /// Accept enclaves built in debug mode, for test networks only.
+        pub allow_debug: bool,
+        /// Milliseconds the timestamp of an IAS report can be away from the chain's time.
+        pub report_max_age: u64,
+    }
+
+    impl Default for RaParams {
+        fn default() -> Self {
+            RaParams {
+                allow_conditional: false,
+                allow_debug: false,
+                report_max_age: REPORT_MAX_AGE_MS,
+            }
+        }
+    }
+
+    impl RaParams {
+        /// A zero freshness window would reject every IAS report.
+        pub fn is_valid(&self) -> bool {
+            self.report_max_age > 0
+        }
+    }
+
+    /// Whether a slash can still be appealed or waits for governance.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub enum SlashStatus {
+        /// Within the appeal window, the funds are slashed once it ends.
+        Pending,
+        /// The provider appealed, the funds are slashed only if governance confirms.
+        Appealed,
+    }
+
+    impl Default for SlashStatus {
+        fn default() -> Self {
+            SlashStatus::Pending
+        }
+    }
+
+    /// A geode's slash waiting for its appeal window to end or for governance to decide.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct PendingSlash<AccountId: Ord, Hash> {
+        pub report_type: ReportType,
+        /// Provider of the geode when it got slashed.
+        pub provider: AccountId,
+        /// Order the geode was serving when it got slashed.
+        pub order: Option<Hash>,
+        /// Attestors which reported the misconduct.
+        pub reporters: BTreeSet<AccountId>,
+        /// Attestors of the geode which didn't report it.
+        pub attestors: BTreeSet<AccountId>,
+        /// Block number when the report got approved.
+        pub start: BlockNumber,
+        pub status: SlashStatus,
+        /// Request of the transcript which got the geode slashed, the appeal has to show the
+        /// enclave answering it.
+        pub request: Vec<u8>,
+        /// Transcript presented by the provider when appealing.
+        #[cfg_attr(feature = "std", serde(skip))]
+        pub counter_evidence: Option<CounterEvidence>,
+    }
+
+    pub type PendingSlashOf<T> =
+        PendingSlash<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::Hash>;
+
+    /// Timeouts of liveness in blocks, and the approval ratio of misconduct reports.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub struct LivenessParams {
+        /// How long a misconduct report stays open.
+        pub report_expiry: BlockNumber,
+        /// How long a Registered geode has to get attested before being removed.
+        pub attestation_expiry: BlockNumber,
+        /// How long an Unknown geode is kept before being removed.
+        pub unknown_expiry: BlockNumber,
+        /// How long a DegradedInstantiated geode has to get attestors before turning Unknown.
+        pub degraded_instantiated_expiry: BlockNumber,
+        /// How long an attestor can stay without notifying the chain before being removed.
+        pub attestor_notify_timeout: BlockNumber,
+        /// Ratio of the stake of a geode's attestors which has to report a misconduct to
+        /// slash it.
+        pub report_approval_ratio: Percent,
+        /// Number of a geode's attestors which have to report a misconduct to slash it, capped
+        /// by the number of its attestors.
+        pub report_quorum: u32,
+        /// Milliseconds a geode has to answer an attestor's request in.
+        pub response_timeout: u64,
+        /// Milliseconds after which the evidence of a misconduct isn't accepted anymore.
+        pub evidence_max_age: u64,
+        /// How long the provider of a slashed geode can appeal before the funds are slashed.
+        pub appeal_window: BlockNumber,
+        /// How long governance has to decide an appealed slash after the appeal window, the
+        /// slash is confirmed once it passed.
+        pub appeal_deadline: BlockNumber,
+        /// How long a geode which couldn't get enough attestors assigned waits before trying
+        /// again.
+        pub assignment_retry: BlockNumber,
+        /// How long an exiting attestor keeps attesting its geodes while they are handed over
+        /// to other attestors, before it is removed and its stake released.
+        pub unbonding_period: BlockNumber,
+    }
+
+    impl Default for LivenessParams {
+        fn default() -> Self {
+            LivenessParams {
+                report_expiry: REPORT_EXPIRY_BLOCK_NUMBER,
+                attestation_expiry: ATTESTATION_EXPIRY_BLOCK_NUMBER,
+                unknown_expiry: UNKNOWN_EXPIRY_BLOCK_NUMBER,
+                degraded_instantiated_expiry: DEGRADED_INSTANTIATED_EXPIRY_BLOCK_NUMBER,
+                attestor_notify_timeout: ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER,
+                report_approval_ratio: REPORT_APPROVAL_RATIO,
+                report_quorum: REPORT_QUORUM,
+                response_timeout: RESPONSE_TIMEOUT_MS,
+                evidence_max_age: EVIDENCE_MAX_AGE_MS,
+                appeal_window: APPEAL_WINDOW_BLOCK_NUMBER,
+                appeal_deadline: APPEAL_DEADLINE_BLOCK_NUMBER,
+                assignment_retry: ASSIGNMENT_RETRY_BLOCK_NUMBER,
+                unbonding_period: UNBONDING_PERIOD_BLOCK_NUMBER,
+            }
+        }
+    }
+
+    impl LivenessParams {
+        /// Timeouts and the approval ratio must not be zero, otherwise items expire as soon as
+        /// they are created and any report slashes the geode. Evidence must live longer than
+        /// the response timeout so that unanswered requests can be reported. Appeals have to
+        /// end before slashed geodes, which are Unknown, get removed.
+        pub fn is_valid(&self) -> bool {
+            self.report_expiry > 0
+                && self.attestation_expiry > 0
+                && self.unknown_expiry > 0
+                && self.degraded_instantiated_expiry > 0
+                && self.attestor_notify_timeout > 0
+                && !self.report_approval_ratio.is_zero()
+                && self.report_quorum > 0
+                && self.response_timeout > 0
+                && self.evidence_max_age > self.response_timeout
+                && self.appeal_window > 0
+                && self.appeal_window < self.unknown_expiry
+                && self.appeal_deadline > 0
+                && self.assignment_retry > 0
+                && self.unbonding_period > 0
+        }
+    }
+
+    /// How much of a provider's stake is slashed for a misbehaving geode, and how the slashed
+    /// funds are paid out.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub struct SlashParams {
+        /// Ratio of the stake slashed for a failed challenge, per offence of the provider.
+        pub challenge_slash: Percent,
+        /// Ratio of the stake slashed for a failed service check, per offence of the provider.
+        pub service_slash: Percent,
+        /// Ratio of the slashed funds shared by the reporting attestors.
+        pub reporters_reward: Percent,
+        /// Ratio of the slashed funds paid to the user of the order the geode was serving.
+        pub holder_compensation: Percent,
+        /// Ratio of the stake slashed from an attestor removed for missing its heartbeats.
+        pub missed_heartbeat_slash: Percent,
+        /// Ratio of the stake slashed from the attestors of a slashed geode which didn't
+        /// report it.
+        pub bad_attestation_slash: Percent,
+        /// Ratio of the stake slashed from the attestors of an overturned report.
+        pub false_report_slash: Percent,
+    }
+
+    impl Default for SlashParams {
+        fn default() -> Self {
+            SlashParams {
+                challenge_slash: CHALLENGE_SLASH_RATIO,
+                service_slash: SERVICE_SLASH_RATIO,
+                reporters_reward: REPORTERS_REWARD_RATIO,
+                holder_compensation: HOLDER_COMPENSATION_RATIO,
+                missed_heartbeat_slash: MISSED_HEARTBEAT_SLASH_RATIO,
+                bad_attestation_slash: BAD_ATTESTATION_SLASH_RATIO,
+                false_report_slash: FALSE_REPORT_SLASH_RATIO,
+            }
+        }
+    }
+
+    impl SlashParams {
+        /// The rewards and the compensation can't pay out more than what is slashed.
+        pub fn is_valid(&self) -> bool {
+            self.reporters_reward.deconstruct() as u32
+                + self.holder_compensation.deconstruct() as u32
+                <= 100
+        }
+
+        /// Ratio of the stake slashed for the provider's `offences`-th misconduct of the given
+        /// type, capped at the whole stake.
+        pub fn slash_ratio(&self, report_type: &ReportType, offences: u32) -> Percent {
+            let ratio = match report_type {
+                ReportType::Challenge => self.challenge_slash,
+                _ => self.service_slash,
+            };
+            Percent::from_percent(
+                (ratio.deconstruct() as u32)
+                    .saturating_mul(offences)
+                    .min(100) as u8,
+            )
+        }
+
+        /// Ratio of the stake slashed from an attestor for the offence.
+        pub fn attestor_slash_ratio(&self, offence: &AttestorOffence) -> Percent {
+            match offence {
+                AttestorOffence::MissedHeartbeat => self.missed_heartbeat_slash,
+                AttestorOffence::BadAttestation => self.bad_attestation_slash,
+                AttestorOffence::FalseReport => self.false_report_slash,
+            }
+        }
+    }
+
+    /// Misbehaviours attestors get slashed for.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub enum AttestorOffence {
+        /// The attestor stopped notifying the chain and got removed.
+        MissedHeartbeat,
+        /// The attestor kept attesting a geode slashed for misconduct without reporting it.
+        BadAttestation,
+        /// The attestor reported a misconduct which got overturned.
+        FalseReport,
+    }
+
+    /// Queues of items expiring after a timeout, which are processed in `on_initialize`.
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
+    pub enum ExpiryQueue {
+        /// Misconduct reports, queued in `ReportQueue`.
+        Reports,
+        /// Geodes waiting for attestation, queued in `pallet_geode::StateQueues`.
+        RegisteredGeodes,
+        /// Geodes in Unknown state, queued in `pallet_geode::StateQueues`.
+        UnknownGeodes,
+        /// Geodes lacking of attestors, queued in `pallet_geode::StateQueues`.
+        DegradedInstantiatedGeodes,
+        /// Attestors by their last notification, queued in `pallet_attestor::NotifyQueue`.
+        Attestors,
+        /// Slashes waiting for their appeal window to end, queued in `SlashQueue`.
+        Slashes,
+        /// Appealed slashes waiting for governance until their deadline, queued in
+        /// `AppealQueue`.
+        Appeals,
+        /// Geodes waiting for attestors to be assigned, queued in `AssignmentQueue`.
+        Assignments,
+        /// Attestors waiting for their unbonding period to end, queued in `UnbondingQueue`.
+        Unbonding,
+    }
+
+    impl ExpiryQueue {
+        pub const ALL: [ExpiryQueue; 9] = [
+            ExpiryQueue::Reports,
+            ExpiryQueue::RegisteredGeodes,
+            ExpiryQueue::UnknownGeodes,
+            ExpiryQueue::DegradedInstantiatedGeodes,
+            ExpiryQueue::Attestors,
+            ExpiryQueue::Slashes,
+            ExpiryQueue::Appeals,
+            ExpiryQueue::Assignments,
+            ExpiryQueue::Unbonding,
+        ];
+    }
 
     /// Configure the pallet by specifying the parameters and types on which it depends.
     #[pallet::config]
@@ -79,6 +412,36 @@ pub mod pallet {
     {
         /// Because this pallet emits events, it depends on the runtime's definition of an event.
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// Max number of blocks and items each expiry queue visits in a block, the items left
+        /// are processed in the next blocks.
+        #[pallet::constant]
+        type MaxExpiriesPerBlock: Get<u32>;
+        /// Max weight the hook spends in a block, the items left are processed in the next
+        /// blocks. It has to cover the removal of an attestor with `MAX_ATTESTOR_GEODES`
+        /// geodes.
+        #[pallet::constant]
+        type MaxHookWeight: Get<Weight>;
+        /// Time source used to check the timestamps of misconduct evidence.
+        type UnixTime: UnixTime;
+        /// Source of randomness attestors are assigned to geodes with, e.g. BABE's.
+        type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+        /// Origin allowed to change the liveness parameters, e.g. root or the council.
+        type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+        /// Origin deciding appealed slashes, e.g. the council or the technical committee.
+        type AppealOrigin: EnsureOrigin<Self::Origin>;
+        /// Resolves the user to compensate when the geode serving its order is slashed.
+        type OrderHolder: pallet_geode::OrderHolder<Self::AccountId, Self::Hash>;
+        /// Handler for the slashed funds left after rewards and compensation, e.g. the
+        /// treasury.
+        type Slash: OnUnbalanced<pallet_geode::NegativeImbalanceOf<Self>>;
+        /// Handler for the funds slashed from attestors, e.g. the treasury.
+        type AttestorSlash: OnUnbalanced<pallet_attestor::NegativeImbalanceOf<Self>>;
+        /// Checks the signatures of the attestation reports attestors submit.
+        type ReportVerifier: ReportVerifier;
+        /// Enclave builds geodes may run, e.g. `pallet_enclave`.
+        type MeasurementRegistry: MeasurementRegistry;
+        /// Weight information for extrinsics and hooks in this pallet.
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::pallet]
@@ -91,6 +454,193 @@ pub mod pallet {
     pub(super) type Reports<T: Config> =
         StorageMap<_, Blake2_128Concat, (T::AccountId, u8), ReportOf<T>, ValueQuery>;
 
+    /// Misconduct reports indexed by the block they started. Entries are left behind when
+    /// reports are removed, they are checked against `Reports` when processed.
+    #[pallet::storage]
+    #[pallet::getter(fn report_queue)]
+    pub type ReportQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<(T::AccountId, u8)>, ValueQuery>;
+
+    /// The first block of each expiry queue which hasn't been fully processed yet. The queues
+    /// start at genesis, or at the block the pallet first runs at when added later.
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_cursors)]
+    pub type ExpiryCursors<T: Config> =
+        StorageMap<_, Twox64Concat, ExpiryQueue, BlockNumber, ValueQuery>;
+
+    /// Timeouts and approval ratio used by liveness, set by governance.
+    #[pallet::storage]
+    #[pallet::getter(fn liveness_params)]
+    pub type LivenessParameters<T: Config> = StorageValue<_, LivenessParams, ValueQuery>;
+
+    /// Slash ratios and payouts of misbehaving geodes, set by governance.
+    #[pallet::storage]
+    #[pallet::getter(fn slash_params)]
+    pub type SlashParameters<T: Config> = StorageValue<_, SlashParams, ValueQuery>;
+
+    /// Slashes of geodes which can still be appealed or wait for governance.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_slashes)]
+    pub type PendingSlashes<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, PendingSlashOf<T>, ValueQuery>;
+
+    /// Slashed geodes indexed by the block their report got approved. Entries are left behind
+    /// when slashes are appealed, they are checked against `PendingSlashes` when processed.
+    #[pallet::storage]
+    #[pallet::getter(fn slash_queue)]
+    pub type SlashQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    /// Appealed slashes indexed by the block their report got approved. Entries are left
+    /// behind when appeals are decided, they are checked against `PendingSlashes` when
+    /// processed.
+    #[pallet::storage]
+    #[pallet::getter(fn appeal_queue)]
+    pub type AppealQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    /// Geodes waiting for attestors to be assigned, indexed by the block they got queued.
+    #[pallet::storage]
+    #[pallet::getter(fn assignment_queue)]
+    pub type AssignmentQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    /// Attestors assigned to each geode which haven't attested it yet.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_assignments)]
+    pub type GeodeAssignments<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BTreeSet<T::AccountId>, ValueQuery>;
+
+    /// Geodes assigned to each attestor which it hasn't attested yet.
+    #[pallet::storage]
+    #[pallet::getter(fn attestor_assignments)]
+    pub type AttestorAssignments<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BTreeSet<T::AccountId>, ValueQuery>;
+
+    /// Block each exiting attestor started unbonding at.
+    #[pallet::storage]
+    #[pallet::getter(fn unbonding)]
+    pub type Unbonding<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumber, OptionQuery>;
+
+    /// Attestors unbonding, indexed by the block they started. Entries are left behind when
+    /// attestors are removed before the end, consumers have to check them against `Unbonding`.
+    #[pallet::storage]
+    #[pallet::getter(fn unbonding_queue)]
+    pub type UnbondingQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::AccountId>, ValueQuery>;
+
+    /// Number of attestors the geodes matching each selector need, set by governance.
+    #[pallet::storage]
+    #[pallet::getter(fn attestor_target_classes)]
+    pub type AttestorTargetClasses<T: Config> =
+        StorageValue<_, Vec<(pallet_geode::Selector, u32)>, ValueQuery>;
+
+    /// Number of attestors each geode needs, set by its provider.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_attestor_targets)]
+    pub type GeodeAttestorTargets<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultGeodeHealth<T: Config>() -> u8 {
+        MAX_HEALTH
+    }
+
+    /// Health of each geode scored by the challenge results in its attestors' heartbeats,
+    /// from 0 to `MAX_HEALTH`.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_health)]
+    pub type GeodeHealth<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u8, ValueQuery, DefaultGeodeHealth<T>>;
+
+    /// Raw key of the last geode checked against the attestor targets after they changed,
+    /// `None` once every geode is checked.
+    #[pallet::storage]
+    #[pallet::getter(fn target_sweep)]
+    pub type TargetSweep<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+    /// Number of times each provider's geodes got slashed.
+    #[pallet::storage]
+    #[pallet::getter(fn provider_offences)]
+    pub type ProviderOffences<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn ra_root_cert)]
+    pub type RaRootCert<T: Config> = StorageValue<_, Vec<u8>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn ra_params)]
+    pub type RaParameters<T: Config> = StorageValue<_, RaParams, ValueQuery>;
+
+    /// Nonce the IAS reports of each geode have to carry, issued when its first attestor gets
+    /// assigned and dropped once no assigned attestor is left.
+    #[pallet::storage]
+    #[pallet::getter(fn attestation_nonces)]
+    pub type AttestationNonces<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Vec<u8>, ValueQuery>;
+
+    /// Enclave build each geode was last attested with.
+    #[pallet::storage]
+    #[pallet::getter(fn geode_measurements)]
+    pub type GeodeMeasurements<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, Measurement, OptionQuery>;
+
+    /// Geodes indexed by the enclave build they were last attested with.
+    #[pallet::storage]
+    #[pallet::getter(fn measurement_geodes)]
+    pub type MeasurementGeodes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        Measurement,
+        Blake2_128Concat,
+        T::AccountId,
+        (),
+        OptionQuery,
+    >;
+
+    /// Revoked enclave builds whose geodes are still to be degraded, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn revocation_sweep)]
+    pub type RevocationSweep<T: Config> = StorageValue<_, Vec<Measurement>, ValueQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig {
+        pub params: LivenessParams,
+        pub slash_params: SlashParams,
+        pub ra_root_cert: Vec<u8>,
+        pub ra_params: RaParams,
+    }
+
+    #[cfg(feature = "std")]
+    impl Default for GenesisConfig {
+        fn default() -> Self {
+            GenesisConfig {
+                params: Default::default(),
+                slash_params: Default::default(),
+                ra_root_cert: Default::default(),
+                ra_params: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig {
+        fn build(&self) {
+            assert!(self.params.is_valid(), "Invalid liveness params");
+            assert!(self.slash_params.is_valid(), "Invalid slash params");
+            assert!(self.ra_params.is_valid(), "Invalid ra params");
+            <LivenessParameters<T>>::put(&self.params);
+            <SlashParameters<T>>::put(&self.slash_params);
+            <RaRootCert<T>>::put(&self.ra_root_cert);
+            <RaParameters<T>>::put(&self.ra_params);
+            for queue in ExpiryQueue::ALL.iter() {
+                <ExpiryCursors<T>>::insert(queue, 0);
+            }
+        }
+    }
+
     #[pallet::type_value]
     pub fn DefaultMinAttestorNum<T: Config>() -> u32 {
         DEFAULT_MIN_ATTESTOR_NUM
@@ -109,10 +659,20 @@ pub mod pallet {
     #[pallet::getter(fn degrade_mode)]
     pub type DegradeMode<T: Config> = StorageValue<_, bool, ValueQuery, DefaultDegradeMode<T>>;
 
+    /// Block the degrade mode ended at. Registered and DegradedInstantiated geodes queued
+    /// before it don't expire, their time in the state restarts instead.
+    #[pallet::storage]
+    #[pallet::getter(fn degrade_mode_end)]
+    pub type DegradeModeEnd<T: Config> = StorageValue<_, BlockNumber, ValueQuery>;
+
     // Pallets use events to inform users when important changes are made.
     // https://substrate.dev/docs/en/knowledgebase/runtime/events
     #[pallet::event]
-    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::metadata(
+        T::AccountId = "AccountId",
+        BalanceOf<T> = "Balance",
+        pallet_attestor::BalanceOf<T> = "Balance"
+    )]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         /// Attestor attested a geode. \[attestor_id, geode_id\]
@@ -124,6 +684,15 @@ pub mod pallet {
         ReportBlame(T::AccountId, T::AccountId),
         /// Geode being slashed due to approval of misconduct report. \[geode_id\]
         SlashGeode(T::AccountId),
+        /// Funds slashed for a geode paid out.
+        /// \[geode_id, slashed, reporters_reward, compensation, remainder\]
+        SlashPayout(
+            T::AccountId,
+            BalanceOf<T>,
+            BalanceOf<T>,
+            BalanceOf<T>,
+            BalanceOf<T>,
+        ),
         /// Event documentation should end with an array that provides descriptive names for event
         /// parameters. [something, who]
         SomethingStored(u32, T::AccountId),
@@ -131,6 +700,37 @@ pub mod pallet {
         AttestorExited(T::AccountId),
         /// Storage cleaned
         StorageCleaned,
+        /// Liveness parameters updated by governance.
+        LivenessParamsUpdated(LivenessParams),
+        /// Slash parameters updated by governance.
+        SlashParamsUpdated(SlashParams),
+        /// Attestor slashed. \[attestor_id, offence, amount\]
+        SlashAttestor(T::AccountId, AttestorOffence, pallet_attestor::BalanceOf<T>),
+        /// Misconduct report overturned by governance. \[geode_id, report_type\]
+        ReportOverturned(T::AccountId, u8),
+        /// Provider appealed its geode's slash. \[geode_id\]
+        SlashAppealed(T::AccountId),
+        /// Governance confirmed an appealed slash. \[geode_id\]
+        SlashConfirmed(T::AccountId),
+        /// Governance reversed an appealed slash. \[geode_id\]
+        SlashReversed(T::AccountId),
+        /// Attestors assigned to attest a geode. \[geode_id, Vec<attestor_id>\]
+        AttestorsAssigned(T::AccountId, Vec<T::AccountId>),
+        /// Attestor targets of the property classes updated by governance.
+        AttestorTargetClassesUpdated(Vec<(pallet_geode::Selector, u32)>),
+        /// Provider set the attestor target of its geode. \[geode_id, target\]
+        GeodeAttestorTargetSet(T::AccountId, u32),
+        /// Attestor started unbonding, it is removed at the block. \[attestor_id, block\]
+        AttestorUnbonding(T::AccountId, BlockNumber),
+        /// Geode's health dropped to zero after failing challenges. \[geode_id\]
+        GeodeUnhealthy(T::AccountId),
+        /// Root certificate of the attestation reports updated by governance.
+        RaRootCertUpdated,
+        /// Remote attestation parameters updated by governance.
+        RaParamsUpdated(RaParams),
+        /// The enclave build the geode runs was revoked, its attestations are dropped.
+        /// \[geode_id\]
+        GeodeEnclaveRevoked(T::AccountId),
     }
 
     // Errors inform users that something went wrong.
@@ -142,110 +742,290 @@ pub mod pallet {
         NotAttestingFor,
         /// Invalid Report Type
         InvalidReportType,
+        /// Liveness timeouts and approval ratio must not be zero.
+        InvalidLivenessParams,
+        /// The evidence isn't signed by the geode and the attestor, or is stale.
+        InvalidEvidence,
+        /// The evidence doesn't show the geode failing.
+        NoMisconduct,
+        /// Rewards and compensation of slashes must not exceed the slashed funds.
+        InvalidSlashParams,
+        /// No such misconduct report.
+        InvalidReport,
+        /// The geode has no slash pending.
+        NoPendingSlash,
+        /// The appeal window ended or the slash is already appealed.
+        AppealClosed,
+        /// The slash isn't appealed.
+        NotAppealed,
+        /// The deadline to decide the appeal passed, the slash is confirmed.
+        AppealDeadlinePassed,
+        /// Only the provider of the slashed geode can appeal.
+        NotProvider,
+        /// The attestor isn't assigned to attest the geode.
+        NotAssigned,
+        /// More property classes than `MAX_TARGET_CLASSES`.
+        TooManyTargetClasses,
+        /// The attestor is unbonding and doesn't take new geodes.
+        AttestorUnbonding,
+        /// The attestation report is malformed, too long or its signature doesn't chain up to
+        /// the root certificate.
+        InvalidAttestationReport,
+        /// The quote status or the debug mode of the enclave isn't accepted.
+        InsecureEnclave,
+        /// The enclave build isn't approved, or is deprecated or revoked.
+        EnclaveNotAllowed,
+        /// The enclave didn't bind the geode's account to the report.
+        ReportNotBound,
+        /// The IAS report doesn't carry the nonce issued for the geode.
+        InvalidReportNonce,
+        /// The timestamp of the IAS report is outside the freshness window.
+        StaleAttestationReport,
+        /// The freshness window of IAS reports must not be zero.
+        InvalidRaParams,
     }
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-        /// 1. At every block, check if a misconduct report has expired or not,
-        /// if expired, clean the report.
-        /// 2. At every block, check if any geode haven't get attested after an expiring block,
-        /// if expired, clean the report.
+        /// 1. At every block, clean the misconduct reports which have expired.
+        /// 2. At every block, remove the geodes which haven't got attested or stayed Unknown
+        /// after an expiring block, and detach the DegradedInstantiated geodes which expired.
+        /// 3. At every block, remove the attestors which haven't notified the chain in time.
+        /// 4. At every block, assign attestors to the geodes registered or left short of
+        /// attestors in the previous blocks. `ASSIGNMENT_WEIGHT_SHARE` of the hook weight is
+        /// kept for it.
+        /// 5. When the attestor targets change, check the geodes against them a few at a time.
+        /// 6. At every block, remove the attestors whose unbonding period ended and release
+        /// their stake.
+        /// 7. When enclave builds are revoked, degrade the geodes running them a few at a time.
+        /// 8. At every block, slash the funds of the geodes whose appeal window ended, or whose
+        /// appeal governance didn't decide before the deadline.
+        ///
+        /// Items are queued by the block they started, so that each block only visits the
+        /// items expiring in it. Each item is weighed before it is processed, the items which
+        /// don't fit in `MaxHookWeight` anymore are left to the next blocks.
         fn on_initialize(block_number: T::BlockNumber) -> Weight {
+            let limit = T::MaxHookWeight::get();
+            let mut remaining = limit.saturating_sub(<T as Config>::WeightInfo::on_initialize());
+            let mut assignment_weight = ASSIGNMENT_WEIGHT_SHARE * remaining;
+            remaining -= assignment_weight;
             if let Ok(now) = TryInto::<BlockNumber>::try_into(block_number) {
-                // check is there a need to cancel degrade mode
+                let params = <LivenessParameters<T>>::get();
+
+                // the expiry queues of a pallet added after genesis start at the block it
+                // first runs at
+                for queue in ExpiryQueue::ALL.iter() {
+                    if !<ExpiryCursors<T>>::contains_key(queue) {
+                        <ExpiryCursors<T>>::insert(queue, now);
+                    }
+                }
+
+                // check is there a need to cancel degrade mode, the geodes which didn't expire
+                // meanwhile get their time restarted as they are visited
                 if <DegradeMode<T>>::get()
                     && pallet_attestor::AttestorNum::<T>::get() >= <MinAttestorNum<T>>::get()
                 {
-                    // reset all the start block num for degraded geode
-                    <pallet_geode::Pallet<T>>::reset_degraded_block_num();
+                    <DegradeModeEnd<T>>::put(now);
                     <DegradeMode<T>>::put(false);
                 }
 
                 // clean expired reports
-                let mut expired = Vec::<(T::AccountId, u8)>::new();
-                <Reports<T>>::iter()
-                    .map(|(key, report)| {
-                        if report.start + REPORT_EXPIRY_BLOCK_NUMBER < now {
-                            expired.push(key);
+                Self::process_queue(
+                    ExpiryQueue::Reports,
+                    now.checked_sub(params.report_expiry + 1),
+                    &mut remaining,
+                    |block| <ReportQueue<T>>::take(block),
+                    |block, rest| <ReportQueue<T>>::insert(block, rest),
+                    |_| <T as Config>::WeightInfo::expire_report(),
+                    |block, key| {
+                        if <Reports<T>>::contains_key(&key)
+                            && <Reports<T>>::get(&key).start == block
+                        {
+                            <Reports<T>>::remove(&key);
                         }
-                    })
-                    .all(|_| true);
-                for key in expired {
-                    <Reports<T>>::remove(key);
-                }
+                    },
+                );
 
-                // clean expired geodes
-                let mut expired_geodes = Vec::<T::AccountId>::new();
-                if !<DegradeMode<T>>::get() {
-                    pallet_geode::RegisteredGeodes::<T>::iter()
-                        .map(|(key, start)| {
-                            if start + ATTESTATION_EXPIRY_BLOCK_NUMBER < now {
-                                expired_geodes.push(key);
+                // slash the funds once the appeal window is over
+                Self::process_queue(
+                    ExpiryQueue::Slashes,
+                    now.checked_sub(params.appeal_window + 1),
+                    &mut remaining,
+                    |block| <SlashQueue<T>>::take(block),
+                    |block, rest| <SlashQueue<T>>::insert(block, rest),
+                    |key| {
+                        let slash = <PendingSlashes<T>>::get(key);
+                        <T as Config>::WeightInfo::apply_slash(
+                            (slash.attestors.len() + slash.reporters.len()) as u32,
+                        )
+                    },
+                    |block, key| {
+                        if <PendingSlashes<T>>::contains_key(&key) {
+                            let slash = <PendingSlashes<T>>::get(&key);
+                            if slash.start == block && slash.status == SlashStatus::Pending {
+                                <PendingSlashes<T>>::remove(&key);
+                                Self::apply_slash(&key, slash);
                             }
-                        })
-                        .all(|_| true);
-                }
-
-                // clean expired unknown geode
-                pallet_geode::UnknownGeodes::<T>::iter()
-                    .map(|(key, start)| {
-                        if start + UNKNOWN_EXPIRY_BLOCK_NUMBER < now {
-                            expired_geodes.push(key);
                         }
-                    })
-                    .all(|_| true);
-
-                for key in expired_geodes {
-                    <pallet_geode::Pallet<T>>::detach_geode(
-                        pallet_geode::DetachOption::Remove,
-                        key,
-                        None,
-                    )
-                    .map_err(|e| {
-                        debug(&e);
-                    })
-                    .ok();
-                }
+                    },
+                );
 
-                // detach DegradedInstantiated geodes
-                if !<DegradeMode<T>>::get() {
-                    let mut expired_degraded_geodes = Vec::<T::AccountId>::new();
-                    pallet_geode::DegradedInstantiatedGeodes::<T>::iter()
-                        .map(|(key, start)| {
-                            if start + DEGRADED_INSTANTIATED_EXPIRY_BLOCK_NUMBER < now {
-                                expired_degraded_geodes.push(key);
-                            }
-                        })
-                        .all(|_| true);
-
-                    for key in expired_degraded_geodes {
-                        <pallet_geode::Pallet<T>>::detach_geode(
-                            pallet_geode::DetachOption::Unknown,
-                            key,
-                            None,
+                // confirm the appealed slashes governance didn't decide in time
+                Self::process_queue(
+                    ExpiryQueue::Appeals,
+                    now.checked_sub(params.appeal_window + params.appeal_deadline + 1),
+                    &mut remaining,
+                    |block| <AppealQueue<T>>::take(block),
+                    |block, rest| <AppealQueue<T>>::insert(block, rest),
+                    |key| {
+                        let slash = <PendingSlashes<T>>::get(key);
+                        <T as Config>::WeightInfo::apply_slash(
+                            (slash.attestors.len() + slash.reporters.len()) as u32,
                         )
-                        .map_err(|e| {
-                            debug(&e);
-                        })
-                        .ok();
-                    }
-                }
+                    },
+                    |block, key| {
+                        if <PendingSlashes<T>>::contains_key(&key) {
+                            let slash = <PendingSlashes<T>>::get(&key);
+                            if slash.start == block && slash.status == SlashStatus::Appealed {
+                                <PendingSlashes<T>>::remove(&key);
+                                Self::apply_slash(&key, slash);
+                                Self::deposit_event(Event::SlashConfirmed(key));
+                            }
+                        }
+                    },
+                );
+
+                // clean expired geodes, Registered and DegradedInstantiated geodes don't expire
+                // in degrade mode
+                let renew_before = if <DegradeMode<T>>::get() {
+                    BlockNumber::max_value()
+                } else {
+                    <DegradeModeEnd<T>>::get()
+                };
+                Self::expire_geodes(
+                    ExpiryQueue::RegisteredGeodes,
+                    pallet_geode::GeodeState::Registered,
+                    now.checked_sub(params.attestation_expiry + 1),
+                    pallet_geode::DetachOption::Remove,
+                    renew_before,
+                    &mut remaining,
+                );
+                Self::expire_geodes(
+                    ExpiryQueue::UnknownGeodes,
+                    pallet_geode::GeodeState::Unknown,
+                    now.checked_sub(params.unknown_expiry + 1),
+                    pallet_geode::DetachOption::Remove,
+                    0,
+                    &mut remaining,
+                );
+                Self::expire_geodes(
+                    ExpiryQueue::DegradedInstantiatedGeodes,
+                    pallet_geode::GeodeState::DegradedInstantiated,
+                    now.checked_sub(params.degraded_instantiated_expiry + 1),
+                    pallet_geode::DetachOption::Unknown,
+                    renew_before,
+                    &mut remaining,
+                );
 
                 // clean expired attestors
-                let mut expired_attestors = Vec::<T::AccountId>::new();
-                pallet_attestor::AttestorLastNotify::<T>::iter()
-                    .map(|(key, notify)| {
-                        if notify + ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER < now {
-                            expired_attestors.push(key);
+                Self::process_queue(
+                    ExpiryQueue::Attestors,
+                    now.checked_sub(params.attestor_notify_timeout + 1),
+                    &mut remaining,
+                    |block| pallet_attestor::NotifyQueue::<T>::take(block),
+                    |block, rest| pallet_attestor::NotifyQueue::<T>::insert(block, rest),
+                    |key| <T as Config>::WeightInfo::remove_attestor(Self::attestor_load(key)),
+                    |block, key| {
+                        if pallet_attestor::Attestors::<T>::contains_key(&key)
+                            && pallet_attestor::AttestorLastNotify::<T>::get(&key) == block
+                        {
+                            Self::slash_attestor(&key, AttestorOffence::MissedHeartbeat);
+                            Self::do_attestor_exit(&key);
                         }
-                    })
-                    .all(|_| true);
+                    },
+                );
+
+                // remove the attestors done unbonding, once their geodes had time to be handed
+                // over
+                Self::process_queue(
+                    ExpiryQueue::Unbonding,
+                    now.checked_sub(params.unbonding_period + 1),
+                    &mut remaining,
+                    |block| <UnbondingQueue<T>>::take(block),
+                    |block, rest| <UnbondingQueue<T>>::insert(block, rest),
+                    |key| <T as Config>::WeightInfo::remove_attestor(Self::attestor_load(key)),
+                    |block, key| {
+                        if <Unbonding<T>>::get(&key) == Some(block) {
+                            Self::do_attestor_exit(&key);
+                        }
+                    },
+                );
 
-                for key in expired_attestors {
-                    Self::do_attestor_exit(&key);
+                // check the geodes against the attestor targets which changed
+                if let Some(last_key) = <TargetSweep<T>>::get() {
+                    Self::sweep_targets(last_key, &mut remaining);
                 }
+
+                // degrade the geodes running revoked enclave builds
+                Self::sweep_revocations(&mut remaining);
+
+                // assign attestors to the geodes queued before this block, with the reserved
+                // weight and whatever the other queues left. The randomness has to be revealed
+                // after a geode got queued, otherwise its provider could pick when to register
+                // it, so the geodes queued since wait for the next one.
+                assignment_weight = assignment_weight.saturating_add(remaining);
+                remaining = 0;
+                let (_, known_since) = T::Randomness::random(ASSIGNMENT_SUBJECT);
+                let known_since = known_since.saturated_into::<BlockNumber>();
+                Self::process_queue(
+                    ExpiryQueue::Assignments,
+                    now.checked_sub(1),
+                    &mut assignment_weight,
+                    |block| <AssignmentQueue<T>>::take(block),
+                    |block, rest| <AssignmentQueue<T>>::insert(block, rest),
+                    |_| {
+                        <T as Config>::WeightInfo::assign_attestors(
+                            pallet_attestor::AttestorNum::<T>::get(),
+                        )
+                    },
+                    |block, geode| {
+                        if block < known_since {
+                            Self::assign_attestors(&geode, now);
+                        } else {
+                            <AssignmentQueue<T>>::append(
+                                now.saturating_add(params.assignment_retry),
+                                geode,
+                            );
+                        }
+                    },
+                );
+            }
+            limit.saturating_sub(remaining.saturating_add(assignment_weight))
+        }
+
+        /// Queue the geodes and attestors of a chain which ran before the expiry queues
+        /// existed, so that their timeouts are enforced as well.
+        fn on_runtime_upgrade() -> Weight {
+            if <ExpiryCursors<T>>::contains_key(ExpiryQueue::RegisteredGeodes) {
+                return T::DbWeight::get().reads(1);
             }
-            0
+            Self::enqueue_existing()
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::MaxHookWeight::get() <= T::BlockWeights::get().max_block,
+                "MaxHookWeight has to fit in a block"
+            );
+            let hook_weight =
+                T::MaxHookWeight::get().saturating_sub(<T as Config>::WeightInfo::on_initialize());
+            assert!(
+                hook_weight.saturating_sub(ASSIGNMENT_WEIGHT_SHARE * hook_weight)
+                    >= <T as Config>::WeightInfo::remove_attestor(
+                        pallet_attestor::MAX_ATTESTOR_GEODES
+                    ),
+                "MaxHookWeight has to cover the removal of an attestor"
+            );
         }
     }
 
@@ -254,13 +1034,15 @@ pub mod pallet {
     // Dispatchable functions must be annotated with a weight and must return a DispatchResult.
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Report that somebody did a misconduct. The actual usage is being considered.
-        #[pallet::weight(0)]
+        /// Called by attestor to report a misconduct of a geode it attests. The evidence has to
+        /// be signed by the attestor, carry the enclave's signed answer if any, and show the
+        /// geode failing, before it counts toward the approval ratio.
+        #[pallet::weight(<T as Config>::WeightInfo::report_misconduct())]
         pub fn report_misconduct(
             origin: OriginFor<T>,
             geode_id: T::AccountId,
             report_type: u8,
-            _proof: Vec<u8>,
+            evidence: Evidence,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             // check attestor existance and whether attested
@@ -275,12 +1057,13 @@ pub mod pallet {
                 Error::<T>::NotAttestingFor
             );
             // check have report
-            match ReportType::try_from(report_type) {
-                Ok(_) => {}
+            let typ = match ReportType::try_from(report_type) {
+                Ok(typ) => typ,
                 Err(_) => {
                     return Err(Error::<T>::InvalidReportType.into());
                 }
             };
+            Self::verify_evidence(&who, &geode_id, &typ, &evidence)?;
             let key = (geode_id.clone(), report_type);
             let mut report = ReportOf::<T>::default();
             if <Reports<T>>::contains_key(&key) {
@@ -290,16 +1073,13 @@ pub mod pallet {
                 report.attestors.insert(who.clone());
                 let block_number = <frame_system::Pallet<T>>::block_number();
                 report.start = block_number.saturated_into::<BlockNumber>();
+                <ReportQueue<T>>::append(report.start, &key);
             }
 
-            // check current amount of misconduct satisfying the approval ratio
-            if Percent::from_rational_approximation(
-                report.attestors.len(),
-                pallet_attestor::GeodeAttestors::<T>::get(&geode_id).len(),
-            ) >= REPORT_APPROVAL_RATIO
-            {
+            // check the stake reporting the misconduct satisfies the quorum and approval ratio
+            if Self::report_approved(&geode_id, &report) {
                 // slash the geode
-                Self::slash_geode(&key.0);
+                Self::slash_geode(&key.0, typ, &report, evidence.transcript.request);
                 <Reports<T>>::remove(&key);
                 Self::deposit_event(Event::SlashGeode(key.0.clone()));
             } else {
@@ -311,11 +1091,13 @@ pub mod pallet {
             Ok(().into())
         }
 
-        /// Called by attestor to attest Geode.
-        #[pallet::weight(0)]
+        /// Called by an attestor assigned to the geode to attest it, with the attestation
+        /// report of the geode's enclave.
+        #[pallet::weight(<T as Config>::WeightInfo::attestor_attest_geode())]
         pub fn attestor_attest_geode(
             origin: OriginFor<T>,
             geode: T::AccountId,
+            report: AttestationReport,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             // check attestor existance and whether atteseted
@@ -323,6 +1105,10 @@ pub mod pallet {
                 pallet_attestor::Attestors::<T>::contains_key(&who),
                 pallet_attestor::Error::<T>::InvalidAttestor
             );
+            ensure!(
+                !<Unbonding<T>>::contains_key(&who),
+                Error::<T>::AttestorUnbonding
+            );
             let mut attestor = pallet_attestor::Attestors::<T>::get(&who);
             ensure!(
                 !attestor.geodes.contains(&geode),
@@ -334,12 +1120,23 @@ pub mod pallet {
                 pallet_geode::Geodes::<T>::contains_key(&geode),
                 pallet_geode::Error::<T>::InvalidGeode
             );
-            let mut geode_record = pallet_geode::Geodes::<T>::get(&geode);
+            let geode_record = pallet_geode::Geodes::<T>::get(&geode);
             ensure!(
                 geode_record.state != pallet_geode::GeodeState::Unknown
                     && geode_record.state != pallet_geode::GeodeState::Offline,
                 pallet_geode::Error::<T>::InvalidGeodeState
             );
+            ensure!(
+                <GeodeAssignments<T>>::get(&geode).contains(&who),
+                Error::<T>::NotAssigned
+            );
+            ensure!(
+                pallet_attestor::Pallet::<T>::has_capacity(&who, attestor.geodes.len()),
+                pallet_attestor::Error::<T>::AttestorAtCapacity
+            );
+            let identity = Self::verify_report(&geode, &report)?;
+            Self::unassign_attestor(&geode, &who);
+            Self::record_measurement(&geode, identity.measurement());
 
             // update pallet_attestor::Attestors
             attestor.geodes.insert(geode.clone());
@@ -353,99 +1150,666 @@ pub mod pallet {
             attestors.insert(who.clone());
             pallet_attestor::GeodeAttestors::<T>::insert(&geode, &attestors);
 
-            // first attestor attesting this geode
-            if geode_record.state == pallet_geode::GeodeState::Registered
-                && attestors.len() as u32 >= <MinAttestorNum<T>>::get()
-            {
-                // update pallet_geode::Geodes
-                geode_record.state = pallet_geode::GeodeState::Attested;
-                pallet_geode::Geodes::<T>::insert(&geode, geode_record);
-
-                // remove from pallet_geode::RegisteredGeodes
-                pallet_geode::RegisteredGeodes::<T>::remove(&geode);
-
-                // move into pallet_geode::AttestedGeodes
-                let block_number = <frame_system::Pallet<T>>::block_number();
-                pallet_geode::AttestedGeodes::<T>::insert(
+            // enough attestors attesting this geode
+            if !Self::check_attestors(&geode, pallet_geode::TransitionCause::Attestation) {
+                // when the state doesn't change, still update GeodeUpdateCounters for the
+                // new attestor, transit updates it otherwise
+                pallet_geode::GeodeUpdateCounters::<T>::insert(
                     &geode,
-                    block_number.saturated_into::<BlockNumber>(),
+                    pallet_geode::GeodeUpdateCounters::<T>::get(&geode) + 1,
                 );
             }
 
-            pallet_geode::GeodeUpdateCounters::<T>::insert(
-                &geode,
-                pallet_geode::GeodeUpdateCounters::<T>::get(&geode) + 1,
-            );
-
             Self::deposit_event(Event::AttestFor(who, geode));
             Ok(().into())
         }
 
-        /// Remove attestors while unlink the related geodes.
-        #[pallet::weight(0)]
+        /// Start unbonding the attestor. It stops taking new geodes and its geodes get other
+        /// attestors assigned, while it keeps attesting them until the unbonding period ends.
+        /// The attestor is removed and its stake released then.
+        #[pallet::weight(
+            <T as Config>::WeightInfo::attestor_exit(pallet_attestor::MAX_ATTESTOR_GEODES)
+        )]
         pub fn attestor_exit(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             ensure!(
                 pallet_attestor::Attestors::<T>::contains_key(&who),
                 pallet_attestor::Error::<T>::InvalidAttestor
             );
-            Self::do_attestor_exit(&who);
-            Ok(().into())
+            ensure!(
+                !<Unbonding<T>>::contains_key(&who),
+                Error::<T>::AttestorUnbonding
+            );
+            let geode_num = Self::start_unbonding(&who);
+            Ok(Some(<T as Config>::WeightInfo::attestor_exit(geode_num)).into())
         }
 
-        /// Called by root to set the min stake
-        #[pallet::weight(0)]
+        /// Called by root to set the number of attestors every geode needs at least. The
+        /// geodes are checked against it in the next blocks.
+        #[pallet::weight(<T as Config>::WeightInfo::set_min_attestor_num())]
         pub fn set_min_attestor_num(origin: OriginFor<T>, num: u32) -> DispatchResultWithPostInfo {
             let _who = ensure_root(origin)?;
             <MinAttestorNum<T>>::put(num);
-            // TODO: cause geode state change immediately
+            Self::start_target_sweep();
             Ok(().into())
         }
 
-        /// Called by root to clean all the storage
-        #[pallet::weight(0)]
-        pub fn clean_all_storage(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
-            let _who = ensure_root(origin)?;
-            Self::clean_storage();
-            Self::deposit_event(Event::StorageCleaned);
+        /// Called by governance to set the number of attestors the geodes matching each
+        /// selector need, e.g. more attestors for geodes with a better TCB level. The geodes
+        /// are checked against them in the next blocks.
+        #[pallet::weight(<T as Config>::WeightInfo::set_attestor_target_classes(classes.len() as u32))]
+        pub fn set_attestor_target_classes(
+            origin: OriginFor<T>,
+            classes: Vec<(pallet_geode::Selector, u32)>,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(
+                classes.len() as u32 <= MAX_TARGET_CLASSES,
+                Error::<T>::TooManyTargetClasses
+            );
+            <AttestorTargetClasses<T>>::put(&classes);
+            Self::start_target_sweep();
+            Self::deposit_event(Event::AttestorTargetClassesUpdated(classes));
             Ok(().into())
         }
-    }
 
-    impl<T: Config> Pallet<T> {
-        /// Slash geode including update storage and penalty related logics
-        fn slash_geode(key: &T::AccountId) {
-            <pallet_geode::Pallet<T>>::detach_geode(
-                pallet_geode::DetachOption::Unknown,
-                key.to_owned(),
-                None,
-            )
-            .map_err(|e| {
-                debug(&e);
-            })
-            .ok();
+        /// Called by provider to require more attestors for its geode than its class does,
+        /// zero falls back to the class target.
+        #[pallet::weight(<T as Config>::WeightInfo::set_geode_attestor_target())]
+        pub fn set_geode_attestor_target(
+            origin: OriginFor<T>,
+            geode: T::AccountId,
+            target: u32,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                pallet_geode::Geodes::<T>::contains_key(&geode),
+                pallet_geode::Error::<T>::InvalidGeode
+            );
+            ensure!(
+                pallet_geode::Geodes::<T>::get(&geode).provider == who,
+                pallet_geode::Error::<T>::NoRight
+            );
+            if target.is_zero() {
+                <GeodeAttestorTargets<T>>::remove(&geode);
+            } else {
+                <GeodeAttestorTargets<T>>::insert(&geode, target);
+            }
+            Self::check_attestors(&geode, pallet_geode::TransitionCause::AttestorTarget);
+            Self::deposit_event(Event::GeodeAttestorTargetSet(geode, target));
+            Ok(().into())
+        }
 
-            // TODO... Service related logic
-            // TODO... Penalty related logic
+        /// Called by governance to set the liveness timeouts and report approval ratio. They
+        /// apply to the items already waiting as well.
+        #[pallet::weight(<T as Config>::WeightInfo::set_liveness_params())]
+        pub fn set_liveness_params(
+            origin: OriginFor<T>,
+            params: LivenessParams,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(params.is_valid(), Error::<T>::InvalidLivenessParams);
+            <LivenessParameters<T>>::put(&params);
+            Self::deposit_event(Event::LivenessParamsUpdated(params));
+            Ok(().into())
         }
 
-        /// Remove attestors while unlink the related geodes.
-        pub fn do_attestor_exit(key: &T::AccountId) {
-            let related_geodes = <pallet_attestor::Module<T>>::attestor_remove(key.to_owned());
+        /// Called by governance to set the root certificate attestation reports have to chain
+        /// up to.
+        #[pallet::weight(<T as Config>::WeightInfo::set_ra_root_cert(cert.len() as u32))]
+        pub fn set_ra_root_cert(origin: OriginFor<T>, cert: Vec<u8>) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            <RaRootCert<T>>::put(cert);
+            Self::deposit_event(Event::RaRootCertUpdated);
+            Ok(().into())
+        }
 
-            for geode in related_geodes.iter() {
-                let mut attestors = pallet_attestor::GeodeAttestors::<T>::get(&geode);
-                attestors.remove(&key);
+        /// Called by governance to set which quote statuses and enclave modes geodes are
+        /// attested with, and how fresh IAS reports have to be.
+        #[pallet::weight(<T as Config>::WeightInfo::set_ra_params())]
+        pub fn set_ra_params(origin: OriginFor<T>, params: RaParams) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(params.is_valid(), Error::<T>::InvalidRaParams);
+            <RaParameters<T>>::put(&params);
+            Self::deposit_event(Event::RaParamsUpdated(params));
+            Ok(().into())
+        }
 
-                if attestors.is_empty() {
-                    pallet_attestor::GeodeAttestors::<T>::remove(&geode);
-                } else {
-                    pallet_attestor::GeodeAttestors::<T>::insert(&geode, &attestors);
-                }
+        /// Called by governance to set how much misbehaving geodes are slashed and how the
+        /// slashed funds are paid out.
+        #[pallet::weight(<T as Config>::WeightInfo::set_slash_params())]
+        pub fn set_slash_params(
+            origin: OriginFor<T>,
+            params: SlashParams,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(params.is_valid(), Error::<T>::InvalidSlashParams);
+            <SlashParameters<T>>::put(&params);
+            Self::deposit_event(Event::SlashParamsUpdated(params));
+            Ok(().into())
+        }
 
-                if <MinAttestorNum<T>>::get() > attestors.len() as u32 {
-                    <pallet_geode::Pallet<T>>::degrade_geode(geode);
-                } else {
+        /// Called by governance to overturn a pending misconduct report, slashing the
+        /// attestors which reported it.
+        #[pallet::weight(<T as Config>::WeightInfo::overturn_report(MAX_REPORTERS))]
+        pub fn overturn_report(
+            origin: OriginFor<T>,
+            geode_id: T::AccountId,
+            report_type: u8,
+        ) -> DispatchResultWithPostInfo {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            let key = (geode_id, report_type);
+            ensure!(<Reports<T>>::contains_key(&key), Error::<T>::InvalidReport);
+            let report = <Reports<T>>::take(&key);
+            for attestor in report.attestors.iter() {
+                Self::slash_attestor(attestor, AttestorOffence::FalseReport);
+            }
+            Self::deposit_event(Event::ReportOverturned(key.0, key.1));
+            Ok(Some(<T as Config>::WeightInfo::overturn_report(
+                report.attestors.len() as u32,
+            ))
+            .into())
+        }
+
+        /// Called by the provider of a slashed geode within the appeal window, with a
+        /// transcript of the geode's enclave answering the reported request. The funds are
+        /// then only slashed if governance confirms.
+        #[pallet::weight(<T as Config>::WeightInfo::appeal_slash())]
+        pub fn appeal_slash(
+            origin: OriginFor<T>,
+            geode_id: T::AccountId,
+            counter_evidence: CounterEvidence,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <PendingSlashes<T>>::contains_key(&geode_id),
+                Error::<T>::NoPendingSlash
+            );
+            let mut slash = <PendingSlashes<T>>::get(&geode_id);
+            ensure!(slash.provider == who, Error::<T>::NotProvider);
+            let params = <LivenessParameters<T>>::get();
+            let now = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            ensure!(
+                slash.status == SlashStatus::Pending
+                    && now <= slash.start.saturating_add(params.appeal_window),
+                Error::<T>::AppealClosed
+            );
+
+            let decode = |key: &[u8]| T::AccountId::decode(&mut &key[..]).ok();
+            ensure!(
+                decode(counter_evidence.geode_key.as_ref()).as_ref() == Some(&geode_id)
+                    && counter_evidence
+                        .shows_liveness(&slash.request, slash.report_type == ReportType::Challenge),
+                Error::<T>::InvalidEvidence
+            );
+
+            slash.status = SlashStatus::Appealed;
+            slash.counter_evidence = Some(counter_evidence);
+            <AppealQueue<T>>::append(slash.start, &geode_id);
+            <PendingSlashes<T>>::insert(&geode_id, slash);
+            Self::deposit_event(Event::SlashAppealed(geode_id));
+            Ok(().into())
+        }
+
+        /// Called by governance to decide an appealed slash before the appeal deadline. A
+        /// confirmed slash moves the funds, a reversed one brings the geode back to Registered
+        /// and slashes its reporters.
+        #[pallet::weight(<T as Config>::WeightInfo::resolve_appeal(MAX_REPORTERS))]
+        pub fn resolve_appeal(
+            origin: OriginFor<T>,
+            geode_id: T::AccountId,
+            confirm: bool,
+        ) -> DispatchResultWithPostInfo {
+            T::AppealOrigin::ensure_origin(origin)?;
+            ensure!(
+                <PendingSlashes<T>>::contains_key(&geode_id),
+                Error::<T>::NoPendingSlash
+            );
+            let slash = <PendingSlashes<T>>::get(&geode_id);
+            ensure!(
+                slash.status == SlashStatus::Appealed,
+                Error::<T>::NotAppealed
+            );
+            let params = <LivenessParameters<T>>::get();
+            let now = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            ensure!(
+                now <= slash
+                    .start
+                    .saturating_add(params.appeal_window)
+                    .saturating_add(params.appeal_deadline),
+                Error::<T>::AppealDeadlinePassed
+            );
+            <PendingSlashes<T>>::remove(&geode_id);
+            let attestor_num = (slash.reporters.len() + slash.attestors.len()) as u32;
+
+            if confirm {
+                Self::apply_slash(&geode_id, slash);
+                Self::deposit_event(Event::SlashConfirmed(geode_id));
+            } else {
+                for reporter in slash.reporters.iter() {
+                    Self::slash_attestor(reporter, AttestorOffence::FalseReport);
+                }
+                if <pallet_geode::Pallet<T>>::geode_state(geode_id.clone())
+                    == Some(pallet_geode::GeodeState::Unknown)
+                {
+                    <pallet_geode::Pallet<T>>::transit(
+                        &geode_id,
+                        pallet_geode::GeodeState::Registered,
+                        pallet_geode::TransitionCause::Appeal,
+                    )?;
+                    Self::queue_assignment(&geode_id);
+                }
+                Self::deposit_event(Event::SlashReversed(geode_id));
+            }
+            Ok(Some(<T as Config>::WeightInfo::resolve_appeal(attestor_num)).into())
+        }
+
+        /// Called by root to clean all the storage
+        #[pallet::weight(T::BlockWeights::get().max_block)]
+        pub fn clean_all_storage(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+            let _who = ensure_root(origin)?;
+            Self::clean_storage();
+            Self::deposit_event(Event::StorageCleaned);
+            Ok(().into())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Slash geode including update storage and penalty related logics. The geode turns
+        /// Unknown at once, the funds are slashed by `apply_slash` once the appeal window ends
+        /// or governance confirms the slash. A slash still pending for the geode takes the new
+        /// reporters and keeps its window and request.
+        fn slash_geode(
+            key: &T::AccountId,
+            report_type: ReportType,
+            report: &ReportOf<T>,
+            request: Vec<u8>,
+        ) {
+            // read what the slash needs before the geode gets detached
+            let geode = pallet_geode::Geodes::<T>::get(key);
+            let attestors = pallet_attestor::GeodeAttestors::<T>::get(key);
+            let reporters = report
+                .attestors
+                .intersection(&attestors)
+                .cloned()
+                .collect::<BTreeSet<_>>();
+
+            <pallet_geode::Pallet<T>>::detach_geode(
+                pallet_geode::DetachOption::Unknown,
+                key.to_owned(),
+                None,
+                pallet_geode::TransitionCause::Misconduct,
+            )
+            .map_err(|e| {
+                debug(&e);
+            })
+            .ok();
+            Self::unassign_geode(key);
+
+            if <PendingSlashes<T>>::contains_key(key) {
+                <PendingSlashes<T>>::mutate(key, |slash| {
+                    slash.reporters.extend(reporters);
+                    slash.attestors = slash
+                        .attestors
+                        .union(&attestors)
+                        .filter(|attestor| !slash.reporters.contains(attestor))
+                        .cloned()
+                        .collect();
+                });
+                return;
+            }
+
+            let start = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            let slash = PendingSlashOf::<T> {
+                report_type,
+                provider: geode.provider,
+                order: geode.order,
+                attestors: attestors.difference(&reporters).cloned().collect(),
+                reporters,
+                start,
+                status: SlashStatus::Pending,
+                request,
+                counter_evidence: None,
+            };
+            <PendingSlashes<T>>::insert(key, slash);
+            <SlashQueue<T>>::append(start, key);
+        }
+
+        /// Move the funds of a geode's slash. The geode's deposit and a share of its provider's
+        /// stake growing with the provider's offences are slashed, then shared by the reporters
+        /// and the user of the order the geode was serving, the remainder goes to `T::Slash`.
+        /// The attestors which didn't report the geode are slashed as well.
+        fn apply_slash(key: &T::AccountId, slash: PendingSlashOf<T>) {
+            // attestors vouching for the geode until the end share the blame
+            for attestor in slash.attestors.iter() {
+                Self::slash_attestor(attestor, AttestorOffence::BadAttestation);
+            }
+
+            let params = <SlashParameters<T>>::get();
+            let offences = <ProviderOffences<T>>::mutate(&slash.provider, |offences| {
+                *offences = offences.saturating_add(1);
+                *offences
+            });
+            let stake = pallet_geode::Providers::<T>::get(&slash.provider).stake;
+            let mut slashed = <pallet_geode::Pallet<T>>::slash_deposit(key);
+            slashed.subsume(<pallet_geode::Pallet<T>>::slash_stake(
+                &slash.provider,
+                params.slash_ratio(&slash.report_type, offences) * stake,
+            ));
+            let total = slashed.peek();
+
+            // share the reward among the reporters, the dust is left in the remainder
+            let (mut reward, mut remainder) = slashed.split(params.reporters_reward * total);
+            let mut rewarded = BalanceOf::<T>::zero();
+            if !slash.reporters.is_empty() {
+                let share = reward.peek() / (slash.reporters.len() as u32).into();
+                for reporter in slash.reporters.iter() {
+                    let (part, rest) = reward.split(share);
+                    reward = rest;
+                    rewarded += part.peek();
+                    <T as pallet_geode::Config>::Currency::resolve_creating(reporter, part);
+                }
+            }
+            remainder.subsume(reward);
+
+            let mut compensated = BalanceOf::<T>::zero();
+            if let Some(holder) = slash
+                .order
+                .as_ref()
+                .and_then(<T::OrderHolder as pallet_geode::OrderHolder<_, _>>::holder)
+            {
+                let (compensation, rest) = remainder.split(params.holder_compensation * total);
+                remainder = rest;
+                compensated = compensation.peek();
+                <T as pallet_geode::Config>::Currency::resolve_creating(&holder, compensation);
+            }
+
+            Self::deposit_event(Event::SlashPayout(
+                key.clone(),
+                total,
+                rewarded,
+                compensated,
+                remainder.peek(),
+            ));
+            T::Slash::on_unbalanced(remainder);
+        }
+
+        /// Slash the attestor's stake by the ratio set for the offence, the slashed funds go to
+        /// `T::AttestorSlash`.
+        pub fn slash_attestor(attestor: &T::AccountId, offence: AttestorOffence) {
+            let ratio = <SlashParameters<T>>::get().attestor_slash_ratio(&offence);
+            let stake = pallet_attestor::AttestorStake::<T>::get(attestor);
+            let slashed = <pallet_attestor::Pallet<T>>::slash_stake(attestor, ratio * stake);
+            Self::deposit_event(Event::SlashAttestor(
+                attestor.clone(),
+                offence,
+                slashed.peek(),
+            ));
+            T::AttestorSlash::on_unbalanced(slashed);
+        }
+
+        /// Whether the attestors of the geode which reported it reach the quorum and hold
+        /// enough of their stake. Votes are counted by head if the attestors stake nothing.
+        fn report_approved(geode: &T::AccountId, report: &ReportOf<T>) -> bool {
+            let params = <LivenessParameters<T>>::get();
+            let attestors = pallet_attestor::GeodeAttestors::<T>::get(geode);
+            let reporters = report
+                .attestors
+                .intersection(&attestors)
+                .collect::<Vec<_>>();
+            if reporters.len() < (params.report_quorum as usize).min(attestors.len()) {
+                return false;
+            }
+
+            let stake_of = |attestor: &T::AccountId| {
+                pallet_attestor::Pallet::<T>::attestor_stake(attestor).saturated_into::<u128>()
+            };
+            let total_stake = attestors.iter().fold(0u128, |acc, attestor| {
+                acc.saturating_add(stake_of(attestor))
+            });
+            let approval = if total_stake.is_zero() {
+                Percent::from_rational_approximation(reporters.len(), attestors.len())
+            } else {
+                let reported_stake = reporters.iter().fold(0u128, |acc, attestor| {
+                    acc.saturating_add(stake_of(attestor))
+                });
+                Percent::from_rational_approximation(reported_stake, total_stake)
+            };
+            approval >= params.report_approval_ratio
+        }
+
+        /// Check the evidence is about the geode, signed by the attestor for this chain and
+        /// with its account as the reporter, signed by the enclave, fresh, and shows a failure
+        /// of the geode.
+        fn verify_evidence(
+            attestor: &T::AccountId,
+            geode: &T::AccountId,
+            report_type: &ReportType,
+            evidence: &Evidence,
+        ) -> Result<(), Error<T>> {
+            let decode = |key: &[u8]| T::AccountId::decode(&mut &key[..]).ok();
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            ensure!(
+                decode(evidence.geode_key.as_ref()).as_ref() == Some(geode)
+                    && (decode(evidence.attestor_key.as_ref()).as_ref() == Some(attestor)
+                        || <pallet_attestor::Pallet<T>>::key_valid_at(
+                            attestor,
+                            evidence.attestor_key.as_ref(),
+                            block_number
+                        )),
+                Error::<T>::InvalidEvidence
+            );
+            let is_challenge = *report_type == ReportType::Challenge;
+            let genesis_hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+            ensure!(
+                evidence.verify_signatures(&genesis_hash, attestor, report_type.clone() as u8),
+                Error::<T>::InvalidEvidence
+            );
+
+            let params = <LivenessParameters<T>>::get();
+            let now = T::UnixTime::now().as_millis().saturated_into::<u64>();
+            ensure!(
+                evidence.transcript.is_fresh(now, params.evidence_max_age),
+                Error::<T>::InvalidEvidence
+            );
+            ensure!(
+                evidence
+                    .transcript
+                    .shows_failure(is_challenge, params.response_timeout),
+                Error::<T>::NoMisconduct
+            );
+            Ok(())
+        }
+
+        /// Visit the queue from its cursor up to the `last` block and call `expire` on each
+        /// item. At most `MaxExpiriesPerBlock` blocks and items are visited, and only the items
+        /// whose weight given by `weigh` fits in the `remaining` weight of the hook. The items
+        /// left are put back and carried over to the next block.
+        #[allow(clippy::too_many_arguments)]
+        fn process_queue<K>(
+            queue: ExpiryQueue,
+            last: Option<BlockNumber>,
+            remaining: &mut Weight,
+            take: impl Fn(BlockNumber) -> Vec<K>,
+            put: impl Fn(BlockNumber, Vec<K>),
+            weigh: impl Fn(&K) -> Weight,
+            mut expire: impl FnMut(BlockNumber, K),
+        ) {
+            let last = match last {
+                Some(last) => last,
+                None => return,
+            };
+            let block_weight = T::DbWeight::get().reads_writes(1, 1);
+            let mut budget = T::MaxExpiriesPerBlock::get();
+            let mut cursor = <ExpiryCursors<T>>::get(queue);
+            while cursor <= last && budget > 0 && *remaining >= block_weight {
+                *remaining -= block_weight;
+                let mut items = take(cursor).into_iter();
+                let mut rest = Vec::new();
+                if items.len() == 0 {
+                    budget -= 1;
+                }
+                while let Some(item) = items.next() {
+                    let weight = weigh(&item);
+                    if budget == 0 || weight > *remaining {
+                        rest.push(item);
+                        rest.extend(items.by_ref());
+                        break;
+                    }
+                    budget -= 1;
+                    *remaining -= weight;
+                    expire(cursor, item);
+                }
+                if !rest.is_empty() {
+                    put(cursor, rest);
+                    break;
+                }
+                cursor += 1;
+            }
+            <ExpiryCursors<T>>::insert(queue, cursor);
+        }
+
+        /// Detach the geodes which entered `state` at or before the `last` block with `option`.
+        /// The time in the state of the geodes queued before the `renew_before` block restarts
+        /// instead, as they couldn't get attested while liveness was degraded, and so does the
+        /// time of the geodes with a slash pending.
+        fn expire_geodes(
+            queue: ExpiryQueue,
+            state: pallet_geode::GeodeState,
+            last: Option<BlockNumber>,
+            option: pallet_geode::DetachOption,
+            renew_before: BlockNumber,
+            remaining: &mut Weight,
+        ) {
+            Self::process_queue(
+                queue,
+                last,
+                remaining,
+                |block| pallet_geode::StateQueues::<T>::take(&state, block),
+                |block, rest| pallet_geode::StateQueues::<T>::insert(&state, block, rest),
+                |geode| {
+                    <T as Config>::WeightInfo::expire_geode(
+                        pallet_attestor::GeodeAttestors::<T>::get(geode).len() as u32,
+                    )
+                },
+                |block, geode| {
+                    if <pallet_geode::Pallet<T>>::state_since(&geode, &state) != Some(block) {
+                        return;
+                    }
+                    // the geode and its deposit stay until its slash is over
+                    if block < renew_before || <PendingSlashes<T>>::contains_key(&geode) {
+                        <pallet_geode::Pallet<T>>::renew_state(&geode);
+                        return;
+                    }
+                    <pallet_geode::Pallet<T>>::detach_geode(
+                        option.clone(),
+                        geode.clone(),
+                        None,
+                        pallet_geode::TransitionCause::LivenessExpiry,
+                    )
+                    .map_err(|e| {
+                        debug(&e);
+                    })
+                    .ok();
+                    Self::unassign_geode(&geode);
+                    <GeodeHealth<T>>::remove(&geode);
+                    Self::forget_measurement(&geode);
+                },
+            )
+        }
+
+        /// Queue the timed geodes and the attestors by the block they started, and start each
+        /// queue at its oldest item so that none of them is skipped.
+        fn enqueue_existing() -> Weight {
+            let now = <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            let mut reads: Weight = 1;
+            let mut writes: Weight = 0;
+
+            for (queue, state) in [
+                (
+                    ExpiryQueue::RegisteredGeodes,
+                    pallet_geode::GeodeState::Registered,
+                ),
+                (
+                    ExpiryQueue::UnknownGeodes,
+                    pallet_geode::GeodeState::Unknown,
+                ),
+                (
+                    ExpiryQueue::DegradedInstantiatedGeodes,
+                    pallet_geode::GeodeState::DegradedInstantiated,
+                ),
+            ]
+            .iter()
+            {
+                let geodes: Vec<(T::AccountId, BlockNumber)> = match state {
+                    pallet_geode::GeodeState::Registered => {
+                        pallet_geode::RegisteredGeodes::<T>::iter().collect()
+                    }
+                    pallet_geode::GeodeState::Unknown => {
+                        pallet_geode::UnknownGeodes::<T>::iter().collect()
+                    }
+                    _ => pallet_geode::DegradedInstantiatedGeodes::<T>::iter().collect(),
+                };
+                let mut cursor = now;
+                for (geode, since) in geodes {
+                    reads += 2;
+                    if !pallet_geode::StateQueues::<T>::get(state, since).contains(&geode) {
+                        pallet_geode::StateQueues::<T>::append(state, since, &geode);
+                        writes += 1;
+                    }
+                    cursor = cursor.min(since);
+                }
+                <ExpiryCursors<T>>::insert(queue, cursor);
+                writes += 1;
+            }
+
+            let attestors: Vec<(T::AccountId, BlockNumber)> =
+                pallet_attestor::AttestorLastNotify::<T>::iter().collect();
+            let mut cursor = now;
+            for (attestor, since) in attestors {
+                reads += 2;
+                if !pallet_attestor::NotifyQueue::<T>::get(since).contains(&attestor) {
+                    pallet_attestor::NotifyQueue::<T>::append(since, &attestor);
+                    writes += 1;
+                }
+                cursor = cursor.min(since);
+            }
+            <ExpiryCursors<T>>::insert(ExpiryQueue::Attestors, cursor);
+            writes += 1;
+
+            // nothing was queued in the other queues before they existed
+            for queue in ExpiryQueue::ALL.iter() {
+                if !<ExpiryCursors<T>>::contains_key(queue) {
+                    <ExpiryCursors<T>>::insert(queue, now);
+                    writes += 1;
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+
+        /// Remove attestors while unlink the related geodes, return the number of geodes
+        /// unlinked. The geodes left short of attestors are degraded, and get other attestors
+        /// assigned like the ones the attestor was assigned to. What is left of the stake once
+        /// slashed is released, whatever the attestor exits for.
+        pub fn do_attestor_exit(key: &T::AccountId) -> u32 {
+            let related_geodes = <pallet_attestor::Module<T>>::attestor_remove(key.to_owned());
+            <pallet_attestor::Pallet<T>>::release_stake(key);
+            <Unbonding<T>>::remove(key);
+            let assigned_geodes = Self::withdraw_assignments(key);
+
+            for geode in related_geodes.iter() {
+                let mut attestors = pallet_attestor::GeodeAttestors::<T>::get(&geode);
+                attestors.remove(&key);
+
+                if attestors.is_empty() {
+                    pallet_attestor::GeodeAttestors::<T>::remove(&geode);
+                } else {
+                    pallet_attestor::GeodeAttestors::<T>::insert(&geode, &attestors);
+                }
+
+                if !Self::check_attestors(geode, pallet_geode::TransitionCause::AttestorRemoval) {
                     // because GeodeUpdateCounters will be updated in degrade_geode
                     pallet_geode::GeodeUpdateCounters::<T>::insert(
                         &geode,
@@ -453,6 +1817,391 @@ pub mod pallet {
                     );
                 }
             }
+            (related_geodes.len() + assigned_geodes.len()) as u32
+        }
+
+        /// Mark the attestor as unbonding, withdraw its assignments and get other attestors
+        /// assigned to the geodes it attests. Return the number of geodes handed over.
+        fn start_unbonding(attestor: &T::AccountId) -> u32 {
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            <Unbonding<T>>::insert(attestor, block_number);
+            <UnbondingQueue<T>>::append(block_number, attestor);
+
+            let assigned_geodes = Self::withdraw_assignments(attestor);
+            let geodes = pallet_attestor::Attestors::<T>::get(attestor).geodes;
+            for geode in geodes.iter() {
+                Self::queue_assignment(geode);
+            }
+
+            let end = block_number + <LivenessParameters<T>>::get().unbonding_period + 1;
+            Self::deposit_event(Event::AttestorUnbonding(attestor.clone(), end));
+            (geodes.len() + assigned_geodes.len()) as u32
+        }
+
+        /// Withdraw every assignment of the attestor and queue the geodes to get other
+        /// attestors assigned. Return the geodes.
+        fn withdraw_assignments(attestor: &T::AccountId) -> BTreeSet<T::AccountId> {
+            let assigned_geodes = <AttestorAssignments<T>>::take(attestor);
+            for geode in assigned_geodes.iter() {
+                Self::unassign_attestor(geode, attestor);
+                Self::queue_assignment(geode);
+            }
+            assigned_geodes
+        }
+
+        /// Number of geodes the attestor attests or is assigned to.
+        fn attestor_load(attestor: &T::AccountId) -> u32 {
+            let attested = pallet_attestor::Attestors::<T>::get(attestor).geodes.len();
+            (attested + <AttestorAssignments<T>>::get(attestor).len()) as u32
+        }
+
+        /// Number of attestors the geode needs: `MinAttestorNum`, raised by the targets of the
+        /// classes its properties match and by its own target, and at least one.
+        pub fn attestor_target(geode: &T::AccountId) -> u32 {
+            let props = pallet_geode::Geodes::<T>::get(geode).props;
+            <AttestorTargetClasses<T>>::get()
+                .iter()
+                .filter(|(selector, _)| selector.matches(&props))
+                .fold(
+                    <MinAttestorNum<T>>::get().max(<GeodeAttestorTargets<T>>::get(geode)),
+                    |target, (_, class_target)| target.max(*class_target),
+                )
+                .max(1)
+        }
+
+        /// Move the geode to the state its attestors allow: Attested or Instantiated once as
+        /// many attestors as its target attest it, Registered or DegradedInstantiated whenever
+        /// fewer do, in which case more attestors get assigned. Return whether the geode
+        /// changed state.
+        pub fn check_attestors(geode: &T::AccountId, cause: pallet_geode::TransitionCause) -> bool {
+            use pallet_geode::GeodeState;
+            if !pallet_geode::Geodes::<T>::contains_key(geode) {
+                return false;
+            }
+            let state = pallet_geode::Geodes::<T>::get(geode).state;
+            let attestors = pallet_attestor::GeodeAttestors::<T>::get(geode).len() as u32;
+            let target = Self::attestor_target(geode);
+            let to = match state {
+                GeodeState::Registered | GeodeState::DegradedInstantiated if attestors < target => {
+                    None
+                }
+                GeodeState::Registered => Some(GeodeState::Attested),
+                GeodeState::DegradedInstantiated => Some(GeodeState::Instantiated),
+                GeodeState::Attested if attestors < target => Some(GeodeState::Registered),
+                GeodeState::Instantiated if attestors < target => {
+                    Some(GeodeState::DegradedInstantiated)
+                }
+                _ => return false,
+            };
+            let assigned = <GeodeAssignments<T>>::get(geode).len() as u32;
+            if attestors.saturating_add(assigned) < target {
+                Self::queue_assignment(geode);
+            }
+            match to {
+                Some(to) => <pallet_geode::Pallet<T>>::transit(geode, to, cause).is_ok(),
+                None => false,
+            }
+        }
+
+        /// Check every geode against the attestor targets in the next blocks.
+        fn start_target_sweep() {
+            <TargetSweep<T>>::put(pallet_geode::Geodes::<T>::final_prefix().to_vec());
+        }
+
+        /// Check at most `MaxExpiriesPerBlock` geodes after the `last_key` against the
+        /// attestor targets, as many as the `remaining` weight of the hook allows.
+        fn sweep_targets(last_key: Vec<u8>, remaining: &mut Weight) {
+            let max = T::MaxExpiriesPerBlock::get() as usize;
+            let weight = <T as Config>::WeightInfo::check_geode();
+            let mut geodes = Vec::new();
+            let mut done = true;
+            for (geode, _) in pallet_geode::Geodes::<T>::iter_from(last_key) {
+                if geodes.len() == max || weight > *remaining {
+                    done = false;
+                    break;
+                }
+                *remaining -= weight;
+                geodes.push(geode);
+            }
+            for geode in geodes.iter() {
+                Self::check_attestors(geode, pallet_geode::TransitionCause::AttestorTarget);
+            }
+            match geodes.last() {
+                Some(geode) if !done => {
+                    <TargetSweep<T>>::put(pallet_geode::Geodes::<T>::hashed_key_for(geode))
+                }
+                None if !done => {}
+                _ => <TargetSweep<T>>::kill(),
+            }
+        }
+
+        /// Queue the geode to get attestors assigned in the next block.
+        pub fn queue_assignment(geode: &T::AccountId) {
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            <AssignmentQueue<T>>::append(block_number, geode);
+        }
+
+        /// Assign attestors to the geode until as many attestors as its target attest it or
+        /// are assigned to. The least loaded attestors are picked, ties are broken by
+        /// `T::Randomness` of the block, which doesn't depend on the geode so that providers
+        /// can't grind geode ids. The geode's provider is never picked so that providers can't
+        /// choose colluding attestors, nor attestors at their declared capacity or unbonding.
+        /// Geodes still short of attestors are queued again after `assignment_retry` blocks.
+        /// Return the number of attestors visited.
+        pub fn assign_attestors(geode: &T::AccountId, now: BlockNumber) -> u32 {
+            // removed and Unknown geodes can't be attested
+            if !pallet_geode::Geodes::<T>::contains_key(geode) {
+                Self::unassign_geode(geode);
+                return 0;
+            }
+            let record = pallet_geode::Geodes::<T>::get(geode);
+            if record.state == pallet_geode::GeodeState::Unknown
+                || record.state == pallet_geode::GeodeState::Offline
+            {
+                Self::unassign_geode(geode);
+                return 0;
+            }
+
+            // unbonding attestors are being replaced
+            let attesting = pallet_attestor::GeodeAttestors::<T>::get(geode);
+            let staying = attesting
+                .iter()
+                .filter(|id| !<Unbonding<T>>::contains_key(id))
+                .count();
+            let assigned = <GeodeAssignments<T>>::get(geode);
+            let needed =
+                (Self::attestor_target(geode) as usize).saturating_sub(staying + assigned.len());
+            if needed == 0 {
+                return 0;
+            }
+
+            let (seed, _) = T::Randomness::random(ASSIGNMENT_SUBJECT);
+            let mut visited = 0u32;
+            let mut candidates = pallet_attestor::Attestors::<T>::iter()
+                .inspect(|_| visited += 1)
+                .filter(|(id, _)| {
+                    *id != record.provider
+                        && !attesting.contains(id)
+                        && !assigned.contains(id)
+                        && !<Unbonding<T>>::contains_key(id)
+                })
+                .map(|(id, attestor)| {
+                    let load = attestor.geodes.len() + <AttestorAssignments<T>>::get(&id).len();
+                    (load, T::Hashing::hash_of(&(seed, &id)), id)
+                })
+                .filter(|(load, _, id)| pallet_attestor::Pallet::<T>::has_capacity(id, *load))
+                .collect::<Vec<_>>();
+            candidates.sort();
+
+            let picked = candidates
+                .into_iter()
+                .take(needed)
+                .map(|(_, _, id)| id)
+                .collect::<Vec<_>>();
+            for attestor in picked.iter() {
+                Self::assign_attestor(geode, attestor);
+            }
+            if picked.len() < needed {
+                let retry = now.saturating_add(<LivenessParameters<T>>::get().assignment_retry);
+                <AssignmentQueue<T>>::append(retry, geode);
+            }
+            if !picked.is_empty() {
+                Self::deposit_event(Event::AttestorsAssigned(geode.clone(), picked));
+            }
+            visited
+        }
+
+        /// Assign the attestor to attest the geode.
+        pub fn assign_attestor(geode: &T::AccountId, attestor: &T::AccountId) {
+            if !<AttestationNonces<T>>::contains_key(geode) {
+                let (seed, _) = T::Randomness::random(NONCE_SUBJECT);
+                let block_number = <frame_system::Pallet<T>>::block_number();
+                let nonce = sp_io::hashing::blake2_128(&(seed, geode, block_number).encode());
+                <AttestationNonces<T>>::insert(geode, hex_encode(&nonce));
+            }
+            <GeodeAssignments<T>>::mutate(geode, |attestors| attestors.insert(attestor.clone()));
+            <AttestorAssignments<T>>::mutate(attestor, |geodes| geodes.insert(geode.clone()));
+        }
+
+        /// Withdraw the attestor's assignment to the geode.
+        fn unassign_attestor(geode: &T::AccountId, attestor: &T::AccountId) {
+            <GeodeAssignments<T>>::mutate_exists(geode, |attestors| {
+                if let Some(set) = attestors {
+                    set.remove(attestor);
+                    if set.is_empty() {
+                        *attestors = None;
+                    }
+                }
+            });
+            if !<GeodeAssignments<T>>::contains_key(geode) {
+                <AttestationNonces<T>>::remove(geode);
+            }
+            <AttestorAssignments<T>>::mutate_exists(attestor, |geodes| {
+                if let Some(set) = geodes {
+                    set.remove(geode);
+                    if set.is_empty() {
+                        *geodes = None;
+                    }
+                }
+            });
+        }
+
+        /// Verify the attestation report of the geode's enclave: its signature chains up to
+        /// the root certificate, the platform and the enclave are trusted, and the enclave
+        /// bound the geode's account to the report. IAS reports also have to be fresh and
+        /// carry the nonce issued for the geode.
+        pub fn verify_report(
+            geode: &T::AccountId,
+            report: &AttestationReport,
+        ) -> Result<EnclaveIdentity, Error<T>> {
+            let root_cert = <RaRootCert<T>>::get();
+            ensure!(
+                !root_cert.is_empty() && report.size() <= MAX_REPORT_LENGTH as usize,
+                Error::<T>::InvalidAttestationReport
+            );
+            let params = <RaParameters<T>>::get();
+            let now = T::UnixTime::now();
+            let (status, identity) = match report {
+                AttestationReport::Ias {
+                    report,
+                    signature,
+                    signing_cert,
+                } => {
+                    ensure!(
+                        T::ReportVerifier::verify_ias(
+                            &root_cert,
+                            signing_cert,
+                            report,
+                            signature,
+                            now.as_secs()
+                        ),
+                        Error::<T>::InvalidAttestationReport
+                    );
+                    let nonce = <AttestationNonces<T>>::get(geode);
+                    ensure!(
+                        !nonce.is_empty()
+                            && json_string_field(report, b"nonce") == Some(&nonce[..]),
+                        Error::<T>::InvalidReportNonce
+                    );
+                    let issued = json_string_field(report, b"timestamp")
+                        .and_then(parse_timestamp)
+                        .ok_or(Error::<T>::InvalidAttestationReport)?;
+                    let now = now.as_millis().saturated_into::<u64>();
+                    ensure!(
+                        issued.max(now) - issued.min(now) <= params.report_max_age,
+                        Error::<T>::StaleAttestationReport
+                    );
+                    (
+                        json_string_field(report, b"isvEnclaveQuoteStatus")
+                            .and_then(QuoteStatus::from_ias),
+                        json_string_field(report, b"isvEnclaveQuoteBody")
+                            .and_then(base64_decode)
+                            .and_then(|quote| EnclaveIdentity::from_quote(&quote)),
+                    )
+                }
+                AttestationReport::Dcap { quote, collateral } => (
+                    T::ReportVerifier::verify_dcap(&root_cert, quote, collateral, now.as_secs()),
+                    EnclaveIdentity::from_quote(quote),
+                ),
+            };
+            let (status, identity) = status
+                .zip(identity)
+                .ok_or(Error::<T>::InvalidAttestationReport)?;
+
+            ensure!(
+                status.is_secure(params.allow_conditional)
+                    && (params.allow_debug || !identity.debug),
+                Error::<T>::InsecureEnclave
+            );
+            ensure!(
+                T::MeasurementRegistry::measurement_status(&identity.measurement())
+                    == Some(MeasurementStatus::Approved),
+                Error::<T>::EnclaveNotAllowed
+            );
+            ensure!(
+                identity.report_data.starts_with(&geode.encode()),
+                Error::<T>::ReportNotBound
+            );
+            Ok(identity)
+        }
+
+        /// Record the enclave build the geode was attested with.
+        fn record_measurement(geode: &T::AccountId, measurement: Measurement) {
+            Self::forget_measurement(geode);
+            <MeasurementGeodes<T>>::insert(&measurement, geode, ());
+            <GeodeMeasurements<T>>::insert(geode, measurement);
+        }
+
+        /// Forget the enclave build of the geode, e.g. once it is removed.
+        fn forget_measurement(geode: &T::AccountId) {
+            if let Some(measurement) = <GeodeMeasurements<T>>::take(geode) {
+                <MeasurementGeodes<T>>::remove(&measurement, geode);
+            }
+        }
+
+        /// Degrade at most `MaxExpiriesPerBlock` geodes running the oldest revoked build, as
+        /// many as the `remaining` weight of the hook allows.
+        fn sweep_revocations(remaining: &mut Weight) {
+            let mut revoked = <RevocationSweep<T>>::get();
+            let measurement = match revoked.first() {
+                Some(measurement) => measurement.clone(),
+                None => return,
+            };
+            let max = T::MaxExpiriesPerBlock::get() as usize;
+            let mut geodes = Vec::new();
+            let mut done = true;
+            for (geode, _) in <MeasurementGeodes<T>>::iter_prefix(&measurement) {
+                let weight = <T as Config>::WeightInfo::revoke_geode(
+                    pallet_attestor::GeodeAttestors::<T>::get(&geode).len() as u32,
+                );
+                if geodes.len() == max || weight > *remaining {
+                    done = false;
+                    break;
+                }
+                *remaining -= weight;
+                geodes.push(geode);
+            }
+            for geode in geodes.iter() {
+                Self::revoke_attestations(geode);
+            }
+            if done {
+                revoked.remove(0);
+                if revoked.is_empty() {
+                    <RevocationSweep<T>>::kill();
+                } else {
+                    <RevocationSweep<T>>::put(revoked);
+                }
+            }
+        }
+
+        /// Drop the attestations of a geode running a revoked build, it is degraded until
+        /// attestors attest it with an approved build.
+        fn revoke_attestations(geode: &T::AccountId) {
+            Self::forget_measurement(geode);
+            for attestor in pallet_attestor::GeodeAttestors::<T>::take(geode).iter() {
+                if pallet_attestor::Attestors::<T>::contains_key(attestor) {
+                    pallet_attestor::Attestors::<T>::mutate(attestor, |attestor| {
+                        attestor.geodes.remove(geode);
+                    });
+                }
+            }
+            if !Self::check_attestors(geode, pallet_geode::TransitionCause::EnclaveRevocation) {
+                // because GeodeUpdateCounters will be updated in degrade_geode
+                pallet_geode::GeodeUpdateCounters::<T>::insert(
+                    geode,
+                    pallet_geode::GeodeUpdateCounters::<T>::get(geode) + 1,
+                );
+            }
+            Self::deposit_event(Event::GeodeEnclaveRevoked(geode.clone()));
+        }
+
+        /// Withdraw every assignment to the geode, e.g. once it is removed.
+        fn unassign_geode(geode: &T::AccountId) {
+            for attestor in <GeodeAssignments<T>>::take(geode).iter() {
+                Self::unassign_attestor(geode, attestor);
+            }
         }
 
         /// clean all the storage, USE WITH CARE!
@@ -470,15 +2219,291 @@ pub mod pallet {
                 }
             }
 
+            // clean ReportQueue
+            {
+                let mut report_queue = Vec::new();
+                <ReportQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        report_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in report_queue.iter() {
+                    <ReportQueue<T>>::remove(block);
+                }
+            }
+
+            // clean PendingSlashes
+            {
+                let mut geodes = Vec::new();
+                <PendingSlashes<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <PendingSlashes<T>>::remove(geode);
+                }
+            }
+
+            // clean SlashQueue
+            {
+                let mut slash_queue = Vec::new();
+                <SlashQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        slash_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in slash_queue.iter() {
+                    <SlashQueue<T>>::remove(block);
+                }
+            }
+
+            // clean ProviderOffences
+            {
+                let mut providers = Vec::new();
+                <ProviderOffences<T>>::iter()
+                    .map(|(key, _)| {
+                        providers.push(key);
+                    })
+                    .all(|_| true);
+                for provider in providers.iter() {
+                    <ProviderOffences<T>>::remove(provider);
+                }
+            }
+
+            // clean AssignmentQueue
+            {
+                let mut assignment_queue = Vec::new();
+                <AssignmentQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        assignment_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in assignment_queue.iter() {
+                    <AssignmentQueue<T>>::remove(block);
+                }
+            }
+
+            // clean GeodeAssignments
+            {
+                let mut geodes = Vec::new();
+                <GeodeAssignments<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <GeodeAssignments<T>>::remove(geode);
+                }
+            }
+
+            // clean AttestorAssignments
+            {
+                let mut attestors = Vec::new();
+                <AttestorAssignments<T>>::iter()
+                    .map(|(key, _)| {
+                        attestors.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in attestors.iter() {
+                    <AttestorAssignments<T>>::remove(attestor);
+                }
+            }
+
+            // clean AttestationNonces
+            {
+                let mut geodes = Vec::new();
+                <AttestationNonces<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <AttestationNonces<T>>::remove(geode);
+                }
+            }
+
+            // clean GeodeAttestorTargets
+            {
+                let mut geodes = Vec::new();
+                <GeodeAttestorTargets<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <GeodeAttestorTargets<T>>::remove(geode);
+                }
+            }
+            <AttestorTargetClasses<T>>::kill();
+            <TargetSweep<T>>::kill();
+
+            // clean GeodeHealth
+            {
+                let mut geodes = Vec::new();
+                <GeodeHealth<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <GeodeHealth<T>>::remove(geode);
+                }
+            }
+
+            // clean GeodeMeasurements
+            {
+                let mut geodes = Vec::new();
+                <GeodeMeasurements<T>>::iter()
+                    .map(|(key, _)| {
+                        geodes.push(key);
+                    })
+                    .all(|_| true);
+                for geode in geodes.iter() {
+                    <GeodeMeasurements<T>>::remove(geode);
+                }
+            }
+
+            // clean MeasurementGeodes
+            {
+                let mut keys = Vec::new();
+                <MeasurementGeodes<T>>::iter()
+                    .map(|(measurement, geode, _)| {
+                        keys.push((measurement, geode));
+                    })
+                    .all(|_| true);
+                for (measurement, geode) in keys.iter() {
+                    <MeasurementGeodes<T>>::remove(measurement, geode);
+                }
+            }
+            <RevocationSweep<T>>::kill();
+
+            // clean Unbonding
+            {
+                let mut attestors = Vec::new();
+                <Unbonding<T>>::iter()
+                    .map(|(key, _)| {
+                        attestors.push(key);
+                    })
+                    .all(|_| true);
+                for attestor in attestors.iter() {
+                    <Unbonding<T>>::remove(attestor);
+                }
+            }
+
+            // clean AppealQueue
+            {
+                let mut appeal_queue = Vec::new();
+                <AppealQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        appeal_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in appeal_queue.iter() {
+                    <AppealQueue<T>>::remove(block);
+                }
+            }
+
+            // clean UnbondingQueue
+            {
+                let mut unbonding_queue = Vec::new();
+                <UnbondingQueue<T>>::iter()
+                    .map(|(key, _)| {
+                        unbonding_queue.push(key);
+                    })
+                    .all(|_| true);
+                for block in unbonding_queue.iter() {
+                    <UnbondingQueue<T>>::remove(block);
+                }
+            }
+
+            // every queue is empty, restart them from the current block
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            for queue in ExpiryQueue::ALL.iter() {
+                <ExpiryCursors<T>>::insert(queue, block_number);
+            }
+
             // reset MinAttestorNum
             <MinAttestorNum<T>>::put(DEFAULT_MIN_ATTESTOR_NUM);
 
             // reset DegradeMode
             <DegradeMode<T>>::put(true);
+            <DegradeModeEnd<T>>::kill();
 
             <pallet_geode::Pallet<T>>::clean_storage();
 
             <pallet_attestor::Module<T>>::clean_storage();
         }
     }
+
+    impl<T: Config> pallet_geode::OnGeodeRegister<T::AccountId> for Pallet<T> {
+        /// Newly registered geodes get attestors assigned in the next block, the assignments
+        /// left from a previous registration of the geode are withdrawn.
+        fn on_geode_register(geode: &T::AccountId) {
+            Self::unassign_geode(geode);
+            <GeodeAttestorTargets<T>>::remove(geode);
+            <GeodeHealth<T>>::remove(geode);
+            Self::forget_measurement(geode);
+            Self::queue_assignment(geode);
+        }
+    }
+
+    impl<T: Config> pallet_geode::OnGeodeRemove<T::AccountId> for Pallet<T> {
+        /// Removed geodes are forgotten, so that the sweeps and the attestor loads stop
+        /// counting them.
+        fn on_geode_remove(geode: &T::AccountId) {
+            Self::unassign_geode(geode);
+            <GeodeAttestorTargets<T>>::remove(geode);
+            <GeodeHealth<T>>::remove(geode);
+            Self::forget_measurement(geode);
+        }
+    }
+
+    impl<T: Config> pallet_geode::SlashRegistry<T::AccountId> for Pallet<T> {
+        /// Slashed geodes are held until the slash is applied or reversed.
+        fn slash_pending(geode: &T::AccountId) -> bool {
+            <PendingSlashes<T>>::contains_key(geode)
+        }
+    }
+
+    impl<T: Config> pallet_geode::OnGeodePropsUpdate<T::AccountId> for Pallet<T> {
+        /// The geode may need a different number of attestors with its new properties.
+        fn on_geode_props_update(geode: &T::AccountId) {
+            Self::check_attestors(geode, pallet_geode::TransitionCause::AttestorTarget);
+        }
+    }
+
+    impl<T: Config> pallet_enclave::OnMeasurementRevoke for Pallet<T> {
+        /// The geodes running the build are degraded in the next blocks.
+        fn on_measurement_revoke(measurement: &Measurement) {
+            <RevocationSweep<T>>::append(measurement);
+        }
+    }
+
+    impl<T: Config> pallet_attestor::AssignmentRegistry<T::AccountId> for Pallet<T> {
+        /// Assigned geodes take capacity of the attestor until it attests them.
+        fn assigned_geodes(attestor: &T::AccountId) -> u32 {
+            <AttestorAssignments<T>>::get(attestor).len() as u32
+        }
+    }
+
+    impl<T: Config> pallet_attestor::OnHeartbeat<T::AccountId> for Pallet<T> {
+        /// Geodes regain health for each challenge they passed and lose more for each they
+        /// failed.
+        fn on_heartbeat(_attestor: &T::AccountId, results: &[(T::AccountId, bool)]) {
+            for (geode, passed) in results.iter() {
+                let health = <GeodeHealth<T>>::get(geode);
+                let updated = if *passed {
+                    health.saturating_add(HEALTH_RECOVERY).min(MAX_HEALTH)
+                } else {
+                    health.saturating_sub(HEALTH_PENALTY)
+                };
+                if updated == 0 && health > 0 {
+                    Self::deposit_event(Event::GeodeUnhealthy(geode.clone()));
+                }
+                <GeodeHealth<T>>::insert(geode, updated);
+            }
+        }
+    }
 }
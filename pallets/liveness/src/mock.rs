@@ -1,16 +1,30 @@
 use crate as liveness;
-use frame_support::parameter_types;
+use codec::{Decode, Encode};
+use frame_support::{
+    parameter_types,
+    traits::{Currency, GenesisBuild, Randomness},
+    weights::Weight,
+};
 use frame_system as system;
-use sp_core::H256;
+use liveness::{AttestationReport, EnclaveIdentity, QuoteStatus, ReportVerifier};
+use sp_core::{sr25519, Pair, H256};
+use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
+    traits::{BlakeTwo256, Hash, IdentityLookup},
 };
+use std::sync::Arc;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
 
 pub const INIT_BALANCE: u128 = 100_100_100;
+/// User of every order in the mock.
+pub const ORDER_HOLDER: u64 = 2;
+/// Root certificate of the attestation reports in the mock.
+pub const ROOT_CERT: &[u8] = b"root";
+/// MRENCLAVE of the build approved at genesis.
+pub const MR_ENCLAVE: [u8; 32] = [1; 32];
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -21,9 +35,11 @@ frame_support::construct_runtime!(
     {
         System: frame_system::{Module, Call, Config, Storage, Event<T>},
         Balances: pallet_balances::{Module, Call, Storage, Event<T>},
+        Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
         AttestorModule: pallet_attestor::{Module, Call, Storage, Event<T>},
         GeodeModule: pallet_geode::{Module, Call, Storage, Event<T>},
-        LivenessModule: liveness::{Module, Call, Storage, Event<T>},
+        EnclaveModule: pallet_enclave::{Module, Call, Storage, Event, Config},
+        LivenessModule: liveness::{Module, Call, Storage, Event<T>, Config},
     }
 );
 
@@ -73,6 +89,17 @@ impl pallet_balances::Config for Test {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const MinimumPeriod: u64 = 1;
+}
+
+impl pallet_timestamp::Config for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
+}
+
 impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
 where
     Call: From<C>,
@@ -85,14 +112,125 @@ impl pallet_attestor::Config for Test {
     type Event = Event;
     type Currency = Balances;
     type Call = Call;
+    type OnHeartbeat = LivenessModule;
+    type Assignments = LivenessModule;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxDnsLength: u32 = 253;
+    pub const MaxProps: u32 = 8;
+    pub const MaxPropLength: u32 = 32;
+    pub const DepositPerByte: u128 = 0;
 }
 
 impl pallet_geode::Config for Test {
     type Event = Event;
+    type Currency = Balances;
+    type MaxDnsLength = MaxDnsLength;
+    type MaxProps = MaxProps;
+    type MaxPropLength = MaxPropLength;
+    type DepositPerByte = DepositPerByte;
+    type OnGeodeRegister = LivenessModule;
+    type OnGeodePropsUpdate = LivenessModule;
+    type OnGeodeRemove = LivenessModule;
+    type Slashes = LivenessModule;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub static MaxExpiriesPerBlock: u32 = 100;
+    pub static MaxHookWeight: Weight = 1_000_000_000_000;
+    pub static RandomnessAge: u64 = 0;
+}
+
+pub struct MockOrderHolder;
+
+impl pallet_geode::OrderHolder<u64, H256> for MockOrderHolder {
+    fn holder(_order: &H256) -> Option<u64> {
+        Some(ORDER_HOLDER)
+    }
+}
+
+/// Randomness changing with the subject and the block, known since `RandomnessAge` blocks.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        let block_number = System::block_number();
+        (
+            BlakeTwo256::hash_of(&(subject, block_number)),
+            block_number.saturating_sub(RandomnessAge::get()),
+        )
+    }
+}
+
+/// Signature of `data` the mock verifier accepts with the root certificate.
+pub fn mock_signature(root_cert: &[u8], data: &[u8]) -> Vec<u8> {
+    sp_io::hashing::blake2_256(&[root_cert, data].concat()).to_vec()
+}
+
+/// Accepts reports signed with `mock_signature`, a DCAP collateral is the encoded quote
+/// status followed by the signature of the quote.
+pub struct MockReportVerifier;
+
+impl ReportVerifier for MockReportVerifier {
+    fn verify_ias(
+        root_cert: &[u8],
+        signing_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        _now: u64,
+    ) -> bool {
+        signing_cert == mock_signature(root_cert, b"signing").as_slice()
+            && signature == mock_signature(root_cert, report).as_slice()
+    }
+
+    fn verify_dcap(
+        root_cert: &[u8],
+        quote: &[u8],
+        collateral: &[u8],
+        _now: u64,
+    ) -> Option<QuoteStatus> {
+        let (status, signature) = collateral.split_first()?;
+        if signature != mock_signature(root_cert, quote).as_slice() {
+            return None;
+        }
+        QuoteStatus::decode(&mut &[*status][..]).ok()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn benchmark_report(quote: Vec<u8>) -> (Vec<u8>, AttestationReport) {
+        let mut collateral = QuoteStatus::Ok.encode();
+        collateral.extend(mock_signature(ROOT_CERT, &quote));
+        (
+            ROOT_CERT.to_vec(),
+            AttestationReport::Dcap { quote, collateral },
+        )
+    }
+}
+
+impl pallet_enclave::Config for Test {
+    type Event = Event;
+    type GovernanceOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type OnRevoke = LivenessModule;
+    type WeightInfo = ();
 }
 
 impl liveness::Config for Test {
     type Event = Event;
+    type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+    type MaxHookWeight = MaxHookWeight;
+    type UnixTime = Timestamp;
+    type Randomness = TestRandomness;
+    type GovernanceOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type AppealOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type OrderHolder = MockOrderHolder;
+    type Slash = ();
+    type AttestorSlash = ();
+    type ReportVerifier = MockReportVerifier;
+    type MeasurementRegistry = EnclaveModule;
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -105,8 +243,24 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     }
     .assimilate_storage(&mut t)
     .unwrap();
+    GenesisBuild::<Test>::assimilate_storage(
+        &liveness::GenesisConfig {
+            ra_root_cert: ROOT_CERT.to_vec(),
+            ..Default::default()
+        },
+        &mut t,
+    )
+    .unwrap();
+    GenesisBuild::<Test>::assimilate_storage(
+        &pallet_enclave::GenesisConfig {
+            measurements: vec![enclave(0).measurement()],
+        },
+        &mut t,
+    )
+    .unwrap();
 
     let mut ext = sp_io::TestExternalities::new(t);
+    ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
     ext.execute_with(|| System::set_block_number(1));
     ext
 }
@@ -125,20 +279,25 @@ pub fn events() -> Vec<Event> {
 
 pub fn register_attestor(_attestor_account: <Test as system::Config>::AccountId) {
     let url = vec![1];
-    let pubkey = vec![2];
+    let pair = sr25519::Pair::from_seed(&[200; 32]);
+    let pubkey = pair.public().0.to_vec();
     let min_stake = 100;
     let attestor_account = 1;
+    let block_number = System::block_number() as u32;
+    let payload = AttestorModule::key_proof_payload(&attestor_account, &pubkey, block_number);
 
     // set the min stake balance
-    AttestorModule::set_att_stake_min(Origin::root(), min_stake)
-        .map_err(|err| println!("{:?}", err));
+    AttestorModule::set_att_stake_min(Origin::root(), min_stake).expect("root sets the min stake");
 
     // successfully call register
     AttestorModule::attestor_register(
         Origin::signed(attestor_account),
         url.clone(),
         pubkey.clone(),
-    );
+        block_number,
+        pair.sign(&payload).0,
+    )
+    .expect("attestor registers");
 }
 
 pub fn provider_register_geode(
@@ -161,3 +320,89 @@ pub fn provider_register_geode(
 
     GeodeModule::provider_register_geode(Origin::signed(provider), geode);
 }
+
+/// An sr25519 key and the account it decodes to, the way liveness checks evidence keys.
+pub fn keyed_account(seed: u8) -> (sr25519::Pair, <Test as system::Config>::AccountId) {
+    let pair = sr25519::Pair::from_seed(&[seed; 32]);
+    let account = codec::Decode::decode(&mut &pair.public().0[..]).unwrap();
+    (pair, account)
+}
+
+/// Register a funded attestor at `attestor_account`, staking the same as `register_attestor`.
+/// It registers the same key as well, tests sign its evidence with its account key.
+pub fn register_keyed_attestor(attestor_account: <Test as system::Config>::AccountId) {
+    Balances::make_free_balance_be(&attestor_account, INIT_BALANCE);
+    AttestorModule::set_att_stake_min(Origin::root(), 100).unwrap();
+    let pair = sr25519::Pair::from_seed(&[200; 32]);
+    let pubkey = pair.public().0.to_vec();
+    let block_number = System::block_number() as u32;
+    let payload = AttestorModule::key_proof_payload(&attestor_account, &pubkey, block_number);
+    AttestorModule::attestor_register(
+        Origin::signed(attestor_account),
+        vec![1],
+        pubkey,
+        block_number,
+        pair.sign(&payload).0,
+    )
+    .unwrap();
+}
+
+/// The approved enclave build run by `geode`, with its account bound to the quote.
+pub fn enclave(geode: <Test as system::Config>::AccountId) -> EnclaveIdentity {
+    EnclaveIdentity {
+        mr_enclave: MR_ENCLAVE,
+        report_data: geode.encode(),
+        ..Default::default()
+    }
+}
+
+/// An IAS report of the quote issued at `timestamp`, signed for the mock verifier.
+pub fn ias_report_of(
+    quote: &[u8],
+    status: &str,
+    nonce: &[u8],
+    timestamp: u64,
+) -> AttestationReport {
+    let mut report = br#"{"id":"1","timestamp":""#.to_vec();
+    report.extend(liveness::format_timestamp(timestamp));
+    report.extend_from_slice(br#"","isvEnclaveQuoteStatus":""#);
+    report.extend_from_slice(status.as_bytes());
+    report.extend_from_slice(br#"","isvEnclaveQuoteBody":""#);
+    report.extend_from_slice(&liveness::base64_encode(quote));
+    report.extend_from_slice(br#"","nonce":""#);
+    report.extend_from_slice(nonce);
+    report.extend_from_slice(br#""}"#);
+    AttestationReport::Ias {
+        signature: mock_signature(ROOT_CERT, &report),
+        signing_cert: mock_signature(ROOT_CERT, b"signing"),
+        report,
+    }
+}
+
+/// An IAS report of the enclave with the quote status, issued now with the nonce of `geode`
+/// and signed for the mock verifier.
+pub fn ias_report(
+    geode: <Test as system::Config>::AccountId,
+    enclave: &EnclaveIdentity,
+    status: &str,
+) -> AttestationReport {
+    ias_report_of(
+        &enclave.to_quote(),
+        status,
+        &LivenessModule::attestation_nonces(geode),
+        Timestamp::now(),
+    )
+}
+
+/// A DCAP quote of the enclave with the quote status, signed for the mock verifier.
+pub fn dcap_report(enclave: &EnclaveIdentity, status: QuoteStatus) -> AttestationReport {
+    let quote = enclave.to_quote();
+    let mut collateral = status.encode();
+    collateral.extend(mock_signature(ROOT_CERT, &quote));
+    AttestationReport::Dcap { quote, collateral }
+}
+
+/// A report of the geode's enclave every check passes.
+pub fn attestation_report(geode: <Test as system::Config>::AccountId) -> AttestationReport {
+    ias_report(geode, &enclave(geode), "OK")
+}
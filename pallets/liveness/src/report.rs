@@ -0,0 +1,492 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use codec::{Decode, Encode};
+use pallet_enclave::Measurement;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Length of an SGX quote without its signature, i.e. the header and the report body.
+pub const QUOTE_BODY_LENGTH: usize = 432;
+/// Offsets of the report body fields in a quote, the body starts after the 48 bytes header in
+/// both EPID and DCAP quotes.
+const ATTRIBUTES_FLAGS_OFFSET: usize = 96;
+const MR_ENCLAVE_OFFSET: usize = 112;
+const MR_SIGNER_OFFSET: usize = 176;
+const ISV_SVN_OFFSET: usize = 306;
+const REPORT_DATA_OFFSET: usize = 368;
+/// `SGX_FLAGS_DEBUG` of the enclave attributes.
+const FLAGS_DEBUG: u64 = 0x02;
+
+/// Evidence of the enclave a geode runs, submitted by the attestor attesting it.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub enum AttestationReport {
+    /// Attestation verification report of the Intel Attestation Service.
+    Ias {
+        /// The json body of the report, as `geode_sgx_ra::IasReportResponse`.
+        report: Vec<u8>,
+        /// `X-IASReport-Signature` over the body, base64 encoded.
+        signature: Vec<u8>,
+        /// `X-IASReport-Signing-Certificate`, DER encoded.
+        signing_cert: Vec<u8>,
+    },
+    /// A DCAP quote with the collateral it is verified with, an encoded `DcapCollateral`.
+    Dcap { quote: Vec<u8>, collateral: Vec<u8> },
+}
+
+/// Collateral of a DCAP quote, as served by the Intel provisioning certification service.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+pub struct DcapCollateral {
+    /// The `tcbInfo` json object of the TCB info of the platform's FMSPC, as signed.
+    pub tcb_info: Vec<u8>,
+    /// Raw `r || s` signature over `tcb_info`.
+    pub tcb_info_signature: Vec<u8>,
+    /// The `enclaveIdentity` json object of the quoting enclave identity, as signed.
+    pub qe_identity: Vec<u8>,
+    /// Raw `r || s` signature over `qe_identity`.
+    pub qe_identity_signature: Vec<u8>,
+    /// Certificate signing the TCB info and the enclave identity, DER encoded.
+    pub tcb_signing_cert: Vec<u8>,
+}
+
+impl AttestationReport {
+    /// Total length of the report fields.
+    pub fn size(&self) -> usize {
+        match self {
+            AttestationReport::Ias {
+                report,
+                signature,
+                signing_cert,
+            } => report.len() + signature.len() + signing_cert.len(),
+            AttestationReport::Dcap { quote, collateral } => quote.len() + collateral.len(),
+        }
+    }
+}
+
+/// Status of an enclave quote, `isvEnclaveQuoteStatus` of the IAS report.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
+pub enum QuoteStatus {
+    Ok,
+    SignatureInvalid,
+    GroupRevoked,
+    SignatureRevoked,
+    KeyRevoked,
+    SigrlVersionMismatch,
+    GroupOutOfDate,
+    ConfigurationNeeded,
+    SwHardeningNeeded,
+    ConfigurationAndSwHardeningNeeded,
+}
+
+impl QuoteStatus {
+    pub fn from_ias(status: &[u8]) -> Option<Self> {
+        match status {
+            b"OK" => Some(QuoteStatus::Ok),
+            b"SIGNATURE_INVALID" => Some(QuoteStatus::SignatureInvalid),
+            b"GROUP_REVOKED" => Some(QuoteStatus::GroupRevoked),
+            b"SIGNATURE_REVOKED" => Some(QuoteStatus::SignatureRevoked),
+            b"KEY_REVOKED" => Some(QuoteStatus::KeyRevoked),
+            b"SIGRL_VERSION_MISMATCH" => Some(QuoteStatus::SigrlVersionMismatch),
+            b"GROUP_OUT_OF_DATE" => Some(QuoteStatus::GroupOutOfDate),
+            b"CONFIGURATION_NEEDED" => Some(QuoteStatus::ConfigurationNeeded),
+            b"SW_HARDENING_NEEDED" => Some(QuoteStatus::SwHardeningNeeded),
+            b"CONFIGURATION_AND_SW_HARDENING_NEEDED" => {
+                Some(QuoteStatus::ConfigurationAndSwHardeningNeeded)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the platform can be trusted, the way `IasReportResponse::is_enclave_secure`
+    /// decides it. The conditional statuses need the platform to be updated or configured.
+    pub fn is_secure(&self, allow_conditional: bool) -> bool {
+        match self {
+            QuoteStatus::Ok => true,
+            QuoteStatus::GroupOutOfDate
+            | QuoteStatus::ConfigurationNeeded
+            | QuoteStatus::SwHardeningNeeded
+            | QuoteStatus::ConfigurationAndSwHardeningNeeded => allow_conditional,
+            _ => false,
+        }
+    }
+}
+
+/// Identity of the enclave which produced a quote.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+pub struct EnclaveIdentity {
+    pub mr_enclave: [u8; 32],
+    pub mr_signer: [u8; 32],
+    pub isv_svn: u16,
+    pub debug: bool,
+    /// Data the enclave bound to the quote, 64 bytes.
+    pub report_data: Vec<u8>,
+}
+
+impl EnclaveIdentity {
+    /// Read the identity from a quote, with or without its signature.
+    pub fn from_quote(quote: &[u8]) -> Option<Self> {
+        if quote.len() < QUOTE_BODY_LENGTH {
+            return None;
+        }
+        let mut flags = [0u8; 8];
+        flags.copy_from_slice(&quote[ATTRIBUTES_FLAGS_OFFSET..ATTRIBUTES_FLAGS_OFFSET + 8]);
+        let mut mr_enclave = [0u8; 32];
+        mr_enclave.copy_from_slice(&quote[MR_ENCLAVE_OFFSET..MR_ENCLAVE_OFFSET + 32]);
+        let mut mr_signer = [0u8; 32];
+        mr_signer.copy_from_slice(&quote[MR_SIGNER_OFFSET..MR_SIGNER_OFFSET + 32]);
+        Some(EnclaveIdentity {
+            mr_enclave,
+            mr_signer,
+            isv_svn: u16::from_le_bytes([quote[ISV_SVN_OFFSET], quote[ISV_SVN_OFFSET + 1]]),
+            debug: u64::from_le_bytes(flags) & FLAGS_DEBUG != 0,
+            report_data: quote[REPORT_DATA_OFFSET..QUOTE_BODY_LENGTH].to_vec(),
+        })
+    }
+
+    /// The enclave build, as the measurement registry knows it.
+    pub fn measurement(&self) -> Measurement {
+        Measurement {
+            mr_enclave: self.mr_enclave,
+            mr_signer: self.mr_signer,
+            isv_svn: self.isv_svn,
+        }
+    }
+
+    /// A quote body of the identity, the rest of the quote left zeroed.
+    #[cfg(any(test, feature = "runtime-benchmarks"))]
+    pub fn to_quote(&self) -> Vec<u8> {
+        let mut quote = sp_std::vec![0u8; QUOTE_BODY_LENGTH];
+        if self.debug {
+            quote[ATTRIBUTES_FLAGS_OFFSET] = FLAGS_DEBUG as u8;
+        }
+        quote[MR_ENCLAVE_OFFSET..MR_ENCLAVE_OFFSET + 32].copy_from_slice(&self.mr_enclave);
+        quote[MR_SIGNER_OFFSET..MR_SIGNER_OFFSET + 32].copy_from_slice(&self.mr_signer);
+        quote[ISV_SVN_OFFSET..ISV_SVN_OFFSET + 2].copy_from_slice(&self.isv_svn.to_le_bytes());
+        let len = self
+            .report_data
+            .len()
+            .min(QUOTE_BODY_LENGTH - REPORT_DATA_OFFSET);
+        quote[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + len]
+            .copy_from_slice(&self.report_data[..len]);
+        quote
+    }
+}
+
+/// Checks the signatures of attestation reports, e.g. `HostReportVerifier` verifying the
+/// certificate chains through host functions. `now` is in seconds since the unix epoch, the
+/// certificates have to be valid then.
+pub trait ReportVerifier {
+    /// Whether `signature` of the IAS `report` verifies with `signing_cert`, and
+    /// `signing_cert` is issued by `root_cert`.
+    fn verify_ias(
+        root_cert: &[u8],
+        signing_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: u64,
+    ) -> bool;
+    /// Status of the DCAP `quote` if its signature and `collateral` chain up to `root_cert`.
+    fn verify_dcap(
+        root_cert: &[u8],
+        quote: &[u8],
+        collateral: &[u8],
+        now: u64,
+    ) -> Option<QuoteStatus>;
+
+    /// A root certificate and a report of `quote` with status `OK` the verifier accepts, for
+    /// benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    fn benchmark_report(quote: Vec<u8>) -> (Vec<u8>, AttestationReport) {
+        (
+            Vec::new(),
+            AttestationReport::Dcap {
+                quote,
+                collateral: Vec::new(),
+            },
+        )
+    }
+}
+
+/// Rejects every report.
+impl ReportVerifier for () {
+    fn verify_ias(_: &[u8], _: &[u8], _: &[u8], _: &[u8], _: u64) -> bool {
+        false
+    }
+
+    fn verify_dcap(_: &[u8], _: &[u8], _: &[u8], _: u64) -> Option<QuoteStatus> {
+        None
+    }
+}
+
+/// Milliseconds since the unix epoch of a UTC timestamp like `2021-09-14T08:43:12.123456`,
+/// the way IAS reports and the DCAP collateral carry them. A trailing `Z` is accepted.
+pub fn parse_timestamp(timestamp: &[u8]) -> Option<u64> {
+    fn number(digits: &[u8]) -> Option<u64> {
+        if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        Some(digits.iter().fold(0, |n, d| n * 10 + (d - b'0') as u64))
+    }
+
+    let timestamp = timestamp.strip_suffix(b"Z").unwrap_or(timestamp);
+    if timestamp.len() < 19
+        || timestamp[4] != b'-'
+        || timestamp[7] != b'-'
+        || timestamp[10] != b'T'
+        || timestamp[13] != b':'
+        || timestamp[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (
+        number(&timestamp[0..4])?,
+        number(&timestamp[5..7])?,
+        number(&timestamp[8..10])?,
+    );
+    let (hour, minute, second) = (
+        number(&timestamp[11..13])?,
+        number(&timestamp[14..16])?,
+        number(&timestamp[17..19])?,
+    );
+    if year < 1970
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+    let millis = match &timestamp[19..] {
+        [] => 0,
+        [b'.', fraction @ ..] => {
+            number(fraction)?;
+            let digits = &fraction[..fraction.len().min(3)];
+            number(digits)? * 10u64.pow(3 - digits.len() as u32)
+        }
+        _ => return None,
+    };
+
+    // days from the civil date, counting years from March so that leap days end them
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis)
+}
+
+/// The IAS timestamp of `millis` since the unix epoch, the inverse of `parse_timestamp`.
+#[cfg(test)]
+pub fn format_timestamp(millis: u64) -> Vec<u8> {
+    let days = millis / 86_400_000 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let time = millis % 86_400_000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}000",
+        year,
+        month,
+        day,
+        time / 3_600_000,
+        time / 60_000 % 60,
+        time / 1000 % 60,
+        time % 1000
+    )
+    .into_bytes()
+}
+
+/// Lowercase hex of the bytes.
+pub fn hex_encode(bytes: &[u8]) -> Vec<u8> {
+    const DIGITS: &[u8] = b"0123456789abcdef";
+    bytes
+        .iter()
+        .flat_map(|b| [DIGITS[(b >> 4) as usize], DIGITS[(b & 0xf) as usize]].to_vec())
+        .collect()
+}
+
+/// Value of the string field `key` of a json object, escaped strings aren't supported.
+pub fn json_string_field<'a>(json: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let mut pattern = Vec::with_capacity(key.len() + 2);
+    pattern.push(b'"');
+    pattern.extend_from_slice(key);
+    pattern.push(b'"');
+    let start = json
+        .windows(pattern.len())
+        .position(|window| window == &pattern[..])?
+        + pattern.len();
+    let mut rest = json[start..]
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())?
+        + start;
+    if json[rest] != b':' {
+        return None;
+    }
+    rest += 1;
+    rest += json[rest..].iter().position(|c| !c.is_ascii_whitespace())?;
+    if json[rest] != b'"' {
+        return None;
+    }
+    rest += 1;
+    let end = json[rest..]
+        .iter()
+        .position(|c| *c == b'"' || *c == b'\\')?
+        + rest;
+    if json[end] != b'"' {
+        return None;
+    }
+    Some(&json[rest..end])
+}
+
+/// Decode standard base64 with padding.
+pub fn base64_decode(input: &[u8]) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    if input.len() % 4 != 0 {
+        return None;
+    }
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    for (i, chunk) in input.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        // padding only ends the input
+        if padding > 2 || (padding > 0 && i + 1 != input.len() / 4) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for c in chunk[..4 - padding].iter() {
+            bits = (bits << 6) | value(*c)?;
+        }
+        bits <<= 6 * padding as u32;
+        let bytes = bits.to_be_bytes();
+        output.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Some(output)
+}
+
+/// Encode standard base64 with padding.
+#[cfg(test)]
+pub fn base64_encode(input: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = Vec::with_capacity((input.len() + 2) / 3 * 4);
+    for chunk in input.chunks(3) {
+        let mut bytes = [0u8; 4];
+        bytes[1..1 + chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes(bytes);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize]);
+            } else {
+                output.push(b'=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_should_work() {
+        assert_eq!(base64_decode(b""), Some(vec![]));
+        assert_eq!(base64_decode(b"TWFu"), Some(b"Man".to_vec()));
+        assert_eq!(base64_decode(b"TWE="), Some(b"Ma".to_vec()));
+        assert_eq!(base64_decode(b"TQ=="), Some(b"M".to_vec()));
+        assert_eq!(base64_encode(b"Ma"), b"TWE=".to_vec());
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(base64_decode(&base64_encode(&bytes)), Some(bytes));
+        // bad length, character or padding
+        assert_eq!(base64_decode(b"TWF"), None);
+        assert_eq!(base64_decode(b"TW-u"), None);
+        assert_eq!(base64_decode(b"TQ==TWFu"), None);
+    }
+
+    #[test]
+    fn json_string_field_should_work() {
+        let json = br#"{"id":"1", "isvEnclaveQuoteStatus" : "OK","nonce":"a\"b"}"#;
+        assert_eq!(
+            json_string_field(json, b"isvEnclaveQuoteStatus"),
+            Some(&b"OK"[..])
+        );
+        assert_eq!(json_string_field(json, b"id"), Some(&b"1"[..]));
+        assert_eq!(json_string_field(json, b"isvEnclaveQuoteBody"), None);
+        // escaped strings aren't supported
+        assert_eq!(json_string_field(json, b"nonce"), None);
+    }
+
+    #[test]
+    fn timestamp_should_work() {
+        assert_eq!(parse_timestamp(b"1970-01-01T00:00:00"), Some(0));
+        assert_eq!(
+            parse_timestamp(b"2021-09-14T08:43:12.123456"),
+            Some(1_631_608_992_123)
+        );
+        assert_eq!(
+            parse_timestamp(b"2000-03-01T00:00:00Z"),
+            Some(951_868_800_000)
+        );
+        for millis in [0, 951_782_400_000, 1_631_608_992_123, 4_107_542_399_999].iter() {
+            assert_eq!(parse_timestamp(&format_timestamp(*millis)), Some(*millis));
+        }
+        // malformed or before the epoch
+        assert_eq!(parse_timestamp(b"2021-09-14 08:43:12"), None);
+        assert_eq!(parse_timestamp(b"2021-13-14T08:43:12"), None);
+        assert_eq!(parse_timestamp(b"2021-09-14T08:43:12.+1"), None);
+        assert_eq!(parse_timestamp(b"1969-12-31T23:59:59"), None);
+    }
+
+    #[test]
+    fn hex_encode_should_work() {
+        assert_eq!(hex_encode(&[]), b"".to_vec());
+        assert_eq!(hex_encode(&[0x01, 0xab, 0xff]), b"01abff".to_vec());
+    }
+
+    #[test]
+    fn enclave_identity_should_work() {
+        let identity = EnclaveIdentity {
+            mr_enclave: [1; 32],
+            mr_signer: [2; 32],
+            isv_svn: 258,
+            debug: true,
+            report_data: vec![3; 64],
+        };
+        let quote = identity.to_quote();
+        assert_eq!(EnclaveIdentity::from_quote(&quote), Some(identity));
+        assert_eq!(
+            EnclaveIdentity::from_quote(&quote[..QUOTE_BODY_LENGTH - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn quote_status_should_work() {
+        assert_eq!(QuoteStatus::from_ias(b"OK"), Some(QuoteStatus::Ok));
+        assert_eq!(QuoteStatus::from_ias(b"ok"), None);
+        assert!(QuoteStatus::Ok.is_secure(false));
+        let out_of_date = QuoteStatus::from_ias(b"GROUP_OUT_OF_DATE").unwrap();
+        assert!(!out_of_date.is_secure(false));
+        assert!(out_of_date.is_secure(true));
+        assert!(!QuoteStatus::KeyRevoked.is_secure(true));
+    }
+}
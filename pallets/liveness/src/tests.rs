@@ -1,5 +1,98 @@
-use crate::{mock::*, Error, ReportType};
+use crate::{
+    json_bytes, mock::*, AttestationNonces, AttestationReport, AttestorOffence, CounterEvidence,
+    DegradeMode, EnclaveIdentity, Error, Evidence, ExpiryCursors, ExpiryQueue, GeodeAssignments,
+    LivenessParams, QuoteStatus, RaParams, ReportType, SignedResponse, SlashParams, SlashStatus,
+    Transcript, WeightInfo, ASSIGNMENT_RETRY_BLOCK_NUMBER, ATTESTATION_EXPIRY_BLOCK_NUMBER,
+    ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER, HEALTH_PENALTY, HEALTH_RECOVERY, MAX_HEALTH,
+    MAX_TARGET_CLASSES, RESPONSE_TIMEOUT_MS,
+};
+use codec::Encode;
+use frame_support::traits::Hooks;
 use frame_support::{assert_noop, assert_ok};
+use pallet_geode::{GeodeState, TransitionCause};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{DispatchError, Percent};
+
+const NONCE: [u8; 4] = [1, 2, 3, 4];
+const SENT_AT: u64 = 1_000;
+
+/// The payload `signer` signs for evidence reported by `reporter`.
+fn sign_evidence(
+    evidence: &mut Evidence,
+    signer: &sr25519::Pair,
+    reporter: u64,
+    report_type: ReportType,
+) {
+    evidence.attestor_signature = signer.sign(&evidence.attestor_payload(
+        &System::block_hash(0),
+        &reporter,
+        report_type as u8,
+    ));
+}
+
+/// Evidence of `attestor` about the transcript of a request to `geode`, the enclave answering
+/// `response` if any.
+fn evidence(
+    geode: &sr25519::Pair,
+    attestor: &sr25519::Pair,
+    report_type: ReportType,
+    response: Option<&[u8]>,
+    received_at: u64,
+) -> Evidence {
+    let mut evidence = Evidence {
+        geode_key: geode.public(),
+        transcript: Transcript {
+            request: NONCE.to_vec(),
+            response: response.map(|msg| SignedResponse {
+                msg: msg.to_vec(),
+                signature: geode.sign(&json_bytes(msg)),
+            }),
+            sent_at: SENT_AT,
+            received_at,
+        },
+        attestor_key: attestor.public(),
+        attestor_signature: Default::default(),
+    };
+    let reporter = codec::Decode::decode(&mut &attestor.public().0[..]).unwrap();
+    sign_evidence(&mut evidence, attestor, reporter, report_type);
+    evidence
+}
+
+/// A geode which didn't answer a challenge in time.
+fn timed_out_evidence(geode: &sr25519::Pair, attestor: &sr25519::Pair) -> Evidence {
+    evidence(
+        geode,
+        attestor,
+        ReportType::Challenge,
+        None,
+        SENT_AT + RESPONSE_TIMEOUT_MS + 1,
+    )
+}
+
+/// Assign the attestor to the geode, as the chain would, and attest it.
+fn attest(attestor: u64, geode: u64) {
+    LivenessModule::assign_attestor(&geode, &attestor);
+    assert_ok!(LivenessModule::attestor_attest_geode(
+        Origin::signed(attestor),
+        geode,
+        attestation_report(geode)
+    ));
+}
+
+/// Register a keyed attestor attesting a keyed geode it provides, return their keys and
+/// accounts.
+fn setup_keyed_attestation() -> ((sr25519::Pair, u64), (sr25519::Pair, u64)) {
+    let (attestor_pair, attestor_account) = keyed_account(1);
+    let (geode_pair, geode_account) = keyed_account(2);
+    register_keyed_attestor(attestor_account);
+    provider_register_geode(attestor_account, geode_account);
+    attest(attestor_account, geode_account);
+    Timestamp::set_timestamp(SENT_AT + RESPONSE_TIMEOUT_MS + 2);
+    (
+        (attestor_pair, attestor_account),
+        (geode_pair, geode_account),
+    )
+}
 
 #[test]
 fn it_works_attestor_attest_geode() {
@@ -10,9 +103,19 @@ fn it_works_attestor_attest_geode() {
         register_attestor(attestor_account);
         provider_register_geode(attestor_account, geode_account);
 
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                attestation_report(geode_account)
+            ),
+            Error::<Test>::NotAssigned
+        );
+        LivenessModule::assign_attestor(&geode_account, &attestor_account);
         assert_ok!(LivenessModule::attestor_attest_geode(
             Origin::signed(attestor_account),
-            geode_account
+            geode_account,
+            attestation_report(geode_account)
         ));
     });
 }
@@ -25,7 +128,11 @@ fn it_attestor_attest_geode_invalid_attestor() {
 
         // attestor not registered
         assert_noop!(
-            LivenessModule::attestor_attest_geode(Origin::signed(attestor_account), geode_account),
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                attestation_report(geode_account)
+            ),
             pallet_attestor::Error::<Test>::InvalidAttestor
         );
     });
@@ -40,14 +147,15 @@ fn it_attestor_attest_geode_already_attestor() {
         register_attestor(attestor_account);
         provider_register_geode(attestor_account, geode_account);
 
-        assert_ok!(LivenessModule::attestor_attest_geode(
-            Origin::signed(attestor_account),
-            geode_account
-        ));
+        attest(attestor_account, geode_account);
 
         // readly registered before
         assert_noop!(
-            LivenessModule::attestor_attest_geode(Origin::signed(attestor_account), geode_account),
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                attestation_report(geode_account)
+            ),
             Error::<Test>::AlreadyAttestFor
         );
     });
@@ -63,53 +171,380 @@ fn it_attestor_attest_geode_invalid_geode() {
 
         // geode not registered before
         assert_noop!(
-            LivenessModule::attestor_attest_geode(Origin::signed(attestor_account), geode_account),
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                attestation_report(geode_account)
+            ),
             pallet_geode::Error::<Test>::InvalidGeode
         );
     });
 }
 
 #[test]
-fn it_works_report_misconduct() {
+fn it_works_attestor_attest_geode_report() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let geode_account = 2;
+        register_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+        LivenessModule::assign_attestor(&geode_account, &attestor_account);
+        let attest_with = |report| {
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                report,
+            )
+        };
+
+        // not signed for the root certificate
+        let mut report = attestation_report(geode_account);
+        if let AttestationReport::Ias { signature, .. } = &mut report {
+            signature[0] ^= 1;
+        }
+        assert_noop!(attest_with(report), Error::<Test>::InvalidAttestationReport);
+        let mut report = dcap_report(&enclave(geode_account), QuoteStatus::Ok);
+        if let AttestationReport::Dcap { quote, .. } = &mut report {
+            quote.push(0);
+        }
+        assert_noop!(attest_with(report), Error::<Test>::InvalidAttestationReport);
+        assert_noop!(
+            attest_with(ias_report(
+                geode_account,
+                &enclave(geode_account),
+                "UNKNOWN"
+            )),
+            Error::<Test>::InvalidAttestationReport
+        );
+
+        // revoked or outdated platforms and debug enclaves
+        assert_noop!(
+            attest_with(ias_report(
+                geode_account,
+                &enclave(geode_account),
+                "KEY_REVOKED"
+            )),
+            Error::<Test>::InsecureEnclave
+        );
+        assert_noop!(
+            attest_with(dcap_report(
+                &enclave(geode_account),
+                QuoteStatus::GroupOutOfDate
+            )),
+            Error::<Test>::InsecureEnclave
+        );
+        let debug = EnclaveIdentity {
+            debug: true,
+            ..enclave(geode_account)
+        };
+        assert_noop!(
+            attest_with(ias_report(geode_account, &debug, "OK")),
+            Error::<Test>::InsecureEnclave
+        );
+
+        // builds which aren't approved, deprecated builds, and reports of other geodes
+        let other_build = EnclaveIdentity {
+            mr_enclave: [2; 32],
+            mr_signer: [3; 32],
+            ..enclave(geode_account)
+        };
+        assert_noop!(
+            attest_with(ias_report(geode_account, &other_build, "OK")),
+            Error::<Test>::EnclaveNotAllowed
+        );
+        let old_build = EnclaveIdentity {
+            mr_enclave: [4; 32],
+            ..enclave(geode_account)
+        };
+        assert_ok!(EnclaveModule::add_measurement(
+            Origin::root(),
+            old_build.measurement()
+        ));
+        assert_ok!(EnclaveModule::deprecate_measurement(
+            Origin::root(),
+            old_build.measurement()
+        ));
+        assert_noop!(
+            attest_with(ias_report(geode_account, &old_build, "OK")),
+            Error::<Test>::EnclaveNotAllowed
+        );
+        assert_noop!(
+            attest_with(ias_report(geode_account, &enclave(geode_account + 1), "OK")),
+            Error::<Test>::ReportNotBound
+        );
+
+        // approved builds are accepted, outdated platforms when allowed
+        assert_ok!(EnclaveModule::add_measurement(
+            Origin::root(),
+            other_build.measurement()
+        ));
+        assert_ok!(LivenessModule::set_ra_params(
+            Origin::root(),
+            RaParams {
+                allow_conditional: true,
+                ..Default::default()
+            }
+        ));
+        assert_ok!(attest_with(dcap_report(
+            &other_build,
+            QuoteStatus::GroupOutOfDate
+        )));
+
+        // reports are verified against the root certificate set by governance
+        provider_register_geode(attestor_account, geode_account + 1);
+        LivenessModule::assign_attestor(&(geode_account + 1), &attestor_account);
+        assert_ok!(LivenessModule::set_ra_root_cert(
+            Origin::root(),
+            b"other root".to_vec()
+        ));
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account + 1,
+                attestation_report(geode_account + 1)
+            ),
+            Error::<Test>::InvalidAttestationReport
+        );
+    });
+}
+
+#[test]
+fn it_attestor_attest_geode_report_fresh() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let geode_account = 2;
+        let quote = enclave(geode_account).to_quote();
+        let now = 1_631_608_992_123;
+        let max_age = RaParams::default().report_max_age;
+        register_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+        Timestamp::set_timestamp(now);
+        let attest_with = |report| {
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                report,
+            )
+        };
+
+        // the nonce is issued once an attestor is assigned
+        assert!(!AttestationNonces::<Test>::contains_key(geode_account));
+        LivenessModule::assign_attestor(&geode_account, &attestor_account);
+        let nonce = LivenessModule::attestation_nonces(geode_account);
+        assert_eq!(nonce.len(), 32);
+
+        // reports requested with another nonce
+        assert_noop!(
+            attest_with(ias_report_of(&quote, "OK", &[b'0'; 32], now)),
+            Error::<Test>::InvalidReportNonce
+        );
+        assert_noop!(
+            attest_with(ias_report_of(&quote, "OK", b"", now)),
+            Error::<Test>::InvalidReportNonce
+        );
+
+        // reports issued outside the freshness window around the chain's time
+        assert_noop!(
+            attest_with(ias_report_of(&quote, "OK", &nonce, now - max_age - 1)),
+            Error::<Test>::StaleAttestationReport
+        );
+        assert_noop!(
+            attest_with(ias_report_of(&quote, "OK", &nonce, now + max_age + 1)),
+            Error::<Test>::StaleAttestationReport
+        );
+
+        // the nonce is dropped once no attestor is left to attest the geode
+        assert_ok!(attest_with(ias_report_of(
+            &quote,
+            "OK",
+            &nonce,
+            now - max_age
+        )));
+        assert!(!AttestationNonces::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_works_set_ra_params() {
+    new_test_ext().execute_with(|| {
+        let params = RaParams {
+            allow_conditional: true,
+            allow_debug: true,
+            report_max_age: 1000,
+        };
+
+        assert_noop!(
+            LivenessModule::set_ra_params(Origin::signed(1), params.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            LivenessModule::set_ra_root_cert(Origin::signed(1), vec![1]),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            LivenessModule::set_ra_params(
+                Origin::root(),
+                RaParams {
+                    report_max_age: 0,
+                    ..params.clone()
+                }
+            ),
+            Error::<Test>::InvalidRaParams
+        );
+
+        assert_ok!(LivenessModule::set_ra_params(
+            Origin::root(),
+            params.clone()
+        ));
+        assert_eq!(LivenessModule::ra_params(), params);
+        assert_ok!(LivenessModule::set_ra_root_cert(Origin::root(), vec![1]));
+        assert_eq!(LivenessModule::ra_root_cert(), vec![1]);
+        assert_eq!(
+            events().last(),
+            Some(&Event::liveness(crate::Event::RaRootCertUpdated))
+        );
+    });
+}
+
+#[test]
+fn it_works_enclave_revocation() {
     new_test_ext().execute_with(|| {
         let attestor_account = 1;
         let geode_account = 2;
-        let report_type = ReportType::Challenge;
-        let proof: Vec<u8> = vec![];
+        let measurement = enclave(geode_account).measurement();
 
         register_attestor(attestor_account);
         provider_register_geode(attestor_account, geode_account);
+        attest(attestor_account, geode_account);
+        assert_eq!(
+            LivenessModule::geode_measurements(geode_account),
+            Some(measurement.clone())
+        );
 
-        assert_ok!(LivenessModule::attestor_attest_geode(
-            Origin::signed(attestor_account),
-            geode_account
+        // deprecating the build keeps the geode attested
+        assert_ok!(EnclaveModule::deprecate_measurement(
+            Origin::root(),
+            measurement.clone()
         ));
+        run_to_block(2);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).unwrap().state,
+            GeodeState::Attested
+        );
+
+        // revoking it drops the attestors at the next block
+        assert_ok!(EnclaveModule::revoke_measurement(
+            Origin::root(),
+            measurement.clone()
+        ));
+        assert_eq!(LivenessModule::revocation_sweep(), vec![measurement]);
+        run_to_block(3);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).unwrap().state,
+            GeodeState::Registered
+        );
+        assert!(AttestorModule::geode_attestors(geode_account).is_empty());
+        assert!(!AttestorModule::attestors(attestor_account)
+            .geodes
+            .contains(&geode_account));
+        assert_eq!(LivenessModule::geode_measurements(geode_account), None);
+        assert!(LivenessModule::revocation_sweep().is_empty());
+        assert!(
+            events().contains(&Event::liveness(crate::Event::GeodeEnclaveRevoked(
+                geode_account
+            )))
+        );
+
+        // the revoked build can't be attested again
+        LivenessModule::assign_attestor(&geode_account, &attestor_account);
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(attestor_account),
+                geode_account,
+                attestation_report(geode_account)
+            ),
+            Error::<Test>::EnclaveNotAllowed
+        );
+    });
+}
+
+#[test]
+fn it_works_report_misconduct() {
+    new_test_ext().execute_with(|| {
+        let ((attestor_pair, attestor_account), (geode_pair, geode_account)) =
+            setup_keyed_attestation();
 
         // report misconduct successfully
         assert_ok!(LivenessModule::report_misconduct(
             Origin::signed(attestor_account),
             geode_account,
-            report_type as u8,
-            proof
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &attestor_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+    });
+}
+
+#[test]
+fn it_works_report_merges_pending_slash() {
+    new_test_ext().execute_with(|| {
+        let ((attestor_pair, attestor_account), (geode_pair, geode_account)) =
+            setup_keyed_attestation();
+        let earlier_reporter = 9;
+        let start = System::block_number() as u32;
+        crate::PendingSlashes::<Test>::insert(
+            geode_account,
+            crate::PendingSlash {
+                report_type: ReportType::Service,
+                provider: attestor_account,
+                reporters: [earlier_reporter].iter().cloned().collect(),
+                start,
+                status: SlashStatus::Pending,
+                request: vec![9],
+                ..Default::default()
+            },
+        );
+        crate::SlashQueue::<Test>::append(start, geode_account);
+
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(attestor_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &attestor_pair)
         ));
+
+        // the pending slash takes the new reporter and keeps its request
+        let slash = LivenessModule::pending_slashes(geode_account);
+        assert_eq!(
+            slash.reporters,
+            [earlier_reporter, attestor_account]
+                .iter()
+                .cloned()
+                .collect::<std::collections::BTreeSet<_>>()
+        );
+        assert!(slash.attestors.is_empty());
+        assert_eq!(slash.report_type, ReportType::Service);
+        assert_eq!(slash.request, vec![9]);
+        assert_eq!(LivenessModule::slash_queue(start), vec![geode_account]);
     });
 }
 
 #[test]
 fn it_report_misconduct_invalid_attestor() {
     new_test_ext().execute_with(|| {
-        let attestor_account = 1;
-        let geode_account = 2;
-        let report_type = ReportType::Challenge;
-        let proof: Vec<u8> = vec![];
+        let (attestor_pair, attestor_account) = keyed_account(1);
+        let (geode_pair, geode_account) = keyed_account(2);
 
         // attestor not registered
         assert_noop!(
             LivenessModule::report_misconduct(
                 Origin::signed(attestor_account),
                 geode_account,
-                report_type as u8,
-                proof
+                ReportType::Challenge as u8,
+                timed_out_evidence(&geode_pair, &attestor_pair)
             ),
             pallet_attestor::Error::<Test>::InvalidAttestor
         );
@@ -119,19 +554,17 @@ fn it_report_misconduct_invalid_attestor() {
 #[test]
 fn it_report_misconduct_not_attesting_for() {
     new_test_ext().execute_with(|| {
-        let attestor_account = 1;
-        let geode_account = 2;
-        let report_type = ReportType::Challenge;
-        let proof: Vec<u8> = vec![];
-        register_attestor(attestor_account);
+        let (attestor_pair, attestor_account) = keyed_account(1);
+        let (geode_pair, geode_account) = keyed_account(2);
+        register_keyed_attestor(attestor_account);
 
         // attestor not for the geode
         assert_noop!(
             LivenessModule::report_misconduct(
                 Origin::signed(attestor_account),
                 geode_account,
-                report_type as u8,
-                proof
+                ReportType::Challenge as u8,
+                timed_out_evidence(&geode_pair, &attestor_pair)
             ),
             Error::<Test>::NotAttestingFor
         );
@@ -141,29 +574,1706 @@ fn it_report_misconduct_not_attesting_for() {
 #[test]
 fn it_report_misconduct_invalid_report_type() {
     new_test_ext().execute_with(|| {
-        let attestor_account = 1;
-        let geode_account = 2;
-        // wrong report type
-        let report_type = 100_u8;
-        let proof: Vec<u8> = vec![];
+        let ((attestor_pair, attestor_account), (geode_pair, geode_account)) =
+            setup_keyed_attestation();
 
-        register_attestor(attestor_account);
-        provider_register_geode(attestor_account, geode_account);
+        // report type is wrong
+        assert_noop!(
+            LivenessModule::report_misconduct(
+                Origin::signed(attestor_account),
+                geode_account,
+                100_u8,
+                timed_out_evidence(&geode_pair, &attestor_pair)
+            ),
+            Error::<Test>::InvalidReportType
+        );
+    });
+}
 
-        assert_ok!(LivenessModule::attestor_attest_geode(
-            Origin::signed(attestor_account),
-            geode_account
+#[test]
+fn it_report_misconduct_invalid_evidence() {
+    new_test_ext().execute_with(|| {
+        let ((attestor_pair, attestor_account), (geode_pair, geode_account)) =
+            setup_keyed_attestation();
+        let (other_pair, _) = keyed_account(3);
+        let report = |evidence| {
+            LivenessModule::report_misconduct(
+                Origin::signed(attestor_account),
+                geode_account,
+                ReportType::Challenge as u8,
+                evidence,
+            )
+        };
+
+        // signed by somebody else than the attestor
+        let mut forged = timed_out_evidence(&geode_pair, &attestor_pair);
+        sign_evidence(
+            &mut forged,
+            &other_pair,
+            attestor_account,
+            ReportType::Challenge,
+        );
+        assert_noop!(report(forged), Error::<Test>::InvalidEvidence);
+
+        // signed for another reporter
+        let mut forged = timed_out_evidence(&geode_pair, &attestor_pair);
+        sign_evidence(
+            &mut forged,
+            &attestor_pair,
+            attestor_account.wrapping_add(1),
+            ReportType::Challenge,
+        );
+        assert_noop!(report(forged), Error::<Test>::InvalidEvidence);
+
+        // signed for another chain
+        let mut forged = timed_out_evidence(&geode_pair, &attestor_pair);
+        forged.attestor_signature = attestor_pair.sign(&forged.attestor_payload(
+            &H256::repeat_byte(7),
+            &attestor_account,
+            ReportType::Challenge as u8,
         ));
+        assert_noop!(report(forged), Error::<Test>::InvalidEvidence);
 
-        // report type is wrong
+        // about another geode
+        assert_noop!(
+            report(timed_out_evidence(&other_pair, &attestor_pair)),
+            Error::<Test>::InvalidEvidence
+        );
+
+        // the response isn't signed by the enclave
+        let mut forged = evidence(
+            &geode_pair,
+            &attestor_pair,
+            ReportType::Challenge,
+            Some(&[4, 3, 2, 1]),
+            SENT_AT + 1,
+        );
+        forged.transcript.response.as_mut().unwrap().signature =
+            other_pair.sign(&json_bytes(&[4, 3, 2, 1]));
+        sign_evidence(
+            &mut forged,
+            &attestor_pair,
+            attestor_account,
+            ReportType::Challenge,
+        );
+        assert_noop!(report(forged), Error::<Test>::InvalidEvidence);
+
+        // received in the future
+        assert_noop!(
+            report(evidence(
+                &geode_pair,
+                &attestor_pair,
+                ReportType::Challenge,
+                None,
+                SENT_AT + RESPONSE_TIMEOUT_MS + 10
+            )),
+            Error::<Test>::InvalidEvidence
+        );
+
+        // too old
+        Timestamp::set_timestamp(SENT_AT + crate::EVIDENCE_MAX_AGE_MS + 1);
         assert_noop!(
+            report(timed_out_evidence(&geode_pair, &attestor_pair)),
+            Error::<Test>::InvalidEvidence
+        );
+    });
+}
+
+#[test]
+fn it_works_report_misconduct_rotated_key() {
+    new_test_ext().execute_with(|| {
+        let ((_, attestor_account), (geode_pair, geode_account)) = setup_keyed_attestation();
+        let (rotated_pair, _) = keyed_account(3);
+        let report = || {
+            let mut evidence = timed_out_evidence(&geode_pair, &rotated_pair);
+            sign_evidence(
+                &mut evidence,
+                &rotated_pair,
+                attestor_account,
+                ReportType::Challenge,
+            );
+            LivenessModule::report_misconduct(
+                Origin::signed(attestor_account),
+                geode_account,
+                ReportType::Challenge as u8,
+                evidence,
+            )
+        };
+
+        // only keys of the attestor sign its evidence
+        assert_noop!(report(), Error::<Test>::InvalidEvidence);
+        let pubkey = rotated_pair.public().0.to_vec();
+        let block_number = System::block_number() as u32;
+        let payload = AttestorModule::key_proof_payload(&attestor_account, &pubkey, block_number);
+        assert_ok!(AttestorModule::attestor_rotate_key(
+            Origin::signed(attestor_account),
+            pubkey,
+            block_number,
+            rotated_pair.sign(&payload).0
+        ));
+        assert_ok!(report());
+    });
+}
+
+#[test]
+fn it_report_misconduct_no_misconduct() {
+    new_test_ext().execute_with(|| {
+        let ((attestor_pair, attestor_account), (geode_pair, geode_account)) =
+            setup_keyed_attestation();
+        let report = |report_type: ReportType, response: &[u8], received_at| {
             LivenessModule::report_misconduct(
                 Origin::signed(attestor_account),
                 geode_account,
-                report_type,
-                proof
+                report_type.clone() as u8,
+                evidence(
+                    &geode_pair,
+                    &attestor_pair,
+                    report_type,
+                    Some(response),
+                    received_at,
+                ),
+            )
+        };
+
+        // the enclave answered the challenge in time
+        assert_noop!(
+            report(ReportType::Challenge, &NONCE, SENT_AT + 1),
+            Error::<Test>::NoMisconduct
+        );
+        // services are only checked for timeouts
+        assert_noop!(
+            report(ReportType::Service, &[9], SENT_AT + 1),
+            Error::<Test>::NoMisconduct
+        );
+        // a signed answer claimed late proves nothing
+        assert_noop!(
+            report(
+                ReportType::Challenge,
+                &NONCE,
+                SENT_AT + RESPONSE_TIMEOUT_MS + 1
             ),
-            Error::<Test>::InvalidReportType
+            Error::<Test>::NoMisconduct
+        );
+
+        // wrong answer to the challenge
+        assert_ok!(report(ReportType::Challenge, &[9], SENT_AT + 1));
+    });
+}
+
+#[test]
+fn it_works_geode_history_on_attestor_exit() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let geode_account = 2;
+
+        register_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+
+        attest(attestor_account, geode_account);
+        set_unbonding_period(2);
+        assert_ok!(LivenessModule::attestor_exit(Origin::signed(
+            attestor_account
+        )));
+        pass_unbonding_period();
+
+        let causes: Vec<_> = GeodeModule::geode_history(geode_account)
+            .into_iter()
+            .map(|transition| (transition.to, transition.cause))
+            .collect();
+        assert_eq!(
+            causes,
+            vec![
+                (GeodeState::Registered, TransitionCause::ProviderAction),
+                (GeodeState::Attested, TransitionCause::Attestation),
+                (GeodeState::Registered, TransitionCause::AttestorRemoval),
+            ]
+        );
+    });
+}
+
+#[test]
+fn it_works_report_misconduct_slash_deposit() {
+    new_test_ext().execute_with(|| {
+        let (attestor_pair, attestor_account) = keyed_account(1);
+        let (geode_pair, geode_account) = keyed_account(2);
+        let deposit = 1000;
+        let attestor_stake = 100;
+
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+        register_keyed_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+        assert_eq!(
+            Balances::reserved_balance(attestor_account),
+            attestor_stake + deposit
+        );
+
+        attest(attestor_account, geode_account);
+        Timestamp::set_timestamp(SENT_AT + RESPONSE_TIMEOUT_MS + 2);
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(attestor_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &attestor_pair)
+        ));
+
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+        assert_eq!(GeodeModule::reserved_deposits(geode_account), 0);
+        assert_eq!(Balances::reserved_balance(attestor_account), attestor_stake);
+        assert_eq!(
+            Balances::free_balance(attestor_account),
+            INIT_BALANCE - attestor_stake - deposit
+        );
+    });
+}
+
+/// Two keyed attestors attesting a keyed geode, the second one bonding `extra_stake`.
+fn setup_two_attestors(extra_stake: u128) -> ([(sr25519::Pair, u64); 2], (sr25519::Pair, u64)) {
+    let (cheap_pair, cheap_account) = keyed_account(1);
+    let (bonded_pair, bonded_account) = keyed_account(3);
+    let (geode_pair, geode_account) = keyed_account(2);
+    register_keyed_attestor(cheap_account);
+    register_keyed_attestor(bonded_account);
+    assert_ok!(AttestorModule::attestor_bond(
+        Origin::signed(bonded_account),
+        extra_stake
+    ));
+    provider_register_geode(cheap_account, geode_account);
+    for attestor in [cheap_account, bonded_account].iter() {
+        attest(*attestor, geode_account);
+    }
+    Timestamp::set_timestamp(SENT_AT + RESPONSE_TIMEOUT_MS + 2);
+    (
+        [(cheap_pair, cheap_account), (bonded_pair, bonded_account)],
+        (geode_pair, geode_account),
+    )
+}
+
+#[test]
+fn it_works_report_misconduct_stake_weighted() {
+    new_test_ext().execute_with(|| {
+        let (
+            [(cheap_pair, cheap_account), (bonded_pair, bonded_account)],
+            (geode_pair, geode_account),
+        ) = setup_two_attestors(200);
+
+        // a quarter of the stake doesn't reach the approval ratio
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(cheap_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &cheap_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(bonded_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &bonded_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+    });
+}
+
+#[test]
+fn it_works_report_misconduct_quorum() {
+    new_test_ext().execute_with(|| {
+        let (
+            [(cheap_pair, cheap_account), (bonded_pair, bonded_account)],
+            (geode_pair, geode_account),
+        ) = setup_two_attestors(200);
+        assert_ok!(LivenessModule::set_liveness_params(
+            Origin::root(),
+            LivenessParams {
+                report_quorum: 2,
+                ..Default::default()
+            }
+        ));
+
+        // three quarters of the stake, but a single attestor
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(bonded_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &bonded_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(cheap_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &cheap_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+    });
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        LivenessModule::on_initialize(System::block_number());
+    }
+}
+
+/// Shorten the appeal window so that slashes apply before the attestors time out.
+fn set_appeal_window(appeal_window: u32) {
+    assert_ok!(LivenessModule::set_liveness_params(
+        Origin::root(),
+        LivenessParams {
+            appeal_window,
+            ..Default::default()
+        }
+    ));
+}
+
+/// Run until the funds of the slashes made in the current block are slashed.
+fn pass_appeal_window() {
+    run_to_block(
+        System::block_number() + LivenessModule::liveness_params().appeal_window as u64 + 1,
+    );
+}
+
+/// Shorten the unbonding period so that exiting attestors are removed before they time out.
+fn set_unbonding_period(unbonding_period: u32) {
+    assert_ok!(LivenessModule::set_liveness_params(
+        Origin::root(),
+        LivenessParams {
+            unbonding_period,
+            ..Default::default()
+        }
+    ));
+}
+
+/// Run until the attestors exiting in the current block are removed.
+fn pass_unbonding_period() {
+    run_to_block(
+        System::block_number() + LivenessModule::liveness_params().unbonding_period as u64 + 1,
+    );
+}
+
+#[test]
+fn it_works_registered_geode_expiry() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        DegradeMode::<Test>::put(false);
+        provider_register_geode(provider, geode_account);
+
+        let expiry = 1 + ATTESTATION_EXPIRY_BLOCK_NUMBER as u64;
+        run_to_block(expiry);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+
+        run_to_block(expiry + 1);
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+        assert_eq!(
+            GeodeModule::geode_history(geode_account)
+                .last()
+                .unwrap()
+                .cause,
+            TransitionCause::LivenessExpiry
+        );
+    });
+}
+
+#[test]
+fn it_works_expiry_skips_stale_entries() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let geode_account = 2;
+        DegradeMode::<Test>::put(false);
+        register_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+        attest(attestor_account, geode_account);
+
+        // the attestor times out and the geode goes back to Registered
+        let timeout = 1 + ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER as u64 + 1;
+        run_to_block(timeout);
+        assert!(!pallet_attestor::Attestors::<Test>::contains_key(
+            attestor_account
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+
+        // the queue entry of the first registration is stale
+        let expiry = timeout + ATTESTATION_EXPIRY_BLOCK_NUMBER as u64;
+        run_to_block(expiry);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+
+        run_to_block(expiry + 1);
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_works_expiry_carry_over() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geodes = [10, 11, 12, 13];
+        MaxExpiriesPerBlock::set(&3);
+        DegradeMode::<Test>::put(false);
+        for geode in geodes.iter() {
+            provider_register_geode(provider, *geode);
+        }
+        let remaining = || {
+            geodes
+                .iter()
+                .filter(|geode| pallet_geode::Geodes::<Test>::contains_key(*geode))
+                .count()
+        };
+
+        // the empty block 0 and two geodes use up the cap
+        let expiry = 1 + ATTESTATION_EXPIRY_BLOCK_NUMBER as u64 + 1;
+        run_to_block(expiry);
+        assert_eq!(remaining(), 2);
+
+        run_to_block(expiry + 1);
+        assert_eq!(remaining(), 0);
+    });
+}
+
+#[test]
+fn it_works_expiry_within_hook_weight() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geodes = [10, 11, 12, 13];
+        // room for two geodes only, besides the weight kept for assignments
+        let geode_weight = <() as WeightInfo>::expire_geode(0);
+        let room = 2 * geode_weight + geode_weight / 2;
+        MaxHookWeight::set(&(<() as WeightInfo>::on_initialize() + room * 4 / 3));
+        DegradeMode::<Test>::put(false);
+        for geode in geodes.iter() {
+            provider_register_geode(provider, *geode);
+        }
+        let remaining = || {
+            geodes
+                .iter()
+                .filter(|geode| pallet_geode::Geodes::<Test>::contains_key(*geode))
+                .count()
+        };
+
+        let expiry = 1 + ATTESTATION_EXPIRY_BLOCK_NUMBER as u64 + 1;
+        run_to_block(expiry);
+        assert_eq!(remaining(), 2);
+
+        run_to_block(expiry + 1);
+        assert_eq!(remaining(), 0);
+    });
+}
+
+#[test]
+fn it_works_upgrade_enqueues_existing_items() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        let geode_account = 2;
+        DegradeMode::<Test>::put(false);
+        register_attestor(attestor_account);
+        provider_register_geode(attestor_account, geode_account);
+
+        // a chain which ran before the expiry queues existed
+        for queue in ExpiryQueue::ALL.iter() {
+            ExpiryCursors::<Test>::remove(queue);
+        }
+        pallet_geode::StateQueues::<Test>::remove(GeodeState::Registered, 0);
+        pallet_attestor::NotifyQueue::<Test>::remove(0);
+        System::set_block_number(5);
+
+        LivenessModule::on_runtime_upgrade();
+        assert_eq!(
+            LivenessModule::expiry_cursors(ExpiryQueue::RegisteredGeodes),
+            0
+        );
+        assert_eq!(LivenessModule::expiry_cursors(ExpiryQueue::Attestors), 0);
+        assert_eq!(LivenessModule::expiry_cursors(ExpiryQueue::Reports), 5);
+
+        // the items are queued only once
+        LivenessModule::on_runtime_upgrade();
+        assert_eq!(
+            pallet_geode::StateQueues::<Test>::get(GeodeState::Registered, 0),
+            vec![geode_account]
+        );
+        assert_eq!(
+            pallet_attestor::NotifyQueue::<Test>::get(0),
+            vec![attestor_account]
+        );
+
+        run_to_block(ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER as u64 + 1);
+        assert!(!pallet_attestor::Attestors::<Test>::contains_key(
+            attestor_account
+        ));
+        run_to_block(ATTESTATION_EXPIRY_BLOCK_NUMBER as u64 + 1);
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_works_degrade_mode_renews_geodes() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        let expiry = ATTESTATION_EXPIRY_BLOCK_NUMBER as u64;
+        assert!(LivenessModule::degrade_mode());
+        provider_register_geode(provider, geode_account);
+
+        // no attestor to attest the geode, it doesn't expire
+        run_to_block(1 + expiry + 1);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+
+        register_attestor(3);
+        run_to_block(1 + expiry + 2);
+        assert!(!LivenessModule::degrade_mode());
+
+        // the geode gets a full attestation expiry once the degrade mode ended
+        let renewed = 1 + expiry + 1 + expiry + 1;
+        run_to_block(renewed + expiry);
+        assert!(pallet_geode::Geodes::<Test>::contains_key(geode_account));
+        run_to_block(renewed + expiry + 1);
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_works_set_liveness_params() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        let params = LivenessParams {
+            attestation_expiry: 5,
+            ..Default::default()
+        };
+
+        assert_noop!(
+            LivenessModule::set_liveness_params(Origin::signed(provider), params.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            LivenessModule::set_liveness_params(
+                Origin::root(),
+                LivenessParams {
+                    report_approval_ratio: Percent::from_percent(0),
+                    ..Default::default()
+                }
+            ),
+            Error::<Test>::InvalidLivenessParams
+        );
+        assert_noop!(
+            LivenessModule::set_liveness_params(
+                Origin::root(),
+                LivenessParams {
+                    report_quorum: 0,
+                    ..Default::default()
+                }
+            ),
+            Error::<Test>::InvalidLivenessParams
+        );
+        assert_ok!(LivenessModule::set_liveness_params(
+            Origin::root(),
+            params.clone()
+        ));
+        assert_eq!(LivenessModule::liveness_params(), params);
+
+        // the new timeout applies
+        DegradeMode::<Test>::put(false);
+        provider_register_geode(provider, geode_account);
+        run_to_block(1 + 5);
+        assert!(pallet_geode::Geodes::<Test>::contains_key(geode_account));
+        run_to_block(1 + 5 + 1);
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_works_slash_geode_payout() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let stake = 10_000;
+        let deposit = 1000;
+        let (attestor_pair, attestor_account) = keyed_account(1);
+        let (geode_pair, geode_account) = keyed_account(2);
+        set_appeal_window(5);
+
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(provider),
+            vec![],
+            vec![],
+            stake
+        ));
+        register_keyed_attestor(attestor_account);
+        provider_register_geode(provider, geode_account);
+        attest(attestor_account, geode_account);
+        assert_ok!(GeodeModule::instantiate_geode(
+            &geode_account,
+            H256::repeat_byte(1)
+        ));
+        Timestamp::set_timestamp(SENT_AT + RESPONSE_TIMEOUT_MS + 2);
+        let issuance = Balances::total_issuance();
+
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(attestor_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &attestor_pair)
+        ));
+
+        // nothing moves during the appeal window
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+        assert_eq!(GeodeModule::providers(provider).stake, stake);
+        assert!(crate::PendingSlashes::<Test>::contains_key(geode_account));
+        pass_appeal_window();
+        assert!(!crate::PendingSlashes::<Test>::contains_key(geode_account));
+
+        // the deposit and 10% of the stake are slashed, 10% of it rewards the reporter, 40%
+        // compensates the order holder and the rest is burnt by the mock
+        assert!(
+            events().contains(&Event::liveness(crate::Event::SlashPayout(
+                geode_account,
+                2000,
+                200,
+                800,
+                1000
+            )))
+        );
+        assert_eq!(LivenessModule::provider_offences(provider), 1);
+        assert_eq!(GeodeModule::providers(provider).stake, stake - 1000);
+        assert_eq!(Balances::reserved_balance(provider), stake - 1000);
+        assert_eq!(
+            Balances::free_balance(attestor_account),
+            INIT_BALANCE - 100 + 200
+        );
+        assert_eq!(Balances::free_balance(ORDER_HOLDER), INIT_BALANCE + 800);
+        assert_eq!(Balances::total_issuance(), issuance - 1000);
+    });
+}
+
+#[test]
+fn it_works_slash_ratio() {
+    let params = SlashParams::default();
+    assert_eq!(
+        params.slash_ratio(&ReportType::Challenge, 1),
+        Percent::from_percent(10)
+    );
+    assert_eq!(
+        params.slash_ratio(&ReportType::Service, 2),
+        Percent::from_percent(10)
+    );
+    // repeat offenders lose everything at most
+    assert_eq!(
+        params.slash_ratio(&ReportType::Challenge, 20),
+        Percent::from_percent(100)
+    );
+}
+
+#[test]
+fn it_works_set_slash_params() {
+    new_test_ext().execute_with(|| {
+        let params = SlashParams {
+            challenge_slash: Percent::from_percent(50),
+            ..Default::default()
+        };
+        assert_noop!(
+            LivenessModule::set_slash_params(Origin::signed(1), params.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            LivenessModule::set_slash_params(
+                Origin::root(),
+                SlashParams {
+                    reporters_reward: Percent::from_percent(70),
+                    holder_compensation: Percent::from_percent(40),
+                    ..Default::default()
+                }
+            ),
+            Error::<Test>::InvalidSlashParams
+        );
+        assert_ok!(LivenessModule::set_slash_params(
+            Origin::root(),
+            params.clone()
+        ));
+        assert_eq!(LivenessModule::slash_params(), params);
+    });
+}
+
+#[test]
+fn it_works_slash_attestor_missed_heartbeat() {
+    new_test_ext().execute_with(|| {
+        let attestor_account = 1;
+        DegradeMode::<Test>::put(false);
+        register_attestor(attestor_account);
+
+        run_to_block(1 + ATTESTOR_NOTIFY_TIMEOUT_BLOCK_NUMBER as u64 + 1);
+        assert!(!pallet_attestor::Attestors::<Test>::contains_key(
+            attestor_account
+        ));
+        // 5% of the stake of 100 is slashed, the rest released
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), 0);
+        assert_eq!(Balances::reserved_balance(attestor_account), 0);
+        assert_eq!(Balances::free_balance(attestor_account), INIT_BALANCE - 5);
+        let events = events();
+        assert!(
+            events.contains(&Event::liveness(crate::Event::SlashAttestor(
+                attestor_account,
+                AttestorOffence::MissedHeartbeat,
+                5
+            )))
+        );
+        assert!(events.contains(&Event::pallet_attestor(
+            pallet_attestor::Event::AttestorUnbond(attestor_account, 95)
+        )));
+    });
+}
+
+#[test]
+fn it_works_slash_attestor_bad_attestation() {
+    new_test_ext().execute_with(|| {
+        let ([(_, cheap_account), (bonded_pair, bonded_account)], (geode_pair, geode_account)) =
+            setup_two_attestors(200);
+        set_appeal_window(5);
+
+        // the bonded attestor alone gets the geode slashed
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(bonded_account),
+            geode_account,
+            ReportType::Challenge as u8,
+            timed_out_evidence(&geode_pair, &bonded_pair)
+        ));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+        pass_appeal_window();
+
+        // the attestor which didn't report loses 10% of its stake
+        assert_eq!(AttestorModule::attestor_stake(cheap_account), 90);
+        assert_eq!(AttestorModule::attestor_stake(bonded_account), 300);
+    });
+}
+
+#[test]
+fn it_works_overturn_report() {
+    new_test_ext().execute_with(|| {
+        let ([(cheap_pair, cheap_account), (_, bonded_account)], (geode_pair, geode_account)) =
+            setup_two_attestors(200);
+        let report_type = ReportType::Challenge as u8;
+
+        assert_noop!(
+            LivenessModule::overturn_report(Origin::root(), geode_account, report_type),
+            Error::<Test>::InvalidReport
+        );
+        assert_ok!(LivenessModule::report_misconduct(
+            Origin::signed(cheap_account),
+            geode_account,
+            report_type,
+            timed_out_evidence(&geode_pair, &cheap_pair)
+        ));
+        assert_noop!(
+            LivenessModule::overturn_report(
+                Origin::signed(bonded_account),
+                geode_account,
+                report_type
+            ),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(LivenessModule::overturn_report(
+            Origin::root(),
+            geode_account,
+            report_type
+        ));
+        assert!(!crate::Reports::<Test>::contains_key((
+            geode_account,
+            report_type
+        )));
+        assert_eq!(AttestorModule::attestor_stake(cheap_account), 90);
+        assert_eq!(AttestorModule::attestor_stake(bonded_account), 300);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+    });
+}
+
+/// A transcript of the geode's enclave answering the reported challenge.
+fn counter_evidence(geode: &sr25519::Pair) -> CounterEvidence {
+    CounterEvidence {
+        geode_key: geode.public(),
+        transcript: Transcript {
+            request: NONCE.to_vec(),
+            response: Some(SignedResponse {
+                msg: NONCE.to_vec(),
+                signature: geode.sign(&json_bytes(&NONCE)),
+            }),
+            sent_at: SENT_AT,
+            received_at: SENT_AT + 1,
+        },
+    }
+}
+
+/// A provider's geode attested by a keyed attestor which reported it, the slash pending.
+fn setup_pending_slash() -> (u64, (sr25519::Pair, u64), (sr25519::Pair, u64)) {
+    let provider = 1;
+    let (attestor_pair, attestor_account) = keyed_account(1);
+    let (geode_pair, geode_account) = keyed_account(2);
+    set_appeal_window(5);
+    assert_ok!(GeodeModule::provider_register(
+        Origin::signed(provider),
+        vec![],
+        vec![],
+        10_000
+    ));
+    register_keyed_attestor(attestor_account);
+    provider_register_geode(provider, geode_account);
+    attest(attestor_account, geode_account);
+    Timestamp::set_timestamp(SENT_AT + RESPONSE_TIMEOUT_MS + 2);
+    assert_ok!(LivenessModule::report_misconduct(
+        Origin::signed(attestor_account),
+        geode_account,
+        ReportType::Challenge as u8,
+        timed_out_evidence(&geode_pair, &attestor_pair)
+    ));
+    (
+        provider,
+        (attestor_pair, attestor_account),
+        (geode_pair, geode_account),
+    )
+}
+
+#[test]
+fn it_works_appeal_slash_reversed() {
+    new_test_ext().execute_with(|| {
+        let (provider, (_, attestor_account), (geode_pair, geode_account)) = setup_pending_slash();
+
+        assert_noop!(
+            LivenessModule::appeal_slash(
+                Origin::signed(attestor_account),
+                geode_account,
+                counter_evidence(&geode_pair)
+            ),
+            Error::<Test>::NotProvider
+        );
+        assert_noop!(
+            LivenessModule::resolve_appeal(Origin::root(), geode_account, false),
+            Error::<Test>::NotAppealed
+        );
+        assert_ok!(LivenessModule::appeal_slash(
+            Origin::signed(provider),
+            geode_account,
+            counter_evidence(&geode_pair)
+        ));
+        assert_eq!(
+            LivenessModule::pending_slashes(geode_account).status,
+            SlashStatus::Appealed
+        );
+        assert_noop!(
+            LivenessModule::appeal_slash(
+                Origin::signed(provider),
+                geode_account,
+                counter_evidence(&geode_pair)
+            ),
+            Error::<Test>::AppealClosed
+        );
+
+        // appealed slashes wait for governance
+        pass_appeal_window();
+        assert_eq!(GeodeModule::providers(provider).stake, 10_000);
+
+        assert_noop!(
+            LivenessModule::resolve_appeal(Origin::signed(provider), geode_account, false),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(LivenessModule::resolve_appeal(
+            Origin::root(),
+            geode_account,
+            false
+        ));
+        assert!(!crate::PendingSlashes::<Test>::contains_key(geode_account));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+        assert_eq!(GeodeModule::providers(provider).stake, 10_000);
+        assert_eq!(LivenessModule::provider_offences(provider), 0);
+        // the reporter loses 10% of its stake
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), 90);
+    });
+}
+
+#[test]
+fn it_works_appeal_slash_confirmed() {
+    new_test_ext().execute_with(|| {
+        let (provider, (_, attestor_account), (geode_pair, geode_account)) = setup_pending_slash();
+
+        assert_ok!(LivenessModule::appeal_slash(
+            Origin::signed(provider),
+            geode_account,
+            counter_evidence(&geode_pair)
+        ));
+        assert_ok!(LivenessModule::resolve_appeal(
+            Origin::root(),
+            geode_account,
+            true
+        ));
+        assert!(!crate::PendingSlashes::<Test>::contains_key(geode_account));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+        assert_eq!(GeodeModule::providers(provider).stake, 9_000);
+        assert_eq!(LivenessModule::provider_offences(provider), 1);
+        assert_eq!(AttestorModule::attestor_stake(attestor_account), 100);
+    });
+}
+
+#[test]
+fn it_slash_pending_holds_geode() {
+    new_test_ext().execute_with(|| {
+        let new_provider = 2;
+        let deposit = 100;
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+        let (provider, _, (geode_pair, geode_account)) = setup_pending_slash();
+        assert_ok!(GeodeModule::provider_register(
+            Origin::signed(new_provider),
+            vec![],
+            vec![],
+            0
+        ));
+
+        // neither the geode nor its provider can leave with the funds
+        let assert_held = || {
+            assert_noop!(
+                GeodeModule::geode_remove(Origin::signed(provider), geode_account),
+                pallet_geode::Error::<Test>::GeodeSlashPending
+            );
+            assert_noop!(
+                GeodeModule::provider_remove_geodes(Origin::signed(provider), vec![geode_account]),
+                pallet_geode::Error::<Test>::GeodeSlashPending
+            );
+            assert_noop!(
+                GeodeModule::provider_transfer_geodes(
+                    Origin::signed(provider),
+                    vec![geode_account],
+                    new_provider
+                ),
+                pallet_geode::Error::<Test>::GeodeSlashPending
+            );
+            assert_noop!(
+                GeodeModule::provider_deregister(Origin::signed(provider)),
+                pallet_geode::Error::<Test>::ProviderHasGeodes
+            );
+        };
+        assert_held();
+
+        assert_ok!(LivenessModule::appeal_slash(
+            Origin::signed(provider),
+            geode_account,
+            counter_evidence(&geode_pair)
+        ));
+        pass_appeal_window();
+        assert_held();
+        assert_eq!(Balances::reserved_balance(provider), 10_000 + deposit);
+
+        assert_ok!(LivenessModule::resolve_appeal(
+            Origin::root(),
+            geode_account,
+            true
+        ));
+        assert_eq!(Balances::reserved_balance(provider), 9_000);
+        assert_ok!(GeodeModule::geode_remove(
+            Origin::signed(provider),
+            geode_account
+        ));
+    });
+}
+
+#[test]
+fn it_works_appeal_deadline() {
+    new_test_ext().execute_with(|| {
+        let deposit = 100;
+        assert_ok!(GeodeModule::set_geode_deposit(Origin::root(), deposit));
+        let (provider, _, (geode_pair, geode_account)) = setup_pending_slash();
+        let start = System::block_number();
+        let (appeal_window, unknown_expiry, appeal_deadline) = (5, 6, 10);
+        assert_ok!(LivenessModule::set_liveness_params(
+            Origin::root(),
+            LivenessParams {
+                appeal_window,
+                unknown_expiry,
+                appeal_deadline,
+                ..Default::default()
+            }
+        ));
+        assert_ok!(LivenessModule::appeal_slash(
+            Origin::signed(provider),
+            geode_account,
+            counter_evidence(&geode_pair)
+        ));
+
+        // the Unknown geode outlives its expiry while the appeal is undecided
+        let deadline = start + (appeal_window + appeal_deadline) as u64;
+        run_to_block(deadline);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Unknown
+        );
+        assert_eq!(Balances::reserved_balance(provider), 10_000 + deposit);
+
+        // governance can't decide it anymore once the deadline passed
+        System::set_block_number(deadline + 1);
+        assert_noop!(
+            LivenessModule::resolve_appeal(Origin::root(), geode_account, false),
+            Error::<Test>::AppealDeadlinePassed
+        );
+
+        // and the slash gets confirmed
+        LivenessModule::on_initialize(deadline + 1);
+        assert!(!crate::PendingSlashes::<Test>::contains_key(geode_account));
+        assert_eq!(GeodeModule::providers(provider).stake, 9_000);
+        assert_eq!(Balances::reserved_balance(provider), 9_000);
+        assert_eq!(LivenessModule::provider_offences(provider), 1);
+
+        // the geode expires once the slash is over
+        run_to_block(deadline + 2 * (unknown_expiry as u64 + 1));
+        assert!(!pallet_geode::Geodes::<Test>::contains_key(geode_account));
+    });
+}
+
+#[test]
+fn it_appeal_slash_invalid() {
+    new_test_ext().execute_with(|| {
+        let (provider, _, (geode_pair, geode_account)) = setup_pending_slash();
+        let (other_pair, _) = keyed_account(3);
+
+        assert_noop!(
+            LivenessModule::appeal_slash(
+                Origin::signed(provider),
+                3,
+                counter_evidence(&geode_pair)
+            ),
+            Error::<Test>::NoPendingSlash
+        );
+        // about another geode
+        assert_noop!(
+            LivenessModule::appeal_slash(
+                Origin::signed(provider),
+                geode_account,
+                counter_evidence(&other_pair)
+            ),
+            Error::<Test>::InvalidEvidence
+        );
+        // the enclave didn't answer
+        let mut unanswered = counter_evidence(&geode_pair);
+        unanswered.transcript.response = None;
+        assert_noop!(
+            LivenessModule::appeal_slash(Origin::signed(provider), geode_account, unanswered),
+            Error::<Test>::InvalidEvidence
+        );
+        // answering another challenge than the reported one
+        let mut other_request = counter_evidence(&geode_pair);
+        other_request.transcript.request = vec![4, 3, 2, 1];
+        other_request.transcript.response = Some(SignedResponse {
+            msg: vec![4, 3, 2, 1],
+            signature: geode_pair.sign(&json_bytes(&[4, 3, 2, 1])),
+        });
+        assert_noop!(
+            LivenessModule::appeal_slash(Origin::signed(provider), geode_account, other_request),
+            Error::<Test>::InvalidEvidence
+        );
+        // not echoing the reported nonce
+        let mut wrong_echo = counter_evidence(&geode_pair);
+        wrong_echo.transcript.response = Some(SignedResponse {
+            msg: vec![4, 3, 2, 1],
+            signature: geode_pair.sign(&json_bytes(&[4, 3, 2, 1])),
+        });
+        assert_noop!(
+            LivenessModule::appeal_slash(Origin::signed(provider), geode_account, wrong_echo),
+            Error::<Test>::InvalidEvidence
+        );
+
+        // too late
+        run_to_block(System::block_number() + 6);
+        assert_noop!(
+            LivenessModule::appeal_slash(
+                Origin::signed(provider),
+                geode_account,
+                counter_evidence(&geode_pair)
+            ),
+            Error::<Test>::AppealClosed
+        );
+    });
+}
+
+/// Register funded attestors at the accounts of the keys seeded with `seeds`.
+fn register_keyed_attestors(seeds: &[u8]) -> Vec<u64> {
+    seeds
+        .iter()
+        .map(|seed| {
+            let (_, account) = keyed_account(*seed);
+            register_keyed_attestor(account);
+            account
+        })
+        .collect()
+}
+
+#[test]
+fn it_assign_attestors_waits_for_fresh_randomness() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        register_keyed_attestors(&[3]);
+        provider_register_geode(provider, geode_account);
+
+        // the randomness was known when the geode got registered
+        RandomnessAge::set(&5);
+        run_to_block(2);
+        assert!(LivenessModule::geode_assignments(geode_account).is_empty());
+
+        // the geode is retried once the randomness got renewed
+        RandomnessAge::set(&0);
+        run_to_block(3 + ASSIGNMENT_RETRY_BLOCK_NUMBER as u64);
+        assert_eq!(LivenessModule::geode_assignments(geode_account).len(), 1);
+    });
+}
+
+#[test]
+fn it_works_geode_remove_forgets_liveness_state() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let (geode_account, other_geode) = (2, 3);
+        let attestors = register_keyed_attestors(&[4, 5]);
+        assert_ok!(LivenessModule::set_min_attestor_num(Origin::root(), 2));
+        provider_register_geode(provider, geode_account);
+        provider_register_geode(provider, other_geode);
+        run_to_block(2);
+        assert_ok!(LivenessModule::attestor_attest_geode(
+            Origin::signed(attestors[0]),
+            geode_account,
+            attestation_report(geode_account)
+        ));
+        assert_ok!(LivenessModule::set_geode_attestor_target(
+            Origin::signed(provider),
+            geode_account,
+            3
+        ));
+        crate::GeodeHealth::<Test>::insert(geode_account, 50);
+        assert!(crate::GeodeMeasurements::<Test>::contains_key(
+            geode_account
+        ));
+
+        assert_ok!(GeodeModule::geode_remove(
+            Origin::signed(provider),
+            geode_account
+        ));
+        assert!(!GeodeAssignments::<Test>::contains_key(geode_account));
+        assert!(!AttestationNonces::<Test>::contains_key(geode_account));
+        assert_eq!(
+            LivenessModule::attestor_assignments(attestors[1])
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![other_geode]
+        );
+        assert!(!crate::GeodeAttestorTargets::<Test>::contains_key(
+            geode_account
+        ));
+        assert!(!crate::GeodeHealth::<Test>::contains_key(geode_account));
+        assert!(!crate::GeodeMeasurements::<Test>::contains_key(
+            geode_account
+        ));
+        assert_eq!(crate::MeasurementGeodes::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn it_works_assign_attestors() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        // the provider is an attestor as well
+        register_attestor(provider);
+        let attestors = register_keyed_attestors(&[3, 4, 5]);
+        assert_ok!(LivenessModule::set_min_attestor_num(Origin::root(), 2));
+        provider_register_geode(provider, geode_account);
+
+        // attestors are assigned in the next block
+        assert!(LivenessModule::geode_assignments(geode_account).is_empty());
+        run_to_block(2);
+        let assigned = LivenessModule::geode_assignments(geode_account);
+        assert_eq!(assigned.len(), 2);
+        assert!(!assigned.contains(&provider));
+        assert!(events().into_iter().any(|event| matches!(
+            event,
+            Event::liveness(crate::Event::AttestorsAssigned(geode, _)) if geode == geode_account
+        )));
+
+        // only assigned attestors can attest
+        let unassigned = attestors
+            .iter()
+            .find(|attestor| !assigned.contains(attestor))
+            .unwrap();
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(*unassigned),
+                geode_account,
+                attestation_report(geode_account)
+            ),
+            Error::<Test>::NotAssigned
+        );
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(provider),
+                geode_account,
+                attestation_report(geode_account)
+            ),
+            Error::<Test>::NotAssigned
+        );
+        for attestor in assigned.iter() {
+            assert_ok!(LivenessModule::attestor_attest_geode(
+                Origin::signed(*attestor),
+                geode_account,
+                attestation_report(geode_account)
+            ));
+            assert!(!LivenessModule::attestor_assignments(attestor).contains(&geode_account));
+        }
+        assert!(!GeodeAssignments::<Test>::contains_key(geode_account));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+    });
+}
+
+#[test]
+fn it_works_assign_attestors_load_balanced() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geodes = [10, 11, 12, 13];
+        let attestors = register_keyed_attestors(&[3, 4]);
+        for geode in geodes.iter() {
+            provider_register_geode(provider, *geode);
+        }
+        run_to_block(2);
+
+        // each attestor gets half of the geodes
+        for attestor in attestors.iter() {
+            assert_eq!(LivenessModule::attestor_assignments(attestor).len(), 2);
+        }
+        for geode in geodes.iter() {
+            assert_eq!(LivenessModule::geode_assignments(geode).len(), 1);
+        }
+    });
+}
+
+#[test]
+fn it_works_attestor_capacity() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geodes = [10, 11, 12];
+        let attestors = register_keyed_attestors(&[3, 4]);
+        let (limited, other) = (attestors[0], attestors[1]);
+        assert_ok!(AttestorModule::attestor_update_metadata(
+            Origin::signed(limited),
+            pallet_attestor::AttestorMetadata {
+                capacity: 1,
+                ..Default::default()
+            }
+        ));
+        for geode in geodes.iter() {
+            provider_register_geode(provider, *geode);
+        }
+
+        // attestors at their capacity aren't assigned
+        run_to_block(2);
+        assert_eq!(LivenessModule::attestor_assignments(limited).len(), 1);
+        assert_eq!(LivenessModule::attestor_assignments(other).len(), 2);
+
+        // assigned geodes count in the loads
+        let mut loads = AttestorModule::attestor_loads();
+        loads.sort();
+        let mut expected = vec![(limited, 1, 1), (other, 2, 0)];
+        expected.sort();
+        assert_eq!(loads, expected);
+
+        // nor can they attest beyond it
+        let assigned = *LivenessModule::attestor_assignments(limited)
+            .iter()
+            .next()
+            .unwrap();
+        assert_ok!(LivenessModule::attestor_attest_geode(
+            Origin::signed(limited),
+            assigned,
+            attestation_report(assigned)
+        ));
+        let extra = *geodes.iter().find(|geode| **geode != assigned).unwrap();
+        LivenessModule::assign_attestor(&extra, &limited);
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(limited),
+                extra,
+                attestation_report(extra)
+            ),
+            pallet_attestor::Error::<Test>::AttestorAtCapacity
+        );
+    });
+}
+
+#[test]
+fn it_works_reassign_on_attestor_exit() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let (assigned_geode, attested_geode) = (10, 11);
+        let attestors = register_keyed_attestors(&[3, 4]);
+        let (first, second) = (attestors[0], attestors[1]);
+        provider_register_geode(provider, assigned_geode);
+        provider_register_geode(provider, attested_geode);
+        LivenessModule::assign_attestor(&assigned_geode, &first);
+        attest(first, attested_geode);
+
+        // the geodes of the exiting attestor, attested or not, go to the other one
+        assert_ok!(LivenessModule::attestor_exit(Origin::signed(first)));
+        assert!(LivenessModule::attestor_assignments(first).is_empty());
+        assert!(LivenessModule::geode_assignments(assigned_geode).is_empty());
+        run_to_block(2);
+        for geode in [assigned_geode, attested_geode].iter() {
+            assert_eq!(
+                LivenessModule::geode_assignments(geode)
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                vec![second]
+            );
+        }
+    });
+}
+
+#[test]
+fn it_works_attestor_unbonding() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let (geode_account, other_geode) = (10, 11);
+        let attestors = register_keyed_attestors(&[3, 4]);
+        let (first, second) = (attestors[0], attestors[1]);
+        provider_register_geode(provider, geode_account);
+        attest(first, geode_account);
+        set_unbonding_period(5);
+        events();
+
+        assert_ok!(LivenessModule::attestor_exit(Origin::signed(first)));
+        assert_eq!(LivenessModule::unbonding(first), Some(1));
+        assert_eq!(
+            events(),
+            [Event::liveness(crate::Event::AttestorUnbonding(first, 7))]
+        );
+        assert_noop!(
+            LivenessModule::attestor_exit(Origin::signed(first)),
+            Error::<Test>::AttestorUnbonding
+        );
+
+        // the unbonding attestor takes no new geodes
+        provider_register_geode(provider, other_geode);
+        LivenessModule::assign_attestor(&other_geode, &first);
+        assert_noop!(
+            LivenessModule::attestor_attest_geode(
+                Origin::signed(first),
+                other_geode,
+                attestation_report(other_geode)
+            ),
+            Error::<Test>::AttestorUnbonding
+        );
+
+        // its geodes are handed over while it keeps attesting them
+        run_to_block(2);
+        assert!(LivenessModule::geode_assignments(geode_account).contains(&second));
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+        attest(second, geode_account);
+        assert_eq!(Balances::reserved_balance(first), 100);
+
+        // the attestor is removed and its stake released at the end, the geode stays attested
+        run_to_block(7);
+        assert!(!pallet_attestor::Attestors::<Test>::contains_key(first));
+        assert_eq!(LivenessModule::unbonding(first), None);
+        assert_eq!(AttestorModule::attestor_stake(first), 0);
+        assert_eq!(Balances::reserved_balance(first), 0);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+    });
+}
+
+/// Send a heartbeat of the attestor with the results of its geodes.
+fn send_heartbeat(pair: &sr25519::Pair, attestor: u64, nonce: u64, results: Vec<u8>) {
+    let block_number = System::block_number() - 1;
+    frame_system::BlockHash::<Test>::insert(block_number, H256::repeat_byte(1));
+    let message = pallet_attestor::HeartbeatOf::<Test> {
+        version: pallet_attestor::HEARTBEAT_VERSION,
+        attestor: pair.public().0,
+        block_number: block_number as u32,
+        block_hash: H256::repeat_byte(1),
+        nonce,
+        geode_digest: AttestorModule::geode_digest(&AttestorModule::attestors(attestor).geodes),
+        results,
+    }
+    .encode();
+    let signature = pair.sign(&message);
+    assert_ok!(AttestorModule::attestor_notify_chain(
+        Origin::none(),
+        message,
+        signature.0
+    ));
+}
+
+#[test]
+fn it_works_geode_health() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let (passing, failing) = (10, 11);
+        let (pair, attestor) = keyed_account(3);
+        register_keyed_attestor(attestor);
+        provider_register_geode(provider, passing);
+        provider_register_geode(provider, failing);
+        attest(attestor, passing);
+        attest(attestor, failing);
+        run_to_block(2);
+        assert_eq!(LivenessModule::geode_health(failing), MAX_HEALTH);
+
+        // the geodes are in the bitmap in order, only the first one passed
+        send_heartbeat(&pair, attestor, 1, vec![0b01]);
+        assert_eq!(AttestorModule::attestor_last_notification(attestor), 2);
+        assert_eq!(LivenessModule::geode_health(passing), MAX_HEALTH);
+        assert_eq!(
+            LivenessModule::geode_health(failing),
+            MAX_HEALTH - HEALTH_PENALTY
+        );
+
+        events();
+        for nonce in 2..=4 {
+            send_heartbeat(&pair, attestor, nonce, vec![0b01]);
+        }
+        assert_eq!(LivenessModule::geode_health(failing), 0);
+        assert_eq!(
+            events(),
+            [Event::liveness(crate::Event::GeodeUnhealthy(failing))]
+        );
+
+        // and recovers once it passes again
+        send_heartbeat(&pair, attestor, 5, vec![0b11]);
+        assert_eq!(LivenessModule::geode_health(failing), HEALTH_RECOVERY);
+    });
+}
+
+#[test]
+fn it_works_assign_attestors_retry() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        provider_register_geode(provider, geode_account);
+
+        // no attestor to assign yet
+        run_to_block(2);
+        assert!(LivenessModule::geode_assignments(geode_account).is_empty());
+        let attestors = register_keyed_attestors(&[3]);
+
+        let retry = 2 + ASSIGNMENT_RETRY_BLOCK_NUMBER as u64;
+        run_to_block(retry);
+        assert!(LivenessModule::geode_assignments(geode_account).is_empty());
+        run_to_block(retry + 1);
+        assert!(LivenessModule::geode_assignments(geode_account).contains(&attestors[0]));
+    });
+}
+
+#[test]
+fn it_works_geode_attestor_target() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        let attestors = register_keyed_attestors(&[3, 4, 5]);
+        provider_register_geode(provider, geode_account);
+
+        assert_noop!(
+            LivenessModule::set_geode_attestor_target(Origin::signed(2), geode_account, 2),
+            pallet_geode::Error::<Test>::NoRight
+        );
+        assert_ok!(LivenessModule::set_geode_attestor_target(
+            Origin::signed(provider),
+            geode_account,
+            2
+        ));
+        assert_eq!(LivenessModule::attestor_target(&geode_account), 2);
+
+        // attested once both attestors vouched for it
+        attest(attestors[0], geode_account);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+        attest(attestors[1], geode_account);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+
+        // raising the target degrades the geode and assigns the missing attestor
+        assert_ok!(LivenessModule::set_geode_attestor_target(
+            Origin::signed(provider),
+            geode_account,
+            3
+        ));
+        assert_eq!(
+            GeodeModule::geode_history(geode_account)
+                .last()
+                .unwrap()
+                .cause,
+            TransitionCause::AttestorTarget
+        );
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+        run_to_block(2);
+        assert!(LivenessModule::geode_assignments(geode_account).contains(&attestors[2]));
+        attest(attestors[2], geode_account);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+    });
+}
+
+#[test]
+fn it_works_attestor_target_classes() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        let attestors = register_keyed_attestors(&[3]);
+        provider_register_geode(provider, geode_account);
+        attest(attestors[0], geode_account);
+        let classes = vec![("cores>=4".parse().unwrap(), 2)];
+
+        assert_noop!(
+            LivenessModule::set_attestor_target_classes(Origin::signed(provider), classes.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            LivenessModule::set_attestor_target_classes(
+                Origin::root(),
+                vec![(Default::default(), 2); MAX_TARGET_CLASSES as usize + 1]
+            ),
+            Error::<Test>::TooManyTargetClasses
+        );
+        assert_ok!(LivenessModule::set_attestor_target_classes(
+            Origin::root(),
+            classes
+        ));
+
+        // the geode doesn't match the class
+        run_to_block(2);
+        assert!(LivenessModule::target_sweep().is_none());
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Attested
+        );
+
+        // until its properties change
+        assert_ok!(GeodeModule::update_geode_props(
+            Origin::signed(provider),
+            geode_account,
+            b"cores".to_vec(),
+            b"8".to_vec()
+        ));
+        assert_eq!(LivenessModule::attestor_target(&geode_account), 2);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Registered
+        );
+    });
+}
+
+#[test]
+fn it_works_attestor_target_sweep() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geodes = [10, 11];
+        let attestors = register_keyed_attestors(&[3]);
+        MaxExpiriesPerBlock::set(&1);
+        for geode in geodes.iter() {
+            provider_register_geode(provider, *geode);
+            attest(attestors[0], *geode);
+        }
+        let registered = || {
+            geodes
+                .iter()
+                .filter(|geode| GeodeModule::geodes(*geode).state == GeodeState::Registered)
+                .count()
+        };
+
+        // the geodes are checked one per block
+        assert_ok!(LivenessModule::set_min_attestor_num(Origin::root(), 2));
+        assert_eq!(registered(), 0);
+        run_to_block(2);
+        assert_eq!(registered(), 1);
+        run_to_block(3);
+        assert_eq!(registered(), 2);
+        run_to_block(4);
+        assert!(LivenessModule::target_sweep().is_none());
+    });
+}
+
+#[test]
+fn it_works_degraded_instantiated_recovers() {
+    new_test_ext().execute_with(|| {
+        let provider = 1;
+        let geode_account = 2;
+        let attestors = register_keyed_attestors(&[3, 4]);
+        provider_register_geode(provider, geode_account);
+        attest(attestors[0], geode_account);
+        assert_ok!(GeodeModule::instantiate_geode(
+            &geode_account,
+            H256::repeat_byte(1)
+        ));
+
+        // the attestor is removed at once, e.g. for missing heartbeats
+        LivenessModule::do_attestor_exit(&attestors[0]);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::DegradedInstantiated
+        );
+
+        // the other attestor takes over
+        run_to_block(2);
+        attest(attestors[1], geode_account);
+        assert_eq!(
+            GeodeModule::geodes(geode_account).state,
+            GeodeState::Instantiated
         );
     });
 }
@@ -0,0 +1,776 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of attestation reports on the host, the runtime has no X.509, RSA or ECDSA
+//! support of its own. Nodes register `remote_attestation::HostFunctions` with their executor.
+
+use crate::report::{QuoteStatus, ReportVerifier};
+use sp_runtime_interface::runtime_interface;
+
+/// Verifies the certificate chains and signatures of attestation reports natively.
+#[runtime_interface]
+pub trait RemoteAttestation {
+    /// Whether `signing_cert` is issued by `root_cert` and valid at `now`, and the base64
+    /// `signature` of the IAS `report` verifies with it.
+    fn verify_ias(
+        root_cert: &[u8],
+        signing_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: u64,
+    ) -> bool {
+        native::verify_ias(root_cert, signing_cert, report, signature, now).is_some()
+    }
+
+    /// Status of the platform which produced the DCAP `quote`, if the quote is signed by an
+    /// attestation key the quoting enclave vouches for, the PCK certificate of the quoting
+    /// enclave and the collateral chain up to `root_cert`, and the collateral is current.
+    fn verify_dcap(
+        root_cert: &[u8],
+        quote: &[u8],
+        collateral: &[u8],
+        now: u64,
+    ) -> Option<QuoteStatus> {
+        native::verify_dcap(root_cert, quote, collateral, now)
+    }
+}
+
+/// Verifies reports through the `remote_attestation` host functions. Reports of genuine
+/// enclaves can't be made up, benchmarks have to run with a verifier which accepts
+/// `benchmark_report`.
+pub struct HostReportVerifier;
+
+impl ReportVerifier for HostReportVerifier {
+    fn verify_ias(
+        root_cert: &[u8],
+        signing_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: u64,
+    ) -> bool {
+        remote_attestation::verify_ias(root_cert, signing_cert, report, signature, now)
+    }
+
+    fn verify_dcap(
+        root_cert: &[u8],
+        quote: &[u8],
+        collateral: &[u8],
+        now: u64,
+    ) -> Option<QuoteStatus> {
+        remote_attestation::verify_dcap(root_cert, quote, collateral, now)
+    }
+}
+
+#[cfg(feature = "std")]
+mod native {
+    use crate::report::{
+        base64_decode, parse_timestamp, DcapCollateral, QuoteStatus, QUOTE_BODY_LENGTH,
+    };
+    use codec::Decode;
+    use serde_json::Value;
+    use webpki::{EndEntityCert, SignatureAlgorithm, TLSServerTrustAnchors, Time};
+
+    /// Version and attestation key type of the DCAP quotes accepted, ECDSA P-256 keys.
+    const QUOTE_VERSION: u16 = 3;
+    const ECDSA_P256_KEY_TYPE: u16 = 2;
+    /// Certification data type of a PEM encoded PCK certificate chain.
+    const PCK_CERT_CHAIN_TYPE: u16 = 5;
+    /// Length of the report of the quoting enclave, and offsets of its fields.
+    const REPORT_LENGTH: usize = 384;
+    const MISC_SELECT_OFFSET: usize = 16;
+    const ATTRIBUTES_OFFSET: usize = 48;
+    const MR_SIGNER_OFFSET: usize = 128;
+    const ISV_PROD_ID_OFFSET: usize = 256;
+    const ISV_SVN_OFFSET: usize = 258;
+    const REPORT_DATA_OFFSET: usize = 320;
+    /// OID of the SGX extension of PCK certificates, 1.2.840.113741.1.13.1, its TCB entry is
+    /// the arc 2 below it and its FMSPC entry the arc 4.
+    const SGX_EXTENSION_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+    const DER_BOOLEAN: u8 = 0x01;
+    const DER_EXTENSIONS: u8 = 0xa3;
+
+    pub fn verify_ias(
+        root_cert: &[u8],
+        signing_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: u64,
+    ) -> Option<()> {
+        let algorithms = [&webpki::RSA_PKCS1_2048_8192_SHA256];
+        let signer = verified_cert(root_cert, signing_cert, &[], &algorithms, now)?;
+        let signature = base64_decode(signature)?;
+        signer
+            .verify_signature(&webpki::RSA_PKCS1_2048_8192_SHA256, report, &signature)
+            .ok()
+    }
+
+    pub fn verify_dcap(
+        root_cert: &[u8],
+        quote: &[u8],
+        collateral: &[u8],
+        now: u64,
+    ) -> Option<QuoteStatus> {
+        let quote = DcapQuote::parse(quote)?;
+        let collateral = DcapCollateral::decode(&mut &collateral[..]).ok()?;
+        let algorithms = [&webpki::ECDSA_P256_SHA256];
+
+        // the PCK certificate signs the report of the quoting enclave, which vouches for the
+        // attestation key signing the quote
+        let certs = pem_certs(quote.certification_data)?;
+        let (pck_cert, intermediates) = certs.split_first()?;
+        let intermediates = intermediates.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let pck = verified_cert(root_cert, pck_cert, &intermediates, &algorithms, now)?;
+        pck.verify_signature(
+            &webpki::ECDSA_P256_SHA256,
+            quote.qe_report,
+            &der_signature(quote.qe_report_signature)?,
+        )
+        .ok()?;
+        quote.verify_attestation_key()?;
+
+        // the TCB signing certificate signs the collateral the statuses are read from
+        let tcb_signer = verified_cert(
+            root_cert,
+            &collateral.tcb_signing_cert,
+            &[],
+            &algorithms,
+            now,
+        )?;
+        for (data, signature) in [
+            (&collateral.tcb_info, &collateral.tcb_info_signature),
+            (&collateral.qe_identity, &collateral.qe_identity_signature),
+        ]
+        .iter()
+        {
+            tcb_signer
+                .verify_signature(&webpki::ECDSA_P256_SHA256, data, &der_signature(signature)?)
+                .ok()?;
+        }
+        let qe_identity = serde_json::from_slice(&collateral.qe_identity).ok()?;
+        let tcb_info = serde_json::from_slice(&collateral.tcb_info).ok()?;
+        match quoting_enclave_status(&qe_identity, quote.qe_report, now)? {
+            QuoteStatus::Ok => platform_status(&tcb_info, &PckTcb::parse(pck_cert)?, now),
+            status => Some(status),
+        }
+    }
+
+    /// The certificate if it chains up to `root_cert` through `intermediates` and is valid at
+    /// `now`.
+    fn verified_cert<'a>(
+        root_cert: &[u8],
+        cert: &'a [u8],
+        intermediates: &[&[u8]],
+        algorithms: &[&SignatureAlgorithm],
+        now: u64,
+    ) -> Option<EndEntityCert<'a>> {
+        let anchors = [webpki::trust_anchor_util::cert_der_as_trust_anchor(root_cert).ok()?];
+        let cert = EndEntityCert::from(cert).ok()?;
+        cert.verify_is_valid_tls_server_cert(
+            algorithms,
+            &TLSServerTrustAnchors(&anchors),
+            intermediates,
+            Time::from_seconds_since_unix_epoch(now),
+        )
+        .ok()?;
+        Some(cert)
+    }
+
+    /// Reads the little endian fields of a quote one after the other.
+    struct Reader<'a>(&'a [u8]);
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+            if self.0.len() < len {
+                return None;
+            }
+            let (head, rest) = self.0.split_at(len);
+            self.0 = rest;
+            Some(head)
+        }
+
+        fn u16(&mut self) -> Option<u16> {
+            self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            self.take(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        }
+    }
+
+    /// The signed parts of a v3 DCAP quote.
+    struct DcapQuote<'a> {
+        /// Header and report body of the quote.
+        body: &'a [u8],
+        /// Raw `r || s` signature over the body.
+        signature: &'a [u8],
+        /// Raw `x || y` public key signing the quote.
+        attestation_key: &'a [u8],
+        qe_report: &'a [u8],
+        qe_report_signature: &'a [u8],
+        qe_auth_data: &'a [u8],
+        certification_data: &'a [u8],
+    }
+
+    impl<'a> DcapQuote<'a> {
+        fn parse(quote: &'a [u8]) -> Option<Self> {
+            let mut reader = Reader(quote);
+            let body = reader.take(QUOTE_BODY_LENGTH)?;
+            if u16::from_le_bytes([body[0], body[1]]) != QUOTE_VERSION
+                || u16::from_le_bytes([body[2], body[3]]) != ECDSA_P256_KEY_TYPE
+            {
+                return None;
+            }
+            let len = reader.u32()? as usize;
+            let mut reader = Reader(reader.take(len)?);
+            let signature = reader.take(64)?;
+            let attestation_key = reader.take(64)?;
+            let qe_report = reader.take(REPORT_LENGTH)?;
+            let qe_report_signature = reader.take(64)?;
+            let len = reader.u16()? as usize;
+            let qe_auth_data = reader.take(len)?;
+            if reader.u16()? != PCK_CERT_CHAIN_TYPE {
+                return None;
+            }
+            let len = reader.u32()? as usize;
+            let certification_data = reader.take(len)?;
+            Some(DcapQuote {
+                body,
+                signature,
+                attestation_key,
+                qe_report,
+                qe_report_signature,
+                qe_auth_data,
+                certification_data,
+            })
+        }
+
+        /// The quoting enclave bound the attestation key to its report, and the key signed
+        /// the quote.
+        fn verify_attestation_key(&self) -> Option<()> {
+            let key_hash = ring::digest::digest(
+                &ring::digest::SHA256,
+                &[self.attestation_key, self.qe_auth_data].concat(),
+            );
+            if key_hash.as_ref() != &self.qe_report[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + 32] {
+                return None;
+            }
+            let mut key = vec![0x04];
+            key.extend_from_slice(self.attestation_key);
+            ring::signature::UnparsedPublicKey::new(&ring::signature::ECDSA_P256_SHA256_FIXED, key)
+                .verify(self.body, self.signature)
+                .ok()
+        }
+    }
+
+    /// TCB of the platform and its FMSPC, from the SGX extension of its PCK certificate.
+    #[derive(Default, Debug, PartialEq)]
+    struct PckTcb {
+        components: [u8; 16],
+        pce_svn: u16,
+        fmspc: Vec<u8>,
+    }
+
+    impl PckTcb {
+        fn parse(cert: &[u8]) -> Option<Self> {
+            let (_, certificate, _) = der_next(cert)?;
+            let (_, mut fields, _) = der_next(certificate)?;
+            let extensions = loop {
+                let (tag, content, rest) = der_next(fields)?;
+                if tag == DER_EXTENSIONS {
+                    break der_next(content)?.1;
+                }
+                fields = rest;
+            };
+            let entries = der_children(extensions)?
+                .into_iter()
+                .find_map(|extension| {
+                    let (_, extension, _) = der_next(extension)?;
+                    let (_, oid, rest) = der_next(extension)?;
+                    if oid != SGX_EXTENSION_OID {
+                        return None;
+                    }
+                    // skip the critical flag
+                    let (tag, value, rest) = der_next(rest)?;
+                    let value = if tag == DER_BOOLEAN {
+                        der_next(rest)?.1
+                    } else {
+                        value
+                    };
+                    Some(der_next(value)?.1)
+                })?;
+
+            let mut tcb = PckTcb::default();
+            for entry in der_children(entries)? {
+                let (oid, value) = der_pair(entry)?;
+                match oid.strip_prefix(SGX_EXTENSION_OID) {
+                    Some([2]) => {
+                        for component in der_children(value)? {
+                            let (oid, svn) = der_pair(component)?;
+                            match oid.strip_prefix(SGX_EXTENSION_OID) {
+                                Some([2, n @ 1..=16]) => {
+                                    tcb.components[*n as usize - 1] = der_uint(svn)? as u8
+                                }
+                                Some([2, 17]) => tcb.pce_svn = der_uint(svn)?,
+                                _ => {}
+                            }
+                        }
+                    }
+                    Some([4]) => tcb.fmspc = value.to_vec(),
+                    _ => {}
+                }
+            }
+            Some(tcb)
+        }
+    }
+
+    /// Status of the quoting enclave, if it is the one the identity describes and the
+    /// identity is current.
+    fn quoting_enclave_status(identity: &Value, qe_report: &[u8], now: u64) -> Option<QuoteStatus> {
+        let hex = |key: &str| hex_decode(identity.get(key)?.as_str()?);
+        let masked = |value: &[u8], mask: &[u8]| {
+            value
+                .iter()
+                .zip(mask)
+                .map(|(v, m)| v & m)
+                .collect::<Vec<_>>()
+        };
+        let field = |offset: usize, len: usize| &qe_report[offset..offset + len];
+        let u16_at = |offset: usize| u16::from_le_bytes([qe_report[offset], qe_report[offset + 1]]);
+
+        // the identity writes the misc select big endian
+        let mut misc_select = field(MISC_SELECT_OFFSET, 4).to_vec();
+        misc_select.reverse();
+        if !is_current(identity, now)?
+            || masked(&misc_select, &hex("miscselectMask")?[..]) != hex("miscselect")?
+            || masked(field(ATTRIBUTES_OFFSET, 16), &hex("attributesMask")?[..])
+                != hex("attributes")?
+            || field(MR_SIGNER_OFFSET, 32) != &hex("mrsigner")?[..]
+            || identity.get("isvprodid")?.as_u64()? != u16_at(ISV_PROD_ID_OFFSET) as u64
+        {
+            return None;
+        }
+        let isv_svn = u16_at(ISV_SVN_OFFSET) as u64;
+        let level = identity
+            .get("tcbLevels")?
+            .as_array()?
+            .iter()
+            .find(|level| {
+                level["tcb"]["isvsvn"]
+                    .as_u64()
+                    .map_or(false, |svn| svn <= isv_svn)
+            })?;
+        tcb_status(level.get("tcbStatus")?.as_str()?)
+    }
+
+    /// Status of the first TCB level of the platform's FMSPC its TCB reaches, if the TCB info
+    /// is current. Both the version 2 and the version 3 layouts of the levels are read.
+    fn platform_status(tcb_info: &Value, tcb: &PckTcb, now: u64) -> Option<QuoteStatus> {
+        if !is_current(tcb_info, now)? || hex_decode(tcb_info.get("fmspc")?.as_str()?)? != tcb.fmspc
+        {
+            return None;
+        }
+        let level = tcb_info
+            .get("tcbLevels")?
+            .as_array()?
+            .iter()
+            .find(|level| {
+                let level = &level["tcb"];
+                let components = (0..16).map(|i| match level.get("sgxtcbcomponents") {
+                    Some(components) => components[i]["svn"].as_u64(),
+                    None => level[format!("sgxtcbcomp{:02}svn", i + 1).as_str()].as_u64(),
+                });
+                components
+                    .zip(tcb.components.iter())
+                    .all(|(svn, own)| svn.map_or(false, |svn| svn <= *own as u64))
+                    && level["pcesvn"]
+                        .as_u64()
+                        .map_or(false, |svn| svn <= tcb.pce_svn as u64)
+            })?;
+        tcb_status(level.get("tcbStatus")?.as_str()?)
+    }
+
+    /// Whether the collateral's `nextUpdate` is still ahead of `now`.
+    fn is_current(collateral: &Value, now: u64) -> Option<bool> {
+        let next_update = parse_timestamp(collateral.get("nextUpdate")?.as_str()?.as_bytes())?;
+        Some(next_update / 1000 > now)
+    }
+
+    /// The quote status a TCB status of the collateral amounts to.
+    fn tcb_status(status: &str) -> Option<QuoteStatus> {
+        match status {
+            "UpToDate" => Some(QuoteStatus::Ok),
+            "SWHardeningNeeded" => Some(QuoteStatus::SwHardeningNeeded),
+            "ConfigurationNeeded" => Some(QuoteStatus::ConfigurationNeeded),
+            "ConfigurationAndSWHardeningNeeded" => {
+                Some(QuoteStatus::ConfigurationAndSwHardeningNeeded)
+            }
+            "OutOfDate" | "OutOfDateConfigurationNeeded" => Some(QuoteStatus::GroupOutOfDate),
+            "Revoked" => Some(QuoteStatus::KeyRevoked),
+            _ => None,
+        }
+    }
+
+    /// The DER certificates of a PEM certificate chain.
+    fn pem_certs(pem: &[u8]) -> Option<Vec<Vec<u8>>> {
+        const BEGIN: &[u8] = b"-----BEGIN CERTIFICATE-----";
+        const END: &[u8] = b"-----END CERTIFICATE-----";
+        let find = |haystack: &[u8], needle: &[u8]| {
+            haystack
+                .windows(needle.len())
+                .position(|window| window == needle)
+        };
+
+        let mut certs = Vec::new();
+        let mut rest = pem;
+        while let Some(start) = find(rest, BEGIN) {
+            let body = &rest[start + BEGIN.len()..];
+            let end = find(body, END)?;
+            let base64 = body[..end]
+                .iter()
+                .filter(|c| !c.is_ascii_whitespace())
+                .copied()
+                .collect::<Vec<_>>();
+            certs.push(base64_decode(&base64)?);
+            rest = &body[end + END.len()..];
+        }
+        Some(certs)
+    }
+
+    /// DER encoding of a raw `r || s` ECDSA P-256 signature, the way webpki takes it.
+    fn der_signature(raw: &[u8]) -> Option<Vec<u8>> {
+        if raw.len() != 64 {
+            return None;
+        }
+        let integer = |bytes: &[u8]| {
+            let bytes = &bytes[bytes.iter().position(|b| *b != 0).unwrap_or(31)..];
+            let mut der = vec![0x02];
+            if bytes[0] & 0x80 != 0 {
+                der.extend_from_slice(&[bytes.len() as u8 + 1, 0]);
+            } else {
+                der.push(bytes.len() as u8);
+            }
+            der.extend_from_slice(bytes);
+            der
+        };
+        let (r, s) = (integer(&raw[..32]), integer(&raw[32..]));
+        let mut der = vec![0x30, (r.len() + s.len()) as u8];
+        der.extend(r);
+        der.extend(s);
+        Some(der)
+    }
+
+    /// The tag, the content and the rest of a DER encoded `input`.
+    fn der_next(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+        let (&tag, rest) = input.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let n = (first & 0x7f) as usize;
+            if n == 0 || n > 4 || rest.len() < n {
+                return None;
+            }
+            let len = rest[..n]
+                .iter()
+                .fold(0usize, |len, b| (len << 8) | *b as usize);
+            rest = &rest[n..];
+            len
+        };
+        if rest.len() < len {
+            return None;
+        }
+        Some((tag, &rest[..len], &rest[len..]))
+    }
+
+    /// The encoded elements of the content of a DER sequence.
+    fn der_children(mut content: &[u8]) -> Option<Vec<&[u8]>> {
+        let mut children = Vec::new();
+        while !content.is_empty() {
+            let (_, _, rest) = der_next(content)?;
+            children.push(&content[..content.len() - rest.len()]);
+            content = rest;
+        }
+        Some(children)
+    }
+
+    /// The OID and the content of the value of an encoded `SEQUENCE { OID, value }`.
+    fn der_pair(encoded: &[u8]) -> Option<(&[u8], &[u8])> {
+        let (_, pair, _) = der_next(encoded)?;
+        let (_, oid, rest) = der_next(pair)?;
+        let (_, value, _) = der_next(rest)?;
+        Some((oid, value))
+    }
+
+    /// The content of a small unsigned DER integer.
+    fn der_uint(content: &[u8]) -> Option<u16> {
+        if content.is_empty() || content.len() > 3 {
+            return None;
+        }
+        let value = content.iter().fold(0u32, |n, b| (n << 8) | *b as u32);
+        core::convert::TryFrom::try_from(value).ok()
+    }
+
+    fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+        if hex.len() % 2 != 0 {
+            return None;
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::report::base64_encode;
+        use codec::Encode;
+        use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+        /// A v3 quote of `body` signed by a fresh attestation key, the quoting enclave report
+        /// binding the key unless `bind_key` is false.
+        fn signed_quote(body_byte: u8, bind_key: bool, certification_data: &[u8]) -> Vec<u8> {
+            let rng = ring::rand::SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let key =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref()).unwrap();
+            let attestation_key = &key.public_key().as_ref()[1..];
+            let auth_data = b"auth";
+
+            let mut body = vec![body_byte; QUOTE_BODY_LENGTH];
+            body[..4].copy_from_slice(&[3, 0, 2, 0]);
+            let mut qe_report = vec![0u8; REPORT_LENGTH];
+            if bind_key {
+                let hash = ring::digest::digest(
+                    &ring::digest::SHA256,
+                    &[attestation_key, &auth_data[..]].concat(),
+                );
+                qe_report[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + 32]
+                    .copy_from_slice(hash.as_ref());
+            }
+            let mut data = key.sign(&rng, &body).unwrap().as_ref().to_vec();
+            data.extend_from_slice(attestation_key);
+            data.extend(qe_report);
+            data.extend_from_slice(&[0; 64]);
+            data.extend_from_slice(&(auth_data.len() as u16).to_le_bytes());
+            data.extend_from_slice(auth_data);
+            data.extend_from_slice(&PCK_CERT_CHAIN_TYPE.to_le_bytes());
+            data.extend_from_slice(&(certification_data.len() as u32).to_le_bytes());
+            data.extend_from_slice(certification_data);
+
+            let mut quote = body;
+            quote.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            quote.extend(data);
+            quote
+        }
+
+        #[test]
+        fn dcap_quote_should_work() {
+            let quote = signed_quote(1, true, b"chain");
+            let parsed = DcapQuote::parse(&quote).unwrap();
+            assert_eq!(parsed.certification_data, b"chain");
+            assert_eq!(parsed.qe_auth_data, b"auth");
+            assert_eq!(parsed.verify_attestation_key(), Some(()));
+
+            // the body isn't the one signed, or the key isn't bound to the enclave report
+            let mut tampered = quote.clone();
+            tampered[100] ^= 1;
+            assert_eq!(
+                DcapQuote::parse(&tampered)
+                    .unwrap()
+                    .verify_attestation_key(),
+                None
+            );
+            let unbound = signed_quote(1, false, b"chain");
+            assert_eq!(
+                DcapQuote::parse(&unbound).unwrap().verify_attestation_key(),
+                None
+            );
+
+            // truncated, other versions or certification data types
+            assert!(DcapQuote::parse(&quote[..quote.len() - 1]).is_none());
+            let mut epid = quote.clone();
+            epid[0] = 2;
+            assert!(DcapQuote::parse(&epid).is_none());
+            let mut other_type = quote;
+            let type_offset = other_type.len() - 5 - 4 - 2;
+            other_type[type_offset] = 4;
+            assert!(DcapQuote::parse(&other_type).is_none());
+        }
+
+        #[test]
+        fn pck_tcb_should_work() {
+            fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+                let mut der = vec![tag];
+                if content.len() < 0x80 {
+                    der.push(content.len() as u8);
+                } else {
+                    der.extend_from_slice(&[0x82, (content.len() >> 8) as u8, content.len() as u8]);
+                }
+                der.extend_from_slice(content);
+                der
+            }
+            let oid = |arcs: &[u8]| der(0x06, &[SGX_EXTENSION_OID, arcs].concat());
+            let pair = |arcs: &[u8], value: Vec<u8>| der(0x30, &[oid(arcs), value].concat());
+
+            let mut components = (1..=16u8)
+                .map(|n| pair(&[2, n], der(0x02, &[n])))
+                .collect::<Vec<_>>();
+            components.push(pair(&[2, 17], der(0x02, &[0x00, 0xff])));
+            components.push(pair(&[2, 18], der(0x04, &[0; 16])));
+            let sgx = der(
+                0x30,
+                &[
+                    pair(&[1], der(0x04, &[0; 16])),
+                    pair(&[2], der(0x30, &components.concat())),
+                    pair(&[4], der(0x04, &[0, 0x90, 0x6e, 0xd5, 0, 0])),
+                ]
+                .concat(),
+            );
+            let extensions = der(
+                0x30,
+                &[
+                    der(
+                        0x30,
+                        &[
+                            der(0x06, &[0x55, 0x1d, 0x0f]),
+                            der(0x01, &[0xff]),
+                            der(0x04, &[3, 2, 6, 0xc0]),
+                        ]
+                        .concat(),
+                    ),
+                    der(
+                        0x30,
+                        &[der(0x06, SGX_EXTENSION_OID), der(0x04, &sgx)].concat(),
+                    ),
+                ]
+                .concat(),
+            );
+            let tbs = der(
+                0x30,
+                &[der(0x02, &[1]), der(DER_EXTENSIONS, &extensions)].concat(),
+            );
+            let cert = der(0x30, &[tbs, der(0x30, &[]), der(0x03, &[0])].concat());
+
+            let mut expected = PckTcb {
+                pce_svn: 255,
+                fmspc: vec![0, 0x90, 0x6e, 0xd5, 0, 0],
+                ..Default::default()
+            };
+            for n in 0..16 {
+                expected.components[n] = n as u8 + 1;
+            }
+            assert_eq!(PckTcb::parse(&cert), Some(expected));
+            assert_eq!(PckTcb::parse(&cert[..cert.len() - 1]), None);
+        }
+
+        #[test]
+        fn collateral_status_should_work() {
+            let tcb = PckTcb {
+                components: [2; 16],
+                pce_svn: 10,
+                fmspc: vec![0, 0x90, 0x6e, 0xd5, 0, 0],
+            };
+            let level = |svn: u64, pce_svn: u64, status: &str| {
+                let mut tcb = serde_json::Map::new();
+                for n in 1..=16 {
+                    tcb.insert(format!("sgxtcbcomp{:02}svn", n), svn.into());
+                }
+                tcb.insert("pcesvn".into(), pce_svn.into());
+                serde_json::json!({ "tcb": tcb, "tcbStatus": status })
+            };
+            let tcb_info = serde_json::json!({
+                "version": 2,
+                "nextUpdate": "2021-10-14T00:00:00Z",
+                "fmspc": "00906ED50000",
+                "tcbLevels": [level(3, 10, "UpToDate"), level(2, 11, "SWHardeningNeeded"), level(2, 10, "OutOfDate")],
+            });
+            let now = parse_timestamp(b"2021-10-13T00:00:00Z").unwrap() / 1000;
+            assert_eq!(
+                platform_status(&tcb_info, &tcb, now),
+                Some(QuoteStatus::GroupOutOfDate)
+            );
+            let newer = PckTcb { pce_svn: 11, ..tcb };
+            assert_eq!(
+                platform_status(&tcb_info, &newer, now),
+                Some(QuoteStatus::SwHardeningNeeded)
+            );
+            // expired, or of another FMSPC
+            assert_eq!(platform_status(&tcb_info, &newer, now + 86_400), None);
+            let other = PckTcb {
+                fmspc: vec![0; 6],
+                ..newer
+            };
+            assert_eq!(platform_status(&tcb_info, &other, now), None);
+
+            let mut qe_report = vec![0u8; REPORT_LENGTH];
+            qe_report[ATTRIBUTES_OFFSET] = 0x11;
+            qe_report[MR_SIGNER_OFFSET..MR_SIGNER_OFFSET + 32].copy_from_slice(&[0x8c; 32]);
+            qe_report[ISV_PROD_ID_OFFSET] = 1;
+            qe_report[ISV_SVN_OFFSET] = 5;
+            let identity = serde_json::json!({
+                "nextUpdate": "2021-10-14T00:00:00Z",
+                "miscselect": "00000000",
+                "miscselectMask": "FFFFFFFF",
+                "attributes": "11000000000000000000000000000000",
+                "attributesMask": "FBFFFFFFFFFFFFFF0000000000000000",
+                "mrsigner": "8C".repeat(32),
+                "isvprodid": 1,
+                "tcbLevels": [
+                    { "tcb": { "isvsvn": 6 }, "tcbStatus": "UpToDate" },
+                    { "tcb": { "isvsvn": 5 }, "tcbStatus": "OutOfDate" },
+                ],
+            });
+            assert_eq!(
+                quoting_enclave_status(&identity, &qe_report, now),
+                Some(QuoteStatus::GroupOutOfDate)
+            );
+            qe_report[ISV_SVN_OFFSET] = 6;
+            assert_eq!(
+                quoting_enclave_status(&identity, &qe_report, now),
+                Some(QuoteStatus::Ok)
+            );
+            // another enclave
+            qe_report[MR_SIGNER_OFFSET] = 0;
+            assert_eq!(quoting_enclave_status(&identity, &qe_report, now), None);
+        }
+
+        #[test]
+        fn encodings_should_work() {
+            let cert = b"certificate".to_vec();
+            let mut pem = b"-----BEGIN CERTIFICATE-----\n".to_vec();
+            pem.extend(base64_encode(&cert));
+            pem.extend_from_slice(b"\n-----END CERTIFICATE-----\n\0");
+            assert_eq!(
+                pem_certs(&[pem.clone(), pem].concat()),
+                Some(vec![cert.clone(), cert])
+            );
+            assert_eq!(pem_certs(b"-----BEGIN CERTIFICATE-----\nY2VydA=="), None);
+
+            let mut raw = [0u8; 64];
+            raw[1] = 0x80;
+            raw[63] = 1;
+            let mut expected = vec![0x30, 37, 0x02, 32, 0x00];
+            expected.extend_from_slice(&raw[1..32]);
+            expected.extend_from_slice(&[0x02, 1, 1]);
+            assert_eq!(der_signature(&raw), Some(expected));
+            assert_eq!(der_signature(&raw[1..]), None);
+
+            assert_eq!(der_uint(&[0x00, 0xff]), Some(255));
+            assert_eq!(der_uint(&[0x01, 0x00, 0x00]), None);
+            assert_eq!(hex_decode("00ff8C"), Some(vec![0, 255, 0x8c]));
+            assert_eq!(hex_decode("0"), None);
+        }
+
+        #[test]
+        fn untrusted_reports_should_fail() {
+            assert_eq!(verify_ias(b"root", b"signing", b"{}", b"", 0), None);
+            let quote = signed_quote(1, true, b"no certificates");
+            assert_eq!(
+                verify_dcap(b"root", &quote, &DcapCollateral::default().encode(), 0),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weights for pallet_liveness
+//!
+//! The base weights are estimated from the storage accesses of each call until they are
+//! regenerated from the benchmarks on the reference hardware with:
+//!
+//! ./scripts/benchmark-weights.sh liveness
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_liveness.
+pub trait WeightInfo {
+    fn report_misconduct() -> Weight;
+    fn attestor_attest_geode() -> Weight;
+    fn attestor_exit(g: u32) -> Weight;
+    fn set_min_attestor_num() -> Weight;
+    fn set_attestor_target_classes(c: u32) -> Weight;
+    fn set_geode_attestor_target() -> Weight;
+    fn set_liveness_params() -> Weight;
+    fn set_slash_params() -> Weight;
+    fn set_ra_root_cert(l: u32) -> Weight;
+    fn set_ra_params() -> Weight;
+    fn overturn_report(a: u32) -> Weight;
+    fn appeal_slash() -> Weight;
+    fn resolve_appeal(a: u32) -> Weight;
+    fn assign_attestors(a: u32) -> Weight;
+    fn on_initialize() -> Weight;
+    fn expire_report() -> Weight;
+    fn apply_slash(a: u32) -> Weight;
+    fn expire_geode(a: u32) -> Weight;
+    fn remove_attestor(g: u32) -> Weight;
+    fn check_geode() -> Weight;
+    fn revoke_geode(a: u32) -> Weight;
+}
+
+/// Weights for pallet_liveness using the Substrate node and recommended hardware.
+/// Each item of the hook is weighed as a hook run visiting that single item.
+/// Attesting a geode covers verifying the certificate chains of its report on the host.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn report_misconduct() -> Weight {
+        (350_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(25 as Weight))
+            .saturating_add(T::DbWeight::get().writes(22 as Weight))
+    }
+    fn attestor_attest_geode() -> Weight {
+        (1_000_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(15 as Weight))
+            .saturating_add(T::DbWeight::get().writes(12 as Weight))
+    }
+    fn attestor_exit(g: u32) -> Weight {
+        (50_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(g as Weight)))
+    }
+    fn set_min_attestor_num() -> Weight {
+        (3_000_000 as Weight).saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn set_attestor_target_classes(c: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(c as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn set_geode_attestor_target() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes(8 as Weight))
+    }
+    fn set_liveness_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_slash_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_ra_root_cert(l: u32) -> Weight {
+        (4_000_000 as Weight)
+            .saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn set_ra_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn overturn_report(a: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn appeal_slash() -> Weight {
+        (90_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn resolve_appeal(a: u32) -> Weight {
+        (180_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(12 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(10 as Weight))
+            .saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn assign_attestors(a: u32) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+    fn on_initialize() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(12 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn expire_report() -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().writes(10 as Weight))
+    }
+    fn apply_slash(a: u32) -> Weight {
+        (160_000_000 as Weight)
+            .saturating_add((40_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(19 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(15 as Weight))
+            .saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn expire_geode(a: u32) -> Weight {
+        (110_000_000 as Weight)
+            .saturating_add((15_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(24 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(22 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+    fn remove_attestor(g: u32) -> Weight {
+        (120_000_000 as Weight)
+            .saturating_add((60_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(T::DbWeight::get().reads(20 as Weight))
+            .saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(T::DbWeight::get().writes(17 as Weight))
+            .saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(g as Weight)))
+    }
+    fn check_geode() -> Weight {
+        (80_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(22 as Weight))
+            .saturating_add(T::DbWeight::get().writes(16 as Weight))
+    }
+    fn revoke_geode(a: u32) -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(T::DbWeight::get().reads(22 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(T::DbWeight::get().writes(18 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn report_misconduct() -> Weight {
+        (350_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(25 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(22 as Weight))
+    }
+    fn attestor_attest_geode() -> Weight {
+        (1_000_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(15 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(12 as Weight))
+    }
+    fn attestor_exit(g: u32) -> Weight {
+        (50_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(RocksDbWeight::get().reads(5 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(g as Weight)))
+    }
+    fn set_min_attestor_num() -> Weight {
+        (3_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn set_attestor_target_classes(c: u32) -> Weight {
+        (5_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(c as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn set_geode_attestor_target() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(8 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(8 as Weight))
+    }
+    fn set_liveness_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_slash_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_ra_root_cert(l: u32) -> Weight {
+        (4_000_000 as Weight)
+            .saturating_add((1_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn set_ra_params() -> Weight {
+        (4_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn overturn_report(a: u32) -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn appeal_slash() -> Weight {
+        (90_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn resolve_appeal(a: u32) -> Weight {
+        (180_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(12 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(10 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn assign_attestors(a: u32) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(7 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(5 as Weight))
+    }
+    fn on_initialize() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(12 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(1 as Weight))
+    }
+    fn expire_report() -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(14 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(10 as Weight))
+    }
+    fn apply_slash(a: u32) -> Weight {
+        (160_000_000 as Weight)
+            .saturating_add((40_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(19 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(15 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn expire_geode(a: u32) -> Weight {
+        (110_000_000 as Weight)
+            .saturating_add((15_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(24 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(22 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+    fn remove_attestor(g: u32) -> Weight {
+        (120_000_000 as Weight)
+            .saturating_add((60_000_000 as Weight).saturating_mul(g as Weight))
+            .saturating_add(RocksDbWeight::get().reads(20 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((6 as Weight).saturating_mul(g as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(17 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(g as Weight)))
+    }
+    fn check_geode() -> Weight {
+        (80_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(22 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(16 as Weight))
+    }
+    fn revoke_geode(a: u32) -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add((12_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(RocksDbWeight::get().reads(22 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(18 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(a as Weight)))
+    }
+}
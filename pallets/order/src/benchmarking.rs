@@ -0,0 +1,137 @@
+//! Benchmarking setup for pallet-order
+
+use super::*;
+
+use crate::Pallet as OrderModule;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Get, Hooks};
+use frame_system::RawOrigin;
+use pallet_geode::{
+    GeodeState, Operator, PropKey, PropValue, Requirement, Selector, TransitionCause,
+};
+use primitives::BlockNumber;
+use sp_runtime::traits::Bounded;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+const PAYMENT: u32 = 1_000;
+
+fn funded_user<T: Config>() -> T::AccountId {
+    let user: T::AccountId = whitelisted_caller();
+    <T as Config>::Currency::make_free_balance_be(&user, BalanceOf::<T>::max_value() / 2u32.into());
+    user
+}
+
+/// Register `n` attested geodes of a funded provider, starting at `index`.
+fn attested_geodes<T: Config>(index: u32, n: u32) {
+    let provider: T::AccountId = account("provider", 0, SEED);
+    <T as pallet_geode::Config>::Currency::make_free_balance_be(
+        &provider,
+        pallet_geode::BalanceOf::<T>::max_value() / 2u32.into(),
+    );
+    for i in index..index + n {
+        let geode = pallet_geode::GeodeOf::<T> {
+            id: account("geode", i, SEED),
+            provider: provider.clone(),
+            ..Default::default()
+        };
+        let id = geode.id.clone();
+        pallet_geode::Pallet::<T>::provider_register_geode(
+            RawOrigin::Signed(provider.clone()).into(),
+            geode,
+        )
+        .unwrap();
+        pallet_geode::Pallet::<T>::transit(&id, GeodeState::Attested, TransitionCause::Attestation)
+            .unwrap();
+    }
+}
+
+/// A selector with `r` requirements as long as allowed, which no geode without properties
+/// matches.
+fn unmatched_selector<T: Config>(r: u32) -> Selector {
+    let value = vec![b'r'; T::MaxPropLength::get() as usize];
+    Selector {
+        requirements: (0..r)
+            .map(|_| Requirement {
+                key: PropKey::Region,
+                op: Operator::Eq,
+                value: PropValue::Text(value.clone()),
+            })
+            .collect(),
+    }
+}
+
+fn place<T: Config>(user: &T::AccountId, requirements: Selector, duration: BlockNumber) {
+    OrderModule::<T>::place_order(
+        RawOrigin::Signed(user.clone()).into(),
+        T::Hash::default(),
+        requirements,
+        duration,
+        PAYMENT.into(),
+    )
+    .unwrap();
+}
+
+benchmarks! {
+    place_order {
+        let r in 1 .. T::MaxProps::get();
+        let s in 0 .. MAX_MATCH_SCAN;
+        // matching visits every attested geode without a match
+        attested_geodes::<T>(0, s);
+        let user = funded_user::<T>();
+        let requirements = unmatched_selector::<T>(r);
+    }: _(RawOrigin::Signed(user), T::Hash::default(), requirements, 10, PAYMENT.into())
+    verify {
+        assert_eq!(PendingOrders::<T>::iter().count(), 1);
+    }
+
+    cancel_order {
+        let user = funded_user::<T>();
+        place::<T>(&user, unmatched_selector::<T>(1), 10);
+        let order_id = PendingOrders::<T>::iter().next().unwrap().0;
+    }: _(RawOrigin::Signed(user), order_id)
+    verify {
+        assert_eq!(Orders::<T>::get(&order_id).state, OrderState::Cancelled);
+    }
+
+    on_initialize {
+        let p in 0 .. T::MaxOrdersPerBlock::get();
+        let s in 0 .. T::MaxOrdersPerBlock::get() * MAX_MATCH_SCAN;
+        let f in 0 .. T::MaxOrdersPerBlock::get();
+        let d in 0 .. T::MaxOrdersPerBlock::get();
+        frame_system::Pallet::<T>::set_block_number(1u32.into());
+        let user = funded_user::<T>();
+        let now = ORDER_RETENTION_BLOCK_NUMBER + 1;
+
+        // orders cancelled at once, pruned now
+        for _ in 0..d {
+            place::<T>(&user, unmatched_selector::<T>(1), 10);
+        }
+        for (order_id, _) in PendingOrders::<T>::iter().collect::<Vec<_>>() {
+            OrderModule::<T>::cancel_order(RawOrigin::Signed(user.clone()).into(), order_id)
+                .unwrap();
+        }
+        // orders served until now
+        attested_geodes::<T>(0, f);
+        for _ in 0..f {
+            place::<T>(&user, Selector::default(), now - 1);
+        }
+        // orders which visit `s` geodes in total without a match, and expire
+        let scans = if p == 0 { 0 } else { (s / p).min(MAX_MATCH_SCAN) };
+        attested_geodes::<T>(f, scans);
+        for _ in 0..p {
+            place::<T>(&user, unmatched_selector::<T>(1), 10);
+        }
+        ExpiryCursor::<T>::put(now);
+        frame_system::Pallet::<T>::set_block_number(now.into());
+    }: {
+        OrderModule::<T>::on_initialize(now.into());
+    }
+    verify {
+        assert_eq!(PendingOrders::<T>::iter().count(), 0);
+        assert_eq!(ProcessingOrders::<T>::iter().count(), 0);
+        assert!(!OrderPruneQueue::<T>::contains_key(1));
+    }
+}
+
+impl_benchmark_test_suite!(OrderModule, crate::mock::new_test_ext(), crate::mock::Test,);
@@ -0,0 +1,528 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use crate::weights::WeightInfo;
+    use core::convert::TryInto;
+    use frame_support::storage::StoragePrefixedMap;
+    use frame_support::traits::{BalanceStatus, Currency, ReservableCurrency};
+    use frame_support::{dispatch::DispatchResultWithPostInfo, ensure, pallet_prelude::*};
+    use frame_system::pallet_prelude::*;
+    use pallet_geode::Selector;
+    use primitives::BlockNumber;
+    use sp_runtime::traits::{Hash, Zero};
+    use sp_runtime::{RuntimeDebug, SaturatedConversion};
+    use sp_std::prelude::*;
+
+    #[cfg(feature = "std")]
+    use serde::{Deserialize, Serialize};
+
+    pub const PENDING_ORDER_EXPIRY_BLOCK_NUMBER: BlockNumber = 30;
+    /// Max number of attested geodes visited to match an order, the next match goes on from
+    /// the last geode visited.
+    pub const MAX_MATCH_SCAN: u32 = 64;
+    /// Number of blocks finished and cancelled orders are kept for before they are pruned.
+    pub const ORDER_RETENTION_BLOCK_NUMBER: BlockNumber = 14_400;
+
+    /// Order state
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+    pub enum OrderState {
+        /// The order is placed and waiting for a geode to serve it.
+        Pending,
+        /// The order is being served by an instantiated geode.
+        Processing,
+        /// The order reached its duration and the provider got paid.
+        Done,
+        /// The order is cancelled and the payment returned to the user.
+        Cancelled,
+        /// Not available
+        Null,
+    }
+
+    impl Default for OrderState {
+        fn default() -> Self {
+            OrderState::Null
+        }
+    }
+
+    /// The order struct placed by user to run a binary on a geode
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+    pub struct Order<AccountId, Hash, Balance> {
+        /// User who placed the order.
+        pub user: AccountId,
+        /// The binary hash the geode will get and run.
+        pub binary: Hash,
+        /// Selector the serving geode's properties have to match.
+        pub requirements: Selector,
+        /// How many blocks the order should be served for.
+        pub duration: BlockNumber,
+        /// Payment reserved from user and paid to provider once the order is done.
+        pub payment: Balance,
+        /// The geode serving the order.
+        pub geode: Option<AccountId>,
+        /// Block number when the order is placed.
+        pub placed: BlockNumber,
+        /// Block number when the order starts being served.
+        pub start: Option<BlockNumber>,
+        /// Current state of the order.
+        pub state: OrderState,
+    }
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type OrderOf<T> = Order<
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::Hash,
+        BalanceOf<T>,
+    >;
+
+    /// Configure the pallet by specifying the parameters and types on which it depends.
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_geode::Config {
+        /// Because this pallet emits events, it depends on the runtime's definition of an event.
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The currency in which orders are paid.
+        type Currency: ReservableCurrency<Self::AccountId>;
+        /// Max number of pending orders tried, and of blocks and orders of the expiry queue
+        /// visited, in a block. The orders left are processed in the next blocks.
+        #[pallet::constant]
+        type MaxOrdersPerBlock: Get<u32>;
+        /// Weight information for extrinsics and hooks in this pallet.
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// Orders placed, the finished and cancelled ones are kept for
+    /// `ORDER_RETENTION_BLOCK_NUMBER` blocks.
+    #[pallet::storage]
+    #[pallet::getter(fn orders)]
+    pub type Orders<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, OrderOf<T>, ValueQuery>;
+
+    /// Finished and cancelled orders indexed by the block they ended, to prune them.
+    #[pallet::storage]
+    #[pallet::getter(fn order_prune_queue)]
+    pub type OrderPruneQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::Hash>, ValueQuery>;
+
+    /// Orders waiting for a geode, with the block number they are placed.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_order_ids)]
+    pub type PendingOrders<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::Hash, BlockNumber, ValueQuery>;
+
+    /// Orders being served, with the block number they expire.
+    #[pallet::storage]
+    #[pallet::getter(fn processing_order_ids)]
+    pub type ProcessingOrders<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::Hash, BlockNumber, ValueQuery>;
+
+    /// Processing orders indexed by the block they expire. Entries are left behind when
+    /// orders are cancelled, they are checked against `ProcessingOrders` when processed.
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_queue)]
+    pub type ExpiryQueue<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumber, Vec<T::Hash>, ValueQuery>;
+
+    /// The first block of `ExpiryQueue` which hasn't been fully processed yet.
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_cursor)]
+    pub type ExpiryCursor<T: Config> = StorageValue<_, BlockNumber, ValueQuery>;
+
+    /// Raw key of the last pending order tried, `None` to start over from the first one.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_sweep)]
+    pub type PendingSweep<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+    /// Raw key of the last attested geode visited to match an order, `None` to start over
+    /// from the first one.
+    #[pallet::storage]
+    #[pallet::getter(fn match_cursor)]
+    pub type MatchCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn order_nonce)]
+    pub type OrderNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId", T::Hash = "Hash")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// User placed an order. \[user_id, order_id\]
+        OrderPlaced(T::AccountId, T::Hash),
+        /// An order is assigned to a geode. \[order_id, geode_id\]
+        OrderMatched(T::Hash, T::AccountId),
+        /// An order is served till the end and the provider got paid. \[order_id\]
+        OrderDone(T::Hash),
+        /// An order is cancelled and the payment returned. \[order_id\]
+        OrderCancelled(T::Hash),
+        /// The provider got less than the payment of a finished order, e.g. as the reserved
+        /// payment got slashed. \[order_id, shortfall\]
+        OrderUnderpaid(T::Hash, BalanceOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Use an invalid order id.
+        InvalidOrder,
+        /// The OrderState can't allow you to do something now.
+        InvalidOrderState,
+        /// You doesn't have the right to do what you want.
+        NoRight,
+        /// Invalid input
+        InvalidInput,
+        /// More requirements than `MaxProps`, or a value longer than `MaxPropLength`.
+        InvalidRequirements,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// 1. At every block, finish the processing orders which have been served for their
+        /// duration and release their geodes.
+        /// 2. At every block, try to find a geode for some pending orders, going on from the
+        /// last order tried, and cancel the orders not matched after an expiring block.
+        /// 3. At every block, prune the orders which ended `ORDER_RETENTION_BLOCK_NUMBER`
+        /// blocks before.
+        ///
+        /// At most `MaxOrdersPerBlock` orders are visited by the first two steps, and matching
+        /// an order visits at most `MAX_MATCH_SCAN` geodes.
+        fn on_initialize(block_number: T::BlockNumber) -> Weight {
+            // number of pending orders tried, geodes scanned to match them, processing orders
+            // visited and orders pruned, to weigh the hook
+            let (mut p, mut s, mut f, mut d) = (0u32, 0u32, 0u32, 0u32);
+            if let Ok(now) = TryInto::<BlockNumber>::try_into(block_number) {
+                // the expiry queue starts at the block the pallet first runs at
+                if !<ExpiryCursor<T>>::exists() {
+                    <ExpiryCursor<T>>::put(now);
+                }
+                f += Self::finish_expired(now);
+                p += Self::sweep_pending(now, &mut s);
+                if let Some(block) = now.checked_sub(ORDER_RETENTION_BLOCK_NUMBER) {
+                    for order_id in <OrderPruneQueue<T>>::take(block).iter() {
+                        d += 1;
+                        <Orders<T>>::remove(order_id);
+                    }
+                }
+            }
+            <T as Config>::WeightInfo::on_initialize(p, s, f, d)
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Called by user to place an order running `binary` for `duration` blocks on a geode
+        /// with the required properties. The payment is reserved until the order ends. The
+        /// weight assumes matching the order visits `MAX_MATCH_SCAN` geodes, the actual weight
+        /// is refunded after dispatch.
+        #[pallet::weight(<T as Config>::WeightInfo::place_order(
+            requirements.requirements.len() as u32,
+            MAX_MATCH_SCAN
+        ))]
+        pub fn place_order(
+            origin: OriginFor<T>,
+            binary: T::Hash,
+            requirements: Selector,
+            duration: BlockNumber,
+            payment: BalanceOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(duration > 0, Error::<T>::InvalidInput);
+            ensure!(
+                requirements.is_bounded(
+                    T::MaxProps::get() as usize,
+                    T::MaxPropLength::get() as usize
+                ),
+                Error::<T>::InvalidRequirements
+            );
+
+            <T as Config>::Currency::reserve(&who, payment)?;
+
+            let nonce = <OrderNonce<T>>::get();
+            <OrderNonce<T>>::put(nonce + 1);
+            let order_id = T::Hashing::hash_of(&(&who, &binary, nonce));
+
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            let order = OrderOf::<T> {
+                user: who.clone(),
+                binary,
+                requirements,
+                duration,
+                payment,
+                geode: None,
+                placed: block_number,
+                start: None,
+                state: OrderState::Pending,
+            };
+            let requirement_num = order.requirements.requirements.len() as u32;
+            <Orders<T>>::insert(&order_id, order);
+            <PendingOrders<T>>::insert(&order_id, block_number);
+            Self::deposit_event(Event::OrderPlaced(who, order_id));
+
+            let mut scanned = 0u32;
+            Self::match_order(&order_id, &mut scanned);
+            Ok(Some(<T as Config>::WeightInfo::place_order(
+                requirement_num,
+                scanned,
+            ))
+            .into())
+        }
+
+        /// Called by user to cancel an order which hasn't been served yet.
+        #[pallet::weight(<T as Config>::WeightInfo::cancel_order())]
+        pub fn cancel_order(origin: OriginFor<T>, order_id: T::Hash) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                <Orders<T>>::contains_key(&order_id),
+                Error::<T>::InvalidOrder
+            );
+            let order = <Orders<T>>::get(&order_id);
+            ensure!(order.user == who, Error::<T>::NoRight);
+            ensure!(
+                order.state == OrderState::Pending,
+                Error::<T>::InvalidOrderState
+            );
+            Self::cancel(&order_id);
+            Ok(().into())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Return orders waiting for a geode
+        pub fn pending_orders() -> Vec<OrderOf<T>> {
+            let mut res = Vec::new();
+            <PendingOrders<T>>::iter()
+                .map(|(id, _)| {
+                    res.push(<Orders<T>>::get(id));
+                })
+                .all(|_| true);
+            res
+        }
+
+        /// Try to match some pending orders, going on from the last order tried, and cancel
+        /// the ones which have been waiting too long. Return the number of orders tried, the
+        /// number of geodes visited to match them is added to `scanned`.
+        fn sweep_pending(now: BlockNumber, scanned: &mut u32) -> u32 {
+            let max = T::MaxOrdersPerBlock::get() as usize;
+            let start = <PendingSweep<T>>::get()
+                .unwrap_or_else(|| <PendingOrders<T>>::final_prefix().to_vec());
+            let pending = <PendingOrders<T>>::iter_from(start)
+                .take(max)
+                .collect::<Vec<_>>();
+            for (order_id, placed) in pending.iter() {
+                if Self::match_order(order_id, scanned).is_none()
+                    && placed.saturating_add(PENDING_ORDER_EXPIRY_BLOCK_NUMBER) < now
+                {
+                    Self::cancel(order_id);
+                }
+            }
+            match pending.last() {
+                Some((order_id, _)) if pending.len() == max => {
+                    <PendingSweep<T>>::put(<PendingOrders<T>>::hashed_key_for(order_id))
+                }
+                _ => <PendingSweep<T>>::kill(),
+            }
+            pending.len() as u32
+        }
+
+        /// Finish the orders expiring from the cursor up to `now`. At most
+        /// `MaxOrdersPerBlock` blocks and orders are visited, the orders left are carried over
+        /// to the next block. Return the number of blocks and orders visited.
+        fn finish_expired(now: BlockNumber) -> u32 {
+            let max = T::MaxOrdersPerBlock::get();
+            let mut budget = max;
+            let mut cursor = <ExpiryCursor<T>>::get();
+            while cursor <= now && budget > 0 {
+                let mut orders = <ExpiryQueue<T>>::take(cursor);
+                if orders.is_empty() {
+                    budget -= 1;
+                    cursor += 1;
+                    continue;
+                }
+                let rest = orders.split_off(orders.len().min(budget as usize));
+                budget -= orders.len() as u32;
+                for order_id in orders {
+                    if <ProcessingOrders<T>>::contains_key(&order_id)
+                        && <ProcessingOrders<T>>::get(&order_id) == cursor
+                    {
+                        Self::finish(&order_id);
+                    }
+                }
+                if !rest.is_empty() {
+                    <ExpiryQueue<T>>::insert(cursor, rest);
+                    break;
+                }
+                cursor += 1;
+            }
+            <ExpiryCursor<T>>::put(cursor);
+            max - budget
+        }
+
+        /// Try to assign an attested geode to a pending order, return the geode on success.
+        /// The number of geodes visited is added to `scanned`.
+        fn match_order(order_id: &T::Hash, scanned: &mut u32) -> Option<T::AccountId> {
+            let mut order = <Orders<T>>::get(order_id);
+            if order.state != OrderState::Pending {
+                return None;
+            }
+
+            let geode = Self::find_geode(&order.requirements, scanned)?;
+
+            <pallet_geode::Pallet<T>>::instantiate_geode(&geode.id, *order_id).ok()?;
+
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            let expiry = block_number.saturating_add(order.duration);
+            order.geode = Some(geode.id.clone());
+            order.start = Some(block_number);
+            order.state = OrderState::Processing;
+            <ProcessingOrders<T>>::insert(order_id, expiry);
+            <ExpiryQueue<T>>::append(expiry, order_id);
+            <PendingOrders<T>>::remove(order_id);
+            <Orders<T>>::insert(order_id, order);
+
+            Self::deposit_event(Event::OrderMatched(*order_id, geode.id.clone()));
+            Some(geode.id)
+        }
+
+        /// Find an attested geode free of orders whose properties match the requirements.
+        /// At most `MAX_MATCH_SCAN` geodes are visited, going on from the last geode visited
+        /// and wrapping around once the last attested geode is reached. The number of geodes
+        /// visited is added to `scanned`.
+        fn find_geode(
+            requirements: &Selector,
+            scanned: &mut u32,
+        ) -> Option<pallet_geode::GeodeOf<T>> {
+            let mut cursor = <MatchCursor<T>>::get();
+            let mut budget = MAX_MATCH_SCAN as usize;
+            loop {
+                let from_start = cursor.is_none();
+                let start = cursor
+                    .take()
+                    .unwrap_or_else(|| pallet_geode::AttestedGeodes::<T>::final_prefix().to_vec());
+                let mut last = None;
+                let found = pallet_geode::AttestedGeodes::<T>::iter_from(start)
+                    .take(budget)
+                    .map(|(id, _)| {
+                        budget -= 1;
+                        *scanned += 1;
+                        last = Some(id.clone());
+                        pallet_geode::Geodes::<T>::get(id)
+                    })
+                    .find(|geode| geode.order.is_none() && requirements.matches(&geode.props));
+                match last {
+                    Some(id) if found.is_some() || budget == 0 => {
+                        <MatchCursor<T>>::put(pallet_geode::AttestedGeodes::<T>::hashed_key_for(
+                            id,
+                        ));
+                        return found;
+                    }
+                    _ if from_start => {
+                        <MatchCursor<T>>::kill();
+                        return None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        /// End a processing order. The provider gets paid if its geode is still serving the
+        /// order, otherwise the user is refunded. The order is pruned
+        /// `ORDER_RETENTION_BLOCK_NUMBER` blocks later.
+        fn finish(order_id: &T::Hash) {
+            let mut order = <Orders<T>>::get(order_id);
+            <ProcessingOrders<T>>::remove(order_id);
+
+            let geode_id = match order.geode.clone() {
+                Some(geode_id) => geode_id,
+                None => {
+                    Self::cancel(order_id);
+                    return;
+                }
+            };
+            let geode = pallet_geode::Geodes::<T>::get(&geode_id);
+            let served = pallet_geode::Geodes::<T>::contains_key(&geode_id)
+                && geode.order.as_ref() == Some(order_id)
+                && (geode.state == pallet_geode::GeodeState::Instantiated
+                    || geode.state == pallet_geode::GeodeState::DegradedInstantiated);
+
+            <pallet_geode::Pallet<T>>::release_geode(&geode_id, order_id);
+
+            if served {
+                let shortfall = match <T as Config>::Currency::repatriate_reserved(
+                    &order.user,
+                    &geode.provider,
+                    order.payment,
+                    BalanceStatus::Free,
+                ) {
+                    Ok(shortfall) => shortfall,
+                    // nothing got paid, the payment goes back to the user
+                    Err(_) => {
+                        <T as Config>::Currency::unreserve(&order.user, order.payment);
+                        order.payment
+                    }
+                };
+                order.state = OrderState::Done;
+                <Orders<T>>::insert(order_id, order);
+                Self::queue_prune(order_id);
+                if !shortfall.is_zero() {
+                    Self::deposit_event(Event::OrderUnderpaid(*order_id, shortfall));
+                }
+                Self::deposit_event(Event::OrderDone(*order_id));
+            } else {
+                Self::cancel(order_id);
+            }
+        }
+
+        /// Cancel an order and return the payment to the user. The order is pruned
+        /// `ORDER_RETENTION_BLOCK_NUMBER` blocks later.
+        fn cancel(order_id: &T::Hash) {
+            let mut order = <Orders<T>>::get(order_id);
+            <T as Config>::Currency::unreserve(&order.user, order.payment);
+            <PendingOrders<T>>::remove(order_id);
+            <ProcessingOrders<T>>::remove(order_id);
+            if let Some(geode_id) = order.geode.as_ref() {
+                <pallet_geode::Pallet<T>>::release_geode(geode_id, order_id);
+            }
+            order.state = OrderState::Cancelled;
+            <Orders<T>>::insert(order_id, order);
+            Self::queue_prune(order_id);
+            Self::deposit_event(Event::OrderCancelled(*order_id));
+        }
+
+        /// Queue an order which ended to prune it later.
+        fn queue_prune(order_id: &T::Hash) {
+            let block_number =
+                <frame_system::Pallet<T>>::block_number().saturated_into::<BlockNumber>();
+            <OrderPruneQueue<T>>::append(block_number, order_id);
+        }
+    }
+
+    impl<T: Config> pallet_geode::OrderHolder<T::AccountId, T::Hash> for Pallet<T> {
+        fn holder(order: &T::Hash) -> Option<T::AccountId> {
+            if <Orders<T>>::contains_key(order) {
+                Some(<Orders<T>>::get(order).user)
+            } else {
+                None
+            }
+        }
+    }
+}
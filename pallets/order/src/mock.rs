@@ -0,0 +1,204 @@
+use crate as pallet_order;
+use frame_support::parameter_types;
+use frame_system as system;
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const INIT_BALANCE: u128 = 100_100_100;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Module, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Event<T>},
+        AttestorModule: pallet_attestor::{Module, Call, Storage, Event<T>},
+        GeodeModule: pallet_geode::{Module, Call, Storage, Event<T>},
+        OrderModule: pallet_order::{Module, Call, Storage, Event<T>},
+    }
+);
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<u128>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u128 = 500;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    /// The type for recording an account's balance.
+    type Balance = u128;
+    /// The ubiquitous event type.
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+    Call: From<C>,
+{
+    type Extrinsic = UncheckedExtrinsic;
+    type OverarchingCall = Call;
+}
+
+impl pallet_attestor::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type Call = Call;
+    type OnHeartbeat = ();
+    type Assignments = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxDnsLength: u32 = 253;
+    pub const MaxProps: u32 = 8;
+    pub const MaxPropLength: u32 = 32;
+    pub const DepositPerByte: u128 = 0;
+}
+
+impl pallet_geode::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type MaxDnsLength = MaxDnsLength;
+    type MaxProps = MaxProps;
+    type MaxPropLength = MaxPropLength;
+    type DepositPerByte = DepositPerByte;
+    type OnGeodeRegister = ();
+    type OnGeodePropsUpdate = ();
+    type OnGeodeRemove = ();
+    type Slashes = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub static MaxOrdersPerBlock: u32 = 100;
+}
+
+impl pallet_order::Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type MaxOrdersPerBlock = MaxOrdersPerBlock;
+    type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, INIT_BALANCE), (2, INIT_BALANCE), (5, INIT_BALANCE)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+#[allow(dead_code)]
+pub fn events() -> Vec<Event> {
+    let evt = System::events()
+        .into_iter()
+        .map(|evt| evt.event)
+        .collect::<Vec<_>>();
+
+    System::reset_events();
+
+    evt
+}
+
+pub fn register_attestor(_attestor_account: <Test as system::Config>::AccountId) {
+    let url = vec![1];
+    let pair = sr25519::Pair::from_seed(&[200; 32]);
+    let pubkey = pair.public().0.to_vec();
+    let min_stake = 100;
+    let attestor_account = 1;
+    let block_number = System::block_number() as u32;
+    let payload = AttestorModule::key_proof_payload(&attestor_account, &pubkey, block_number);
+
+    // set the min stake balance
+    AttestorModule::set_att_stake_min(Origin::root(), min_stake).expect("root sets the min stake");
+
+    // successfully call register
+    AttestorModule::attestor_register(
+        Origin::signed(attestor_account),
+        url.clone(),
+        pubkey.clone(),
+        block_number,
+        pair.sign(&payload).0,
+    )
+    .expect("attestor registers");
+}
+
+pub fn provider_register_geode(
+    provider: <Test as system::Config>::AccountId,
+    geode_id: <Test as system::Config>::AccountId,
+) {
+    let geode: pallet_geode::Geode<
+        <Test as system::Config>::AccountId,
+        <Test as system::Config>::Hash,
+    > = pallet_geode::Geode {
+        id: geode_id,
+        provider: provider,
+        order: None,
+        ip: vec![],
+        dns: vec![],
+        props: Default::default(),
+        state: Default::default(),
+        promise: Default::default(),
+    };
+
+    GeodeModule::provider_register_geode(Origin::signed(provider), geode);
+}
+
+pub fn attest_geode(geode_id: <Test as system::Config>::AccountId) {
+    GeodeModule::transit(
+        &geode_id,
+        pallet_geode::GeodeState::Attested,
+        pallet_geode::TransitionCause::Attestation,
+    )
+    .unwrap();
+}
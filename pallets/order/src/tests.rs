@@ -0,0 +1,349 @@
+use crate::{
+    mock::*, Error, ExpiryQueue, OrderPruneQueue, OrderState, Orders, PendingOrders,
+    ProcessingOrders, WeightInfo, MAX_MATCH_SCAN, ORDER_RETENTION_BLOCK_NUMBER,
+};
+use frame_support::traits::{Hooks, ReservableCurrency};
+use frame_support::{assert_noop, assert_ok};
+use pallet_geode::{Operator, OrderHolder, PropKey, PropValue, Requirement, Selector};
+use sp_core::H256;
+
+const USER: u64 = 5;
+const PROVIDER: u64 = 2;
+const GEODE: u64 = 3;
+const PAYMENT: u128 = 1000;
+
+fn place_order(duration: u32) -> H256 {
+    assert_ok!(OrderModule::place_order(
+        Origin::signed(USER),
+        H256::repeat_byte(1),
+        Default::default(),
+        duration,
+        PAYMENT
+    ));
+    events()
+        .into_iter()
+        .find_map(|event| match event {
+            Event::pallet_order(crate::Event::OrderPlaced(_, id)) => Some(id),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn it_works_place_order_matches_attested_geode() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+
+        let order_id = place_order(10);
+
+        let order = OrderModule::orders(&order_id);
+        assert_eq!(order.state, OrderState::Processing);
+        assert_eq!(order.geode, Some(GEODE));
+        assert_eq!(Balances::reserved_balance(USER), PAYMENT);
+        assert_eq!(OrderModule::holder(&order_id), Some(USER));
+        assert_eq!(OrderModule::holder(&H256::repeat_byte(2)), None);
+
+        let geode = GeodeModule::geodes(&GEODE);
+        assert_eq!(geode.state, pallet_geode::GeodeState::Instantiated);
+        assert_eq!(geode.order, Some(order_id));
+        assert!(pallet_geode::InstantiatedGeodes::<Test>::contains_key(
+            &GEODE
+        ));
+        assert!(!pallet_geode::AttestedGeodes::<Test>::contains_key(&GEODE));
+    });
+}
+
+#[test]
+fn it_works_place_order_pending_without_geode() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+
+        let order_id = place_order(10);
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Pending);
+
+        // geode attested later gets matched in the next block
+        attest_geode(GEODE);
+        System::set_block_number(2);
+        OrderModule::on_initialize(2);
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Processing);
+    });
+}
+
+#[test]
+fn it_place_order_requirements_not_satisfied() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+
+        let requirements = Selector {
+            requirements: vec![Requirement {
+                key: PropKey::Region,
+                op: Operator::Eq,
+                value: PropValue::Text(b"eu".to_vec()),
+            }],
+        };
+        assert_ok!(OrderModule::place_order(
+            Origin::signed(USER),
+            H256::repeat_byte(1),
+            requirements,
+            10,
+            PAYMENT
+        ));
+
+        assert_eq!(OrderModule::pending_orders().len(), 1);
+        assert_eq!(
+            GeodeModule::geodes(&GEODE).state,
+            pallet_geode::GeodeState::Attested
+        );
+    });
+}
+
+#[test]
+fn it_works_place_order_weighs_geodes_scanned() {
+    new_test_ext().execute_with(|| {
+        let place = || {
+            OrderModule::place_order(
+                Origin::signed(USER),
+                H256::repeat_byte(1),
+                Default::default(),
+                10,
+                PAYMENT,
+            )
+            .unwrap()
+            .actual_weight
+        };
+
+        // nothing to scan
+        assert_eq!(place(), Some(<() as WeightInfo>::place_order(0, 0)));
+
+        // the order is matched with the first geode visited
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        assert_eq!(place(), Some(<() as WeightInfo>::place_order(0, 1)));
+        assert!(
+            <() as WeightInfo>::place_order(0, 1)
+                < <() as WeightInfo>::place_order(0, MAX_MATCH_SCAN)
+        );
+    });
+}
+
+#[test]
+fn it_works_order_done_at_expiry() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        let provider_balance = Balances::free_balance(PROVIDER);
+
+        let order_id = place_order(10);
+
+        System::set_block_number(11);
+        OrderModule::on_initialize(11);
+
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Done);
+        assert_eq!(Balances::reserved_balance(USER), 0);
+        assert_eq!(Balances::free_balance(USER), INIT_BALANCE - PAYMENT);
+        assert_eq!(Balances::free_balance(PROVIDER), provider_balance + PAYMENT);
+
+        let geode = GeodeModule::geodes(&GEODE);
+        assert_eq!(geode.state, pallet_geode::GeodeState::Attested);
+        assert_eq!(geode.order, None);
+    });
+}
+
+#[test]
+fn it_works_pending_order_cancelled_at_expiry() {
+    new_test_ext().execute_with(|| {
+        let order_id = place_order(10);
+
+        let expiry = 2 + crate::PENDING_ORDER_EXPIRY_BLOCK_NUMBER as u64;
+        System::set_block_number(expiry);
+        OrderModule::on_initialize(expiry);
+
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Cancelled);
+        assert_eq!(Balances::reserved_balance(USER), 0);
+        assert_eq!(Balances::free_balance(USER), INIT_BALANCE);
+    });
+}
+
+#[test]
+fn it_works_cancel_order() {
+    new_test_ext().execute_with(|| {
+        let order_id = place_order(10);
+
+        assert_noop!(
+            OrderModule::cancel_order(Origin::signed(PROVIDER), order_id),
+            Error::<Test>::NoRight
+        );
+        assert_ok!(OrderModule::cancel_order(Origin::signed(USER), order_id));
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Cancelled);
+        assert_eq!(Balances::free_balance(USER), INIT_BALANCE);
+    });
+}
+
+#[test]
+fn it_cancel_order_invalid_state() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        let order_id = place_order(10);
+
+        assert_noop!(
+            OrderModule::cancel_order(Origin::signed(USER), order_id),
+            Error::<Test>::InvalidOrderState
+        );
+    });
+}
+
+#[test]
+fn it_place_order_requirements_unbounded() {
+    new_test_ext().execute_with(|| {
+        let requirement = |len: usize| Requirement {
+            key: PropKey::Region,
+            op: Operator::Eq,
+            value: PropValue::Text(vec![b'r'; len]),
+        };
+        let too_many = Selector {
+            requirements: vec![requirement(1); MaxProps::get() as usize + 1],
+        };
+        let too_long = Selector {
+            requirements: vec![requirement(MaxPropLength::get() as usize + 1)],
+        };
+        for requirements in vec![too_many, too_long] {
+            assert_noop!(
+                OrderModule::place_order(
+                    Origin::signed(USER),
+                    H256::repeat_byte(1),
+                    requirements,
+                    10,
+                    PAYMENT
+                ),
+                Error::<Test>::InvalidRequirements
+            );
+        }
+    });
+}
+
+#[test]
+fn it_works_orders_processed_within_budget() {
+    new_test_ext().execute_with(|| {
+        MaxOrdersPerBlock::set(1);
+        let first = place_order(10);
+        let second = place_order(10);
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        provider_register_geode(PROVIDER, 4);
+        attest_geode(4);
+
+        // a single pending order is tried per block
+        System::set_block_number(2);
+        OrderModule::on_initialize(2);
+        assert_eq!(PendingOrders::<Test>::iter().count(), 1);
+        System::set_block_number(3);
+        OrderModule::on_initialize(3);
+        assert_eq!(PendingOrders::<Test>::iter().count(), 0);
+        let first_expiry = ProcessingOrders::<Test>::get(&first);
+        let second_expiry = ProcessingOrders::<Test>::get(&second);
+        assert_eq!(
+            (
+                first_expiry.min(second_expiry),
+                first_expiry.max(second_expiry)
+            ),
+            (12, 13)
+        );
+
+        // a single block of the expiry queue is visited per block
+        System::set_block_number(13);
+        OrderModule::on_initialize(13);
+        assert_eq!(OrderModule::expiry_cursor(), 5);
+        for block in 14..24 {
+            System::set_block_number(block);
+            OrderModule::on_initialize(block);
+        }
+        assert_eq!(OrderModule::expiry_cursor(), 15);
+        assert_eq!(OrderModule::orders(&first).state, OrderState::Done);
+        assert_eq!(OrderModule::orders(&second).state, OrderState::Done);
+        assert_eq!(ExpiryQueue::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn it_works_hook_weighs_geodes_scanned() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        let requirements = Selector {
+            requirements: vec![Requirement {
+                key: PropKey::Region,
+                op: Operator::Eq,
+                value: PropValue::Text(b"eu".to_vec()),
+            }],
+        };
+        assert_ok!(OrderModule::place_order(
+            Origin::signed(USER),
+            H256::repeat_byte(1),
+            requirements,
+            10,
+            PAYMENT
+        ));
+
+        // the pending order visits the geode attested since without a match, and the empty
+        // block of the expiry queue is visited
+        attest_geode(GEODE);
+        System::set_block_number(2);
+        assert_eq!(
+            OrderModule::on_initialize(2),
+            <() as WeightInfo>::on_initialize(1, 1, 1, 0)
+        );
+    });
+}
+
+#[test]
+fn it_works_orders_pruned_after_retention() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        let done = place_order(10);
+        let cancelled = place_order(10);
+        assert_ok!(OrderModule::cancel_order(Origin::signed(USER), cancelled));
+
+        System::set_block_number(11);
+        OrderModule::on_initialize(11);
+        assert_eq!(OrderModule::orders(&done).state, OrderState::Done);
+        assert_eq!(OrderModule::order_prune_queue(1), vec![cancelled]);
+        assert_eq!(OrderModule::order_prune_queue(11), vec![done]);
+
+        let prune_at = 1 + ORDER_RETENTION_BLOCK_NUMBER as u64;
+        System::set_block_number(prune_at);
+        OrderModule::on_initialize(prune_at);
+        assert!(!Orders::<Test>::contains_key(&cancelled));
+        assert!(Orders::<Test>::contains_key(&done));
+        assert!(!OrderPruneQueue::<Test>::contains_key(1));
+
+        System::set_block_number(prune_at + 10);
+        OrderModule::on_initialize(prune_at + 10);
+        assert!(!Orders::<Test>::contains_key(&done));
+    });
+}
+
+#[test]
+fn it_works_order_underpaid() {
+    new_test_ext().execute_with(|| {
+        provider_register_geode(PROVIDER, GEODE);
+        attest_geode(GEODE);
+        let provider_balance = Balances::free_balance(PROVIDER);
+        let order_id = place_order(10);
+
+        // part of the reserved payment is slashed meanwhile
+        drop(Balances::slash_reserved(&USER, 400));
+
+        System::set_block_number(11);
+        OrderModule::on_initialize(11);
+        assert_eq!(OrderModule::orders(&order_id).state, OrderState::Done);
+        assert_eq!(Balances::free_balance(PROVIDER), provider_balance + 600);
+        assert!(
+            events().contains(&Event::pallet_order(crate::Event::OrderUnderpaid(
+                order_id, 400
+            )))
+        );
+    });
+}
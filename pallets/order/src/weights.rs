@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weights for pallet_order
+//!
+//! The base weights are estimated from the storage accesses of each call until they are
+//! regenerated from the benchmarks on the reference hardware with:
+//!
+//! ./scripts/benchmark-weights.sh order
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_order.
+pub trait WeightInfo {
+    fn place_order(r: u32, s: u32) -> Weight;
+    fn cancel_order() -> Weight;
+    fn on_initialize(p: u32, s: u32, f: u32, d: u32) -> Weight;
+}
+
+/// Weights for pallet_order using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn place_order(r: u32, s: u32) -> Weight {
+        (36_000_000 as Weight)
+            .saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+            .saturating_add((6_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(s as Weight)))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn cancel_order() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn on_initialize(p: u32, s: u32, f: u32, d: u32) -> Weight {
+        (9_000_000 as Weight)
+            .saturating_add((26_000_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add((6_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add((75_000_000 as Weight).saturating_mul(f as Weight))
+            .saturating_add((5_000_000 as Weight).saturating_mul(d as Weight))
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(s as Weight)))
+            .saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(f as Weight)))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes((8 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(T::DbWeight::get().writes((10 as Weight).saturating_mul(f as Weight)))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn place_order(r: u32, s: u32) -> Weight {
+        (36_000_000 as Weight)
+            .saturating_add((2_500_000 as Weight).saturating_mul(r as Weight))
+            .saturating_add((6_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(RocksDbWeight::get().reads(6 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(s as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn cancel_order() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(2 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(4 as Weight))
+    }
+    fn on_initialize(p: u32, s: u32, f: u32, d: u32) -> Weight {
+        (9_000_000 as Weight)
+            .saturating_add((26_000_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add((6_000_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add((75_000_000 as Weight).saturating_mul(f as Weight))
+            .saturating_add((5_000_000 as Weight).saturating_mul(d as Weight))
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(s as Weight)))
+            .saturating_add(RocksDbWeight::get().reads((6 as Weight).saturating_mul(f as Weight)))
+            .saturating_add(RocksDbWeight::get().writes(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes((8 as Weight).saturating_mul(p as Weight)))
+            .saturating_add(RocksDbWeight::get().writes((10 as Weight).saturating_mul(f as Weight)))
+            .saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(d as Weight)))
+    }
+}
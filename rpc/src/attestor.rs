@@ -1,4 +1,4 @@
-use automata_primitives::{Block, BlockId};
+use automata_primitives::{AccountId, Block, BlockId};
 use automata_runtime::apis::AttestorApi as AttestorRuntimeApi;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
@@ -11,6 +11,8 @@ use sp_runtime::traits::Block as BlockT;
 use std::sync::Arc;
 
 const RUNTIME_ERROR: i64 = 1;
+/// Version of the heartbeat format, `pallet_attestor::HEARTBEAT_VERSION`.
+const HEARTBEAT_VERSION: u8 = 2;
 
 #[rpc]
 /// Attestor RPC methods
@@ -18,6 +20,9 @@ pub trait AttestorServer<BlockHash> {
     /// return the attestor list
     #[rpc(name = "attestor_list")]
     fn attestor_list(&self) -> Result<Vec<(Vec<u8>, Vec<u8>, u32)>>;
+    /// return each attestor's number of attested and assigned geodes and declared capacity
+    #[rpc(name = "attestor_load")]
+    fn attestor_load(&self) -> Result<Vec<(AccountId, u32, u32)>>;
     /// return the attestor attesting a geode
     #[rpc(name = "geode_attestors")]
     fn geode_attestors(&self, geode: [u8; 32]) -> Result<Vec<(Vec<u8>, Vec<u8>)>>;
@@ -62,6 +67,20 @@ where
         Ok(attestor_list)
     }
 
+    /// return each attestor's number of attested and assigned geodes and declared capacity
+    fn attestor_load(&self) -> Result<Vec<(AccountId, u32, u32)>> {
+        let api = self.client.runtime_api();
+        let best = self.client.info().best_hash;
+        let at = BlockId::hash(best);
+
+        let attestor_loads = api.attestor_loads(&at).map_err(|e| Error {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Runtime unable to get attestor loads.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })?;
+        Ok(attestor_loads)
+    }
+
     /// return the attestor attesting a geode
     fn geode_attestors(&self, geode: [u8; 32]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
         let api = self.client.runtime_api();
@@ -86,16 +105,24 @@ where
         let best = self.client.info().best_hash;
         let at = BlockId::hash(best);
 
-        if attestor_notify.len() != 40 {
+        // an encoded heartbeat starts with its version and the attestor key
+        if attestor_notify.len() < 33 {
             return Err(Error {
                 code: ErrorCode::ServerError(RUNTIME_ERROR),
                 message: "message size incorrect.".into(),
                 data: None,
             });
         }
+        if attestor_notify[0] != HEARTBEAT_VERSION {
+            return Err(Error {
+                code: ErrorCode::ServerError(RUNTIME_ERROR),
+                message: "heartbeat version unsupported.".into(),
+                data: None,
+            });
+        }
 
         let mut attestor = [0u8; 32];
-        attestor.copy_from_slice(&attestor_notify[0..32]);
+        attestor.copy_from_slice(&attestor_notify[1..33]);
 
         let signature_raw_bytes_64;
         if signature_raw_bytes.len() == 64 {
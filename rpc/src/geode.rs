@@ -2,7 +2,7 @@ use automata_primitives::{AccountId, Block, BlockId, BlockNumber, Hash};
 use automata_runtime::apis::GeodeApi as GeodeRuntimeApi;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
-use pallet_geode::{Geode, GeodeState};
+use pallet_geode::{Geode, GeodeState, Selector, StateTransition};
 use sc_light::blockchain::BlockchainHeaderBackend as HeaderBackend;
 use sp_api::ProvideRuntimeApi;
 use sp_runtime::{traits::Block as BlockT, RuntimeDebug};
@@ -29,6 +29,16 @@ pub trait GeodeServer<BlockHash> {
     /// Return the current state of a geode
     #[rpc(name = "geode_state")]
     fn geode_state(&self, geode: [u8; 32]) -> Result<Option<GeodeState>>;
+    /// Return geodes in a state whose properties match the selector, e.g. `cores>=4,region=eu`
+    #[rpc(name = "select_geodes")]
+    fn select_geodes(&self, state: GeodeState, selector: String)
+        -> Result<Vec<WrappedGeode<Hash>>>;
+    /// Return the latest state transitions of a geode, oldest first
+    #[rpc(name = "geode_history")]
+    fn geode_history(&self, geode: [u8; 32]) -> Result<Vec<StateTransition>>;
+    /// Return geodes owned by a provider
+    #[rpc(name = "geodes_of_provider")]
+    fn geodes_of_provider(&self, provider: [u8; 32]) -> Result<Vec<WrappedGeode<Hash>>>;
 }
 
 /// The geode struct shows its status
@@ -154,4 +164,64 @@ where
 
         Ok(geode_state)
     }
+
+    /// Return geodes in a state whose properties match the selector
+    fn select_geodes(
+        &self,
+        state: GeodeState,
+        selector: String,
+    ) -> Result<Vec<WrappedGeode<Hash>>> {
+        let selector = selector.parse::<Selector>().map_err(|e| Error {
+            code: ErrorCode::InvalidParams,
+            message: "Invalid geode selector.".into(),
+            data: Some(e.into()),
+        })?;
+
+        let api = self.client.runtime_api();
+        let best = self.client.info().best_hash;
+        let at = BlockId::hash(best);
+        let geodes = api.select_geodes(&at, state, selector).map_err(|e| Error {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Runtime unable to select geodes.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })?;
+        let mut res = Vec::<WrappedGeode<Hash>>::new();
+        for geode in geodes {
+            res.push(geode.into())
+        }
+        Ok(res)
+    }
+
+    /// Return the latest state transitions of a geode, oldest first
+    fn geode_history(&self, geode: [u8; 32]) -> Result<Vec<StateTransition>> {
+        let api = self.client.runtime_api();
+        let best = self.client.info().best_hash;
+        let at = BlockId::hash(best);
+        let history = api.geode_history(&at, geode.into()).map_err(|e| Error {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Runtime unable to get geode history.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })?;
+
+        Ok(history)
+    }
+
+    /// Return geodes owned by a provider
+    fn geodes_of_provider(&self, provider: [u8; 32]) -> Result<Vec<WrappedGeode<Hash>>> {
+        let api = self.client.runtime_api();
+        let best = self.client.info().best_hash;
+        let at = BlockId::hash(best);
+        let geodes = api
+            .geodes_of_provider(&at, provider.into())
+            .map_err(|e| Error {
+                code: ErrorCode::ServerError(RUNTIME_ERROR),
+                message: "Runtime unable to get geodes of provider.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?;
+        let mut res = Vec::<WrappedGeode<Hash>>::new();
+        for geode in geodes {
+            res.push(geode.into())
+        }
+        Ok(res)
+    }
 }
@@ -1,10 +1,11 @@
 // use automata_primitives::{AccountId, Hash};
-// use pallet_geode::{Geode, GeodeState};
+// use pallet_geode::{Geode, GeodeState, Selector, StateTransition};
 // use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
     // pub trait AttestorApi {
     //     fn attestor_list() -> Vec<(Vec<u8>, Vec<u8>, u32)>;
+    //     fn attestor_loads() -> Vec<(AccountId, u32, u32)>;
     //     fn geode_attestors(geode: AccountId) -> Vec<(Vec<u8>, Vec<u8>)>;
     //     fn unsigned_attestor_notify_chain(message: Vec<u8>, signature_raw_bytes: [u8; 64]) -> Result<(), ()>;
     // }
@@ -14,6 +15,9 @@ sp_api::decl_runtime_apis! {
     //     fn attested_geodes() -> Vec<Geode<AccountId, Hash>>;
     //     fn attestor_attested_geodes(attestor: AccountId) -> Vec<Geode<AccountId, Hash>>;
     //     fn geode_state(geode: AccountId) -> Option<GeodeState>;
+    //     fn select_geodes(state: GeodeState, selector: Selector) -> Vec<Geode<AccountId, Hash>>;
+    //     fn geode_history(geode: AccountId) -> Vec<StateTransition>;
+    //     fn geodes_of_provider(provider: AccountId) -> Vec<Geode<AccountId, Hash>>;
     // }
 
     // pub trait TransferApi {
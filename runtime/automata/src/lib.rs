@@ -19,7 +19,7 @@ pub fn wasm_binary_unwrap() -> &'static [u8] {
 use codec::{Decode, Encode};
 use fp_rpc::TransactionStatus;
 use frame_system::{EnsureOneOf, EnsureRoot};
-// use pallet_geode::{Geode, GeodeState};
+// use pallet_geode::{Geode, GeodeState, Selector, StateTransition};
 use pallet_grandpa::fg_primitives;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use pallet_im_online::sr25519::AuthorityId as ImOnlinedId;
@@ -85,8 +85,10 @@ use constants::time::*;
 pub use pallet_bridge;
 pub use pallet_bridgetransfer;
 // pub use pallet_attestor;
+// pub use pallet_enclave;
 // pub use pallet_geode;
 // pub use pallet_liveness;
+// pub use pallet_order;
 // pub use pallet_transfer;
 
 pub use automata_primitives::*;
@@ -194,8 +196,10 @@ impl Contains<Call> for CallFilter {
             | Call::Session(_)
             | Call::Utility(_)
             | Call::Ethereum(_) => false,
+            // | Call::EnclaveModule(_)
             // | Call::GeodeModule(_)
             // | Call::LivenessModule(_)
+            // | Call::OrderModule(_)
             // | Call::TransferModule(_)
             // | Call::AttestorModule(_) => false,
         }
@@ -689,14 +693,83 @@ impl pallet_ethereum::Config for Runtime {
 //     type Event = Event;
 //     type Currency = Balances;
 //     type Call = Call;
+//     type OnHeartbeat = LivenessModule;
+//     type Assignments = LivenessModule;
+//     type WeightInfo = pallet_attestor::weights::SubstrateWeight<Runtime>;
+// }
+
+// parameter_types! {
+//     pub const GeodeMaxDnsLength: u32 = 253;
+//     pub const GeodeMaxProps: u32 = 32;
+//     pub const GeodeMaxPropLength: u32 = 128;
+//     pub const GeodeDepositPerByte: Balance = 10 * MILLICENTS;
 // }
 
 // impl pallet_geode::Config for Runtime {
 //     type Event = Event;
+//     type Currency = Balances;
+//     type MaxDnsLength = GeodeMaxDnsLength;
+//     type MaxProps = GeodeMaxProps;
+//     type MaxPropLength = GeodeMaxPropLength;
+//     type DepositPerByte = GeodeDepositPerByte;
+//     type OnGeodeRegister = LivenessModule;
+//     type OnGeodePropsUpdate = LivenessModule;
+//     type OnGeodeRemove = LivenessModule;
+//     type Slashes = LivenessModule;
+//     type WeightInfo = pallet_geode::weights::SubstrateWeight<Runtime>;
+// }
+
+// impl pallet_enclave::Config for Runtime {
+//     type Event = Event;
+//     type GovernanceOrigin = EnsureOneOf<
+//         AccountId,
+//         EnsureRoot<AccountId>,
+//         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+//     >;
+//     type OnRevoke = LivenessModule;
+//     type WeightInfo = pallet_enclave::weights::SubstrateWeight<Runtime>;
+// }
+
+// parameter_types! {
+//     pub const LivenessMaxExpiriesPerBlock: u32 = 100;
+//     pub LivenessMaxHookWeight: Weight =
+//         Perbill::from_percent(40) * BlockWeights::get().max_block;
 // }
 
 // impl pallet_liveness::Config for Runtime {
 //     type Event = Event;
+//     type MaxExpiriesPerBlock = LivenessMaxExpiriesPerBlock;
+//     type MaxHookWeight = LivenessMaxHookWeight;
+//     type GovernanceOrigin = EnsureOneOf<
+//         AccountId,
+//         EnsureRoot<AccountId>,
+//         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+//     >;
+//     type AppealOrigin = EnsureOneOf<
+//         AccountId,
+//         pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+//         pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalCollective>,
+//     >;
+//     type UnixTime = Timestamp;
+//     type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
+//     type OrderHolder = OrderModule;
+//     type Slash = Treasury;
+//     type AttestorSlash = Treasury;
+//     // needs the node to register `pallet_liveness::remote_attestation::HostFunctions`
+//     type ReportVerifier = pallet_liveness::HostReportVerifier;
+//     type MeasurementRegistry = EnclaveModule;
+//     type WeightInfo = pallet_liveness::weights::SubstrateWeight<Runtime>;
+// }
+
+// parameter_types! {
+//     pub const OrderMaxOrdersPerBlock: u32 = 20;
+// }
+
+// impl pallet_order::Config for Runtime {
+//     type Event = Event;
+//     type Currency = Balances;
+//     type MaxOrdersPerBlock = OrderMaxOrdersPerBlock;
+//     type WeightInfo = pallet_order::weights::SubstrateWeight<Runtime>;
 // }
 
 // impl pallet_transfer::Config for Runtime {
@@ -1029,8 +1102,10 @@ construct_runtime!(
 
         // Include the custom logic from the pallet-template in the runtime.
         // AttestorModule: pallet_attestor::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+        // EnclaveModule: pallet_enclave::{Pallet, Call, Storage, Event, Config},
         // GeodeModule: pallet_geode::{Pallet, Call, Storage, Event<T>},
-        // LivenessModule: pallet_liveness::{Pallet, Call, Storage, Event<T>},
+        // LivenessModule: pallet_liveness::{Pallet, Call, Storage, Event<T>, Config},
+        // OrderModule: pallet_order::{Pallet, Call, Storage, Event<T>},
         // TransferModule: pallet_transfer::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
         ChainBridge: pallet_bridge::{Pallet, Call, Storage, Event<T>},
         BridgeTransfer: pallet_bridgetransfer::{Pallet, Call, Storage, Event<T>},
@@ -1251,6 +1326,10 @@ impl_runtime_apis! {
     //         AttestorModule::attestor_list()
     //     }
 
+    //     fn attestor_loads() -> Vec<(AccountId, u32, u32)> {
+    //         AttestorModule::attestor_loads()
+    //     }
+
     //     fn geode_attestors(geode: AccountId) -> Vec<(Vec<u8>, Vec<u8>)> {
     //         AttestorModule::attestors_of_geode(geode)
     //     }
@@ -1276,6 +1355,18 @@ impl_runtime_apis! {
     //     fn geode_state(geode: AccountId) -> Option<GeodeState> {
     //         GeodeModule::geode_state(geode)
     //     }
+
+    //     fn select_geodes(state: GeodeState, selector: Selector) -> Vec<Geode<AccountId, Hash>> {
+    //         GeodeModule::select_geodes(state, selector)
+    //     }
+
+    //     fn geode_history(geode: AccountId) -> Vec<StateTransition> {
+    //         GeodeModule::geode_history(geode)
+    //     }
+
+    //     fn geodes_of_provider(provider: AccountId) -> Vec<Geode<AccountId, Hash>> {
+    //         GeodeModule::geodes_of_provider(provider)
+    //     }
     // }
 
     // impl apis::TransferApi<Block> for Runtime {
@@ -1435,6 +1526,11 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_balances, Balances);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+            // add_benchmark!(params, batches, pallet_attestor, AttestorModule);
+            // add_benchmark!(params, batches, pallet_enclave, EnclaveModule);
+            // add_benchmark!(params, batches, pallet_geode, GeodeModule);
+            // add_benchmark!(params, batches, pallet_liveness, LivenessModule);
+            // add_benchmark!(params, batches, pallet_order, OrderModule);
 
             if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
             Ok(batches)
@@ -0,0 +1,33 @@
+#!/bin/bash
+
+# Script for regenerating the weights.rs of the automata pallets from their benchmarks.
+# It has to run on the reference hardware, against a runtime which includes the pallets and
+# their `add_benchmark!` lines.
+#
+# Usage: ./scripts/benchmark-weights.sh [pallet...], e.g. ./scripts/benchmark-weights.sh geode
+
+set -e
+
+PROJECT_ROOT=`git rev-parse --show-toplevel`
+cd $PROJECT_ROOT
+
+PALLETS=("$@")
+if [ ${#PALLETS[@]} -eq 0 ]; then
+  PALLETS=(attestor enclave geode liveness order)
+fi
+
+cargo build --release --features runtime-benchmarks -p automata
+
+for pallet in "${PALLETS[@]}"; do
+  ./target/release/automata benchmark \
+    --chain dev \
+    --execution wasm \
+    --wasm-execution compiled \
+    --pallet "pallet_${pallet}" \
+    --extrinsic '*' \
+    --steps 50 \
+    --repeat 20 \
+    --heap-pages 4096 \
+    --template ./.maintain/frame-weight-template.hbs \
+    --output "./pallets/${pallet}/src/weights.rs"
+done